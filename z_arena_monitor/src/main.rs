@@ -0,0 +1,319 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use clap::Parser;
+use console::{style, Term};
+use serde_json::{json, Value};
+use tokio::time::Instant;
+use zenoh::key_expr::KeyExpr;
+use zenoh_arena::{ArenaObserver, LinkTraffic, ObservedNode, ObservedRole, ProbedHost, RosterChange, SessionExt};
+
+/// z_arena_monitor - Live view of a Zenoh Arena
+#[derive(Parser, Debug)]
+#[command(author, version, about, long_about = None)]
+struct Args {
+    /// Key expression prefix of the observed arena
+    #[arg(short, long, default_value = "zenoh/arena")]
+    prefix: KeyExpr<'static>,
+
+    /// Observe the arenas of all prefixes instead
+    #[arg(short, long)]
+    all_prefixes: bool,
+
+    /// Path to Zenoh config file
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Game identifier to probe hosts with, showing which ones accept clients
+    #[arg(short, long)]
+    game: Option<String>,
+
+    /// Game version to probe hosts with
+    #[arg(long, default_value_t = 0)]
+    game_version: u32,
+
+    /// Refresh interval in milliseconds (also the window of the rates)
+    #[arg(short, long, default_value_t = 1000)]
+    interval_ms: u64,
+
+    /// Number of recent role changes shown (0 hides them)
+    #[arg(long, default_value_t = 10)]
+    history: usize,
+
+    /// Print a JSON snapshot after one interval and exit
+    #[arg(short, long)]
+    json: bool,
+}
+
+/// Message rate on a type of link over the last interval
+struct LinkRate {
+    traffic: LinkTraffic,
+    messages_per_sec: f64,
+    bytes_per_sec: f64,
+}
+
+/// State of the monitor between refreshes
+struct Monitor {
+    observer: ArenaObserver,
+    started: Instant,
+    /// Recent role changes with the time since start, oldest first
+    changes: VecDeque<(Duration, RosterChange)>,
+    history: usize,
+    /// Traffic counters at the previous refresh
+    last_traffic: Vec<LinkTraffic>,
+    last_refresh: Instant,
+    rates: Vec<LinkRate>,
+    /// Hosts that answered the last probe (None without a game to probe with)
+    probed: Option<Vec<ProbedHost>>,
+}
+
+impl Monitor {
+    fn new(observer: ArenaObserver, history: usize) -> Self {
+        let now = Instant::now();
+        Self {
+            observer,
+            started: now,
+            changes: VecDeque::new(),
+            history,
+            last_traffic: Vec::new(),
+            last_refresh: now,
+            rates: Vec::new(),
+            probed: None,
+        }
+    }
+
+    fn record(&mut self, change: RosterChange) {
+        if self.history == 0 {
+            return;
+        }
+        if self.changes.len() == self.history {
+            self.changes.pop_front();
+        }
+        self.changes.push_back((self.started.elapsed(), change));
+    }
+
+    /// Compute the rates since the previous refresh and probe the hosts
+    async fn refresh(&mut self, game: Option<(&str, u32)>) {
+        let elapsed = self.last_refresh.elapsed().as_secs_f64().max(f64::EPSILON);
+        let traffic = self.observer.traffic();
+        self.rates = traffic
+            .iter()
+            .map(|current| {
+                let previous = self.last_traffic.iter().find(|t| t.link == current.link);
+                let messages = current.messages - previous.map_or(0, |t| t.messages);
+                let bytes = current.bytes - previous.map_or(0, |t| t.bytes);
+                LinkRate {
+                    traffic: *current,
+                    messages_per_sec: messages as f64 / elapsed,
+                    bytes_per_sec: bytes as f64 / elapsed,
+                }
+            })
+            .collect();
+        self.last_traffic = traffic;
+        self.last_refresh = Instant::now();
+
+        if let Some((game_id, game_version)) = game {
+            match self.observer.probe_hosts(game_id, game_version).await {
+                Ok(probed) => self.probed = Some(probed),
+                Err(e) => eprintln!("Failed to probe hosts: {}", e),
+            }
+        }
+    }
+
+    /// Capacity of a host as far as it is known
+    fn capacity(&self, host: &ObservedNode) -> Option<Option<u32>> {
+        self.probed.as_ref().map(|probed| {
+            probed
+                .iter()
+                .find(|probed| probed.prefix == host.prefix && probed.id == host.id)
+                .map(|probed| probed.players)
+        })
+    }
+
+    fn render(&self, term: &Term) -> std::io::Result<()> {
+        let roster = self.observer.roster();
+        term.clear_screen()?;
+        term.write_line(&format!(
+            "{} - {} node(s), up {}s",
+            style("=== z_arena_monitor ===").bold(),
+            roster.nodes.len(),
+            self.started.elapsed().as_secs()
+        ))?;
+
+        term.write_line("")?;
+        term.write_line(&style("Hosts").bold().to_string())?;
+        for host in roster.hosts() {
+            let clients: Vec<&ObservedNode> = roster.clients_of(host).collect();
+            let capacity = match self.capacity(host) {
+                None => String::new(),
+                Some(Some(players)) => format!(", open ({} player(s) advertised)", players),
+                Some(None) => ", full or running another game".to_string(),
+            };
+            term.write_line(&format!("  {} [{}] {} client(s){}", host.id, host.prefix, clients.len(), capacity))?;
+            for client in clients {
+                match &client.room {
+                    Some(room) => term.write_line(&format!("    - {} (room {})", client.id, room))?,
+                    None => term.write_line(&format!("    - {}", client.id))?,
+                }
+            }
+        }
+
+        term.write_line("")?;
+        term.write_line(&style("Other nodes").bold().to_string())?;
+        for node in roster.nodes.iter().filter(|node| node.role != ObservedRole::Host) {
+            let host = match (&node.role, &node.host) {
+                (ObservedRole::Client, Some(host)) => format!(" of {}", host),
+                (ObservedRole::Client, None) => " of unknown host".to_string(),
+                _ => String::new(),
+            };
+            term.write_line(&format!("  {} [{}] {}{}", node.id, node.prefix, node.role, host))?;
+        }
+
+        term.write_line("")?;
+        term.write_line(&style("Message rates").bold().to_string())?;
+        for rate in &self.rates {
+            term.write_line(&format!(
+                "  {:<10} {:>8.1} msg/s {:>10.1} B/s  (total {} msg)",
+                rate.traffic.link.as_str(),
+                rate.messages_per_sec,
+                rate.bytes_per_sec,
+                rate.traffic.messages
+            ))?;
+        }
+
+        term.write_line("")?;
+        term.write_line(&style("Recent role changes").bold().to_string())?;
+        for (at, change) in self.changes.iter().rev() {
+            term.write_line(&format!("  {:>7.1}s {}", at.as_secs_f64(), describe(change)))?;
+        }
+        Ok(())
+    }
+
+    fn snapshot(&self) -> Value {
+        let roster = self.observer.roster();
+        let nodes: Vec<Value> = roster.nodes.iter().map(node_json).collect();
+        let hosts: Vec<Value> = roster
+            .hosts()
+            .map(|host| {
+                let clients: Vec<String> = roster.clients_of(host).map(|client| client.id.as_str().to_string()).collect();
+                let capacity = self.capacity(host);
+                json!({
+                    "prefix": host.prefix.as_str(),
+                    "id": host.id.as_str(),
+                    "clients": clients,
+                    "accepting": capacity.map(|players| players.is_some()),
+                    "advertised_players": capacity.flatten(),
+                })
+            })
+            .collect();
+        let traffic: Vec<Value> = self
+            .rates
+            .iter()
+            .map(|rate| {
+                json!({
+                    "link": rate.traffic.link.as_str(),
+                    "messages": rate.traffic.messages,
+                    "bytes": rate.traffic.bytes,
+                    "messages_per_sec": rate.messages_per_sec,
+                    "bytes_per_sec": rate.bytes_per_sec,
+                })
+            })
+            .collect();
+        let changes: Vec<Value> = self
+            .changes
+            .iter()
+            .map(|(at, change)| {
+                let (kind, node) = match change {
+                    RosterChange::Joined(node) => ("joined", node),
+                    RosterChange::Updated(node) => ("updated", node),
+                    RosterChange::Left(node) => ("left", node),
+                };
+                json!({ "at_ms": at.as_millis() as u64, "change": kind, "node": node_json(node) })
+            })
+            .collect();
+        json!({ "nodes": nodes, "hosts": hosts, "traffic": traffic, "changes": changes })
+    }
+}
+
+fn node_json(node: &ObservedNode) -> Value {
+    json!({
+        "prefix": node.prefix.as_str(),
+        "id": node.id.as_str(),
+        "role": node.role.to_string(),
+        "room": node.room.as_ref().map(|room| room.as_str()),
+        "host": node.host.as_ref().map(|host| host.as_str()),
+    })
+}
+
+fn describe(change: &RosterChange) -> String {
+    match change {
+        RosterChange::Joined(node) => format!("{} joined as {}", node.id, node.role),
+        RosterChange::Updated(node) => match &node.host {
+            Some(host) => format!("{} is now {} of {}", node.id, node.role, host),
+            None => format!("{} is now {}", node.id, node.role),
+        },
+        RosterChange::Left(node) => format!("{} left", node.id),
+    }
+}
+
+#[tokio::main(flavor = "multi_thread", worker_threads = 1)]
+async fn main() -> anyhow::Result<()> {
+    // Parse command line arguments
+    let args = Args::parse();
+
+    // Initialize tracing
+    tracing_subscriber::fmt::init();
+
+    // Create zenoh config
+    let zenoh_config = match &args.config {
+        Some(config_path) => zenoh::Config::from_file(config_path)
+            .map_err(|e| anyhow::anyhow!("Failed to load config file: {}", e))?,
+        None => zenoh::Config::default(),
+    };
+
+    // Create zenoh session
+    let session = zenoh::open(zenoh_config)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to open zenoh session: {}", e))?;
+
+    // Observe the arena, counting the messages of each link type
+    let mut observer_builder = session.declare_arena_observer().count_traffic(true);
+    observer_builder = match args.all_prefixes {
+        true => observer_builder.all_prefixes(),
+        false => observer_builder.prefix(args.prefix.clone()),
+    };
+    let observer = observer_builder.await.context("Failed to declare arena observer")?;
+
+    let mut monitor = Monitor::new(observer, args.history);
+    let game = args.game.as_deref().map(|game_id| (game_id, args.game_version));
+    let interval = Duration::from_millis(args.interval_ms);
+
+    if args.json {
+        // Count messages during one interval, then print the snapshot
+        let deadline = Instant::now() + interval;
+        while let Ok(change) = tokio::time::timeout_at(deadline, monitor.observer.next_change()).await {
+            monitor.record(change?);
+        }
+        monitor.refresh(game).await;
+        println!("{}", serde_json::to_string_pretty(&monitor.snapshot())?);
+        return Ok(());
+    }
+
+    let term = Term::stdout();
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            change = monitor.observer.next_change() => monitor.record(change?),
+            _ = ticker.tick() => {
+                monitor.refresh(game).await;
+                monitor.render(&term)?;
+            }
+            _ = tokio::signal::ctrl_c() => break,
+        }
+    }
+
+    println!("Goodbye!");
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use zenoh_arena::{GameEngine, NodeId};
+use zenoh_arena::{EngineExit, EngineThread, GameEngine, NodeId, PlayerSlot, ZenohCodec};
 
 /// Action type - Bonjour increments counter, Bonsoir decrements it
 #[derive(Debug, Clone)]
@@ -34,20 +34,22 @@ impl std::fmt::Display for BonjourState {
 }
 
 /// Game engine that maintains a counter and modifies it based on actions
-pub struct BonjourEngine;
+pub struct BonjourEngine {
+    thread: EngineThread,
+}
 
 impl BonjourEngine {
     pub fn new(
         _host_id: NodeId,
-        input_rx: flume::Receiver<(NodeId, BonjourAction)>,
+        input_rx: flume::Receiver<(NodeId, PlayerSlot, BonjourAction)>,
         output_tx: flume::Sender<BonjourState>,
         initial_state: Option<BonjourState>,
     ) -> Self {
         let mut state = initial_state.unwrap_or_default();
         
         // Spawn a task to process actions
-        std::thread::spawn(move || {
-            while let Ok((_node_id, action)) = input_rx.recv() {
+        let thread = EngineThread::spawn(move || {
+            while let Ok((_node_id, _slot, action)) = input_rx.recv() {
                 // Update bonjours counter based on action type
                 match action {
                     BonjourAction::Bonjour => state.bonjours += 1,
@@ -57,17 +59,26 @@ impl BonjourEngine {
             }
         });
 
-        Self
+        Self { thread }
     }
 }
 
 impl GameEngine for BonjourEngine {
     type Action = BonjourAction;
     type State = BonjourState;
+    type Event = ();
+    type Codec = ZenohCodec;
+
+    const GAME_ID: &'static str = "z_bonjour";
+    const GAME_VERSION: u32 = 1;
 
     fn max_clients(&self) -> Option<usize> {
         Some(2)
     }
+
+    async fn exited(&mut self) -> EngineExit {
+        self.thread.exit().await
+    }
 }
 
 // Implement zenoh-ext serialization for BonjourAction
@@ -123,12 +134,12 @@ mod tests {
         let _engine = BonjourEngine::new(host_id, input_rx, output_tx, None);
         
         // Send first Bonjour action
-        input_tx.send((NodeId::generate(), BonjourAction::Bonjour)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonjour)).unwrap();
         let state1 = output_rx.recv().unwrap();
         assert_eq!(state1.bonjours, 1);
         
         // Send second Bonjour action
-        input_tx.send((NodeId::generate(), BonjourAction::Bonjour)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonjour)).unwrap();
         let state2 = output_rx.recv().unwrap();
         assert_eq!(state2.bonjours, 2);
     }
@@ -144,12 +155,12 @@ mod tests {
         let _engine = BonjourEngine::new(host_id, input_rx, output_tx, None);
         
         // Send Bonsoir action
-        input_tx.send((NodeId::generate(), BonjourAction::Bonsoir)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonsoir)).unwrap();
         let state1 = output_rx.recv().unwrap();
         assert_eq!(state1.bonjours, -1);
         
         // Send another Bonsoir action
-        input_tx.send((NodeId::generate(), BonjourAction::Bonsoir)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonsoir)).unwrap();
         let state2 = output_rx.recv().unwrap();
         assert_eq!(state2.bonjours, -2);
     }
@@ -165,27 +176,27 @@ mod tests {
         let _engine = BonjourEngine::new(host_id, input_rx, output_tx, None);
         
         // Bonjour +1
-        input_tx.send((NodeId::generate(), BonjourAction::Bonjour)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonjour)).unwrap();
         let state = output_rx.recv().unwrap();
         assert_eq!(state.bonjours, 1);
         
         // Bonjour +1
-        input_tx.send((NodeId::generate(), BonjourAction::Bonjour)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonjour)).unwrap();
         let state = output_rx.recv().unwrap();
         assert_eq!(state.bonjours, 2);
         
         // Bonsoir -1
-        input_tx.send((NodeId::generate(), BonjourAction::Bonsoir)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonsoir)).unwrap();
         let state = output_rx.recv().unwrap();
         assert_eq!(state.bonjours, 1);
         
         // Bonsoir -1
-        input_tx.send((NodeId::generate(), BonjourAction::Bonsoir)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonsoir)).unwrap();
         let state = output_rx.recv().unwrap();
         assert_eq!(state.bonjours, 0);
         
         // Bonsoir -1
-        input_tx.send((NodeId::generate(), BonjourAction::Bonsoir)).unwrap();
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), BonjourAction::Bonsoir)).unwrap();
         let state = output_rx.recv().unwrap();
         assert_eq!(state.bonjours, -1);
     }
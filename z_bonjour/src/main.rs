@@ -142,6 +142,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             StepResult::GameState(state) => {
                 println!("{}: new game state {}", node.id(), state);
             }
+            StepResult::RoomState(room, state) => {
+                println!("{}: new game state in room {} {}", node.id(), room, state);
+            }
+            StepResult::EngineCrashed(room, reason) => {
+                println!("{}: engine crashed{}: {}", node.id(), room.map(|r| format!(" in room {}", r)).unwrap_or_default(), reason);
+            }
+            StepResult::Chat(message) => {
+                println!("{}: <{}> {}", node.id(), message.sender, message.text);
+            }
+            StepResult::Event(..) => {
+                // The engine emits no events
+            }
             StepResult::Timeout => {
                 println!("{}: {} {}", node.id(), node.state(), node.game_state().unwrap_or_default());
             }
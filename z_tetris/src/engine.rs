@@ -1,28 +1,31 @@
-use zenoh_arena::{GameEngine, NodeId};
+use zenoh_arena::{EngineExit, EngineThread, GameEngine, JsonCodec, NodeId, PlayerSlot};
 use crate::tetris::Action;
 use crate::tetris_pair::{TetrisPair, PlayerSide};
 use crate::tetris::StepResult;
 use crate::state::TetrisPairState;
+use serde::{Deserialize, Serialize};
 use std::time;
 
 /// Tetris action wrapper
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct TetrisAction {
     pub action: Action,
 }
 
 /// Game engine that manages a Tetris game for two players
-pub struct TetrisEngine;
+pub struct TetrisEngine {
+    thread: EngineThread,
+}
 
 impl TetrisEngine {
     pub fn new(
         host_id: NodeId,
-        input_rx: flume::Receiver<(NodeId, TetrisAction)>,
+        input_rx: flume::Receiver<(NodeId, PlayerSlot, TetrisAction)>,
         output_tx: flume::Sender<TetrisPairState>,
         _initial_state: Option<TetrisPairState>,
     ) -> Self {
         // Spawn a background task to process actions
-        std::thread::spawn(move || {
+        let thread = EngineThread::spawn(move || {
             let mut tetris_pair = TetrisPair::new(10, 20);
             // Setup game speed
             let step_delay = time::Duration::from_millis(10);
@@ -33,21 +36,23 @@ impl TetrisEngine {
             // Set player name initially
             tetris_pair.set_player_name(PlayerSide::Player, Some(host_id.to_string()));
             
-            let mut opponent_id: Option<NodeId> = None;
+            let mut opponent_named = false;
             loop {
                 let start = time::Instant::now();
                 
                 // Process all pending actions using try_recv
-                while let Ok((client_id, action)) = input_rx.try_recv() {
-                    // Determine which player this is based on host_id
-                    let player_side = if client_id == host_id {
-                        PlayerSide::Player
-                    } else {
-                        if opponent_id.is_none() {
-                            tetris_pair.set_player_name(PlayerSide::Opponent, Some(client_id.to_string()));
-                            opponent_id = Some(client_id);
+                while let Ok((client_id, slot, action)) = input_rx.try_recv() {
+                    // The host plays in slot 0, its opponent in slot 1
+                    let player_side = match slot.index {
+                        0 => PlayerSide::Player,
+                        1 => {
+                            if !opponent_named {
+                                tetris_pair.set_player_name(PlayerSide::Opponent, Some(client_id.to_string()));
+                                opponent_named = true;
+                            }
+                            PlayerSide::Opponent
                         }
-                        PlayerSide::Opponent
+                        _ => continue,
                     };
 
                     // Add action to the appropriate player
@@ -73,74 +78,32 @@ impl TetrisEngine {
             }
         });
 
-        Self
+        Self { thread }
     }
 }
 
 impl GameEngine for TetrisEngine {
     type Action = TetrisAction;
     type State = TetrisPairState;
+    type Event = ();
+    type Codec = JsonCodec;
+
+    const GAME_ID: &'static str = "z_tetris";
+    const GAME_VERSION: u32 = 1;
 
     fn max_clients(&self) -> Option<usize> {
         Some(1)
     }
-}
-
-// Implement zenoh-ext serialization for TetrisAction
-impl zenoh_ext::Serialize for TetrisAction {
-    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
-        // Serialize action as u8
-        let action_byte = match self.action {
-            Action::MoveLeft => 0u8,
-            Action::MoveRight => 1u8,
-            Action::MoveDown => 2u8,
-            Action::RotateLeft => 3u8,
-            Action::RotateRight => 4u8,
-            Action::Drop => 5u8,
-            Action::BottomRefill => 6u8,
-        };
-        action_byte.serialize(serializer);
-    }
-}
 
-impl zenoh_ext::Deserialize for TetrisAction {
-    fn deserialize(deserializer: &mut zenoh_ext::ZDeserializer) -> Result<Self, zenoh_ext::ZDeserializeError> {
-        let action_byte: u8 = u8::deserialize(deserializer)?;
-        let action = match action_byte {
-            0 => Action::MoveLeft,
-            1 => Action::MoveRight,
-            2 => Action::MoveDown,
-            3 => Action::RotateLeft,
-            4 => Action::RotateRight,
-            5 => Action::Drop,
-            6 => Action::BottomRefill,
-            // Default to MoveDown for any invalid byte
-            _ => Action::MoveDown,
-        };
-        Ok(TetrisAction { action })
-    }
-}
-
-// Implement zenoh-ext serialization for TetrisPairState
-impl zenoh_ext::Serialize for TetrisPairState {
-    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
-        // Use serde_json for complex state serialization
-        let json = serde_json::to_string(self).unwrap();
-        json.serialize(serializer);
-    }
-}
-
-impl zenoh_ext::Deserialize for TetrisPairState {
-    fn deserialize(deserializer: &mut zenoh_ext::ZDeserializer) -> Result<Self, zenoh_ext::ZDeserializeError> {
-        let json: String = String::deserialize(deserializer)?;
-        // Use expect to panic on JSON errors - should not happen with valid data
-        Ok(serde_json::from_str(&json).expect("Failed to deserialize TetrisPairState from JSON"))
+    async fn exited(&mut self) -> EngineExit {
+        self.thread.exit().await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use zenoh_arena::{ArenaError, Codec};
 
     #[test]
     fn test_engine_creation() {
@@ -155,10 +118,10 @@ mod tests {
         let action = TetrisAction { action: Action::MoveLeft };
         
         // Serialize
-        let zbytes = zenoh_ext::z_serialize(&action);
+        let zbytes = JsonCodec::encode(&action).unwrap();
         
         // Deserialize
-        let deserialized: TetrisAction = zenoh_ext::z_deserialize(&zbytes).unwrap();
+        let deserialized: TetrisAction = JsonCodec::decode(&zbytes).unwrap();
         // Check that actions match
         assert!(matches!(deserialized.action, Action::MoveLeft));
     }
@@ -170,10 +133,17 @@ mod tests {
         let state = tetris_pair.get_state();
         
         // Serialize
-        let zbytes = zenoh_ext::z_serialize(&state);
+        let zbytes = JsonCodec::encode(&state).unwrap();
         
         // Deserialize
-        let deserialized: TetrisPairState = zenoh_ext::z_deserialize(&zbytes).unwrap();
+        let deserialized: TetrisPairState = JsonCodec::decode(&zbytes).unwrap();
         assert_eq!(deserialized.player.game_over, state.player.game_over);
     }
+
+    #[test]
+    fn test_state_deserialization_error() {
+        // Malformed payloads are reported as errors instead of panicking
+        let result: Result<TetrisPairState, _> = JsonCodec::decode(&zenoh::bytes::ZBytes::from("{"));
+        assert!(matches!(result, Err(ArenaError::Serialization(_))));
+    }
 }
@@ -5,7 +5,7 @@ use std::vec;
 use z_tetris::engine::{TetrisAction, TetrisEngine};
 use z_tetris::{Action, AnsiTermStyle, GameFieldPair, TermRender, TetrisPairState};
 use zenoh::key_expr::KeyExpr;
-use zenoh_arena::{NodeCommand, NodeState, SessionExt, StepResult};
+use zenoh_arena::{LinkQos, NodeCommand, NodeState, SessionExt, StateRates, StepResult};
 
 /// z_tetris - Zenoh Arena Tetris Game
 #[derive(Parser, Debug)]
@@ -53,7 +53,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut node_builder = session
         .declare_arena_node(TetrisEngine::new)
         .force_host(args.force_host)
-        .step_timeout_break_ms(1000);
+        .step_timeout_break_ms(1000)
+        // The engine ticks every 10 ms: coalesce states to spare the network and the renderer
+        .state_rates(StateRates {
+            publish: Some(50),
+            deliver: Some(60),
+        })?
+        // Keep key presses from queuing behind state snapshots
+        .action_qos(LinkQos::realtime());
 
     // Apply name if provided
     if let Some(name) = args.name.clone() {
@@ -135,9 +142,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     break;
                 }
             }
+            StepResult::RoomState(..) => {
+                // Rooms are not configured for this game
+            }
             StepResult::RoleChanged(_) => {
                 // Role changed - later show status, now unused
             }
+            StepResult::EngineCrashed(_, _) => {
+                // Engine crashed - it is restarted from the last state, keep playing
+            }
+            StepResult::Chat(_) => {
+                // The game has no chat
+            }
+            StepResult::Event(..) => {
+                // The engine emits no events
+            }
             StepResult::Timeout => {
                 // Timeout - no game state change, don't render
             }
@@ -166,11 +185,11 @@ fn format_node_state_message(state: &NodeState) -> Vec<String> {
         NodeState::SearchingHost => {
             output.push("State: Searching Host".to_string());
         },
-        NodeState::Client { host_id } => {
+        NodeState::Client { host_id, .. } => {
             output.push("State: Client".to_string());
             output.push(format!("Host ID: {}", host_id));
         },
-        NodeState::Host { is_accepting, connected_clients } => {
+        NodeState::Host { is_accepting, connected_clients, .. } => {
             output.push("State: Host".to_string());
             output.push(format!("Accepting: {}", if *is_accepting { "Yes" } else { "No" }));
             output.push(format!("Clients: {}", connected_clients.len()));
@@ -0,0 +1,169 @@
+//! Codecs for encoding game actions and states on the wire
+//!
+//! A [`Codec`] turns values into zenoh payloads and back. Every [`GameEngine`](crate::GameEngine)
+//! selects its codec through the `Codec` associated type:
+//!
+//! - [`ZenohCodec`] (always available) uses `zenoh_ext::Serialize`/`Deserialize`
+//! - [`JsonCodec`] (feature `json`), [`BincodeCodec`] (feature `bincode`) and
+//!   [`PostcardCodec`] (feature `postcard`) work with any type deriving serde's
+//!   `Serialize` and `Deserialize`
+//!
+//! Decoding failures are reported as [`ArenaError::Serialization`] instead of panicking.
+
+use zenoh::bytes::ZBytes;
+
+use crate::error::{ArenaError, Result};
+
+/// Encodes and decodes values of type `T` exchanged between nodes
+pub trait Codec<T>: Send + Sync + 'static {
+    /// Encode a value into a payload
+    fn encode(value: &T) -> Result<ZBytes>;
+
+    /// Decode a value from a payload
+    fn decode(payload: &ZBytes) -> Result<T>;
+}
+
+/// Codec based on zenoh-ext serialization
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ZenohCodec;
+
+impl<T> Codec<T> for ZenohCodec
+where
+    T: zenoh_ext::Serialize + zenoh_ext::Deserialize,
+{
+    fn encode(value: &T) -> Result<ZBytes> {
+        Ok(zenoh_ext::z_serialize(value))
+    }
+
+    fn decode(payload: &ZBytes) -> Result<T> {
+        zenoh_ext::z_deserialize(payload)
+            .map_err(|e| ArenaError::Serialization(format!("Failed to deserialize: {}", e)))
+    }
+}
+
+/// Codec encoding serde types as JSON
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "json")]
+impl<T> Codec<T> for JsonCodec
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<ZBytes> {
+        serde_json::to_vec(value)
+            .map(ZBytes::from)
+            .map_err(|e| ArenaError::Serialization(format!("Failed to encode JSON: {}", e)))
+    }
+
+    fn decode(payload: &ZBytes) -> Result<T> {
+        serde_json::from_slice(&payload.to_bytes())
+            .map_err(|e| ArenaError::Serialization(format!("Failed to decode JSON: {}", e)))
+    }
+}
+
+/// Codec encoding serde types with bincode (standard configuration)
+#[cfg(feature = "bincode")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode")]
+impl<T> Codec<T> for BincodeCodec
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<ZBytes> {
+        bincode::serde::encode_to_vec(value, bincode::config::standard())
+            .map(ZBytes::from)
+            .map_err(|e| ArenaError::Serialization(format!("Failed to encode bincode: {}", e)))
+    }
+
+    fn decode(payload: &ZBytes) -> Result<T> {
+        bincode::serde::decode_from_slice(&payload.to_bytes(), bincode::config::standard())
+            .map(|(value, _)| value)
+            .map_err(|e| ArenaError::Serialization(format!("Failed to decode bincode: {}", e)))
+    }
+}
+
+/// Codec encoding serde types with postcard
+#[cfg(feature = "postcard")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PostcardCodec;
+
+#[cfg(feature = "postcard")]
+impl<T> Codec<T> for PostcardCodec
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    fn encode(value: &T) -> Result<ZBytes> {
+        postcard::to_allocvec(value)
+            .map(ZBytes::from)
+            .map_err(|e| ArenaError::Serialization(format!("Failed to encode postcard: {}", e)))
+    }
+
+    fn decode(payload: &ZBytes) -> Result<T> {
+        postcard::from_bytes(&payload.to_bytes())
+            .map_err(|e| ArenaError::Serialization(format!("Failed to decode postcard: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zenoh_codec_roundtrip() {
+        let payload = <ZenohCodec as Codec<String>>::encode(&"hello".to_string()).unwrap();
+        let decoded: String = <ZenohCodec as Codec<String>>::decode(&payload).unwrap();
+        assert_eq!(decoded, "hello");
+    }
+
+    #[test]
+    fn test_zenoh_codec_decode_error() {
+        let result = <ZenohCodec as Codec<u64>>::decode(&ZBytes::from(vec![1u8]));
+        assert!(matches!(result, Err(ArenaError::Serialization(_))));
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Sample {
+        name: String,
+        values: Vec<i32>,
+    }
+
+    #[cfg(feature = "serde")]
+    fn sample() -> Sample {
+        Sample {
+            name: "sample".to_string(),
+            values: vec![1, -2, 3],
+        }
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_json_codec() {
+        let payload = JsonCodec::encode(&sample()).unwrap();
+        assert_eq!(<JsonCodec as Codec<Sample>>::decode(&payload).unwrap(), sample());
+        let result = <JsonCodec as Codec<Sample>>::decode(&ZBytes::from("not json"));
+        assert!(matches!(result, Err(ArenaError::Serialization(_))));
+    }
+
+    #[cfg(feature = "bincode")]
+    #[test]
+    fn test_bincode_codec() {
+        let payload = BincodeCodec::encode(&sample()).unwrap();
+        assert_eq!(<BincodeCodec as Codec<Sample>>::decode(&payload).unwrap(), sample());
+        let result = <BincodeCodec as Codec<Sample>>::decode(&ZBytes::from(vec![0xffu8]));
+        assert!(matches!(result, Err(ArenaError::Serialization(_))));
+    }
+
+    #[cfg(feature = "postcard")]
+    #[test]
+    fn test_postcard_codec() {
+        let payload = PostcardCodec::encode(&sample()).unwrap();
+        assert_eq!(<PostcardCodec as Codec<Sample>>::decode(&payload).unwrap(), sample());
+        let result = <PostcardCodec as Codec<Sample>>::decode(&ZBytes::from(vec![0xffu8]));
+        assert!(matches!(result, Err(ArenaError::Serialization(_))));
+    }
+}
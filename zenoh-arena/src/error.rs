@@ -19,6 +19,14 @@ pub enum ArenaError {
     #[error("Invalid node name: {0}. Must be a valid single-chunk keyexpr (no /, *, $, ?, #, @)")]
     InvalidNodeName(String),
 
+    /// Invalid room name provided
+    #[error("Invalid room name: {0}. Must be a valid single-chunk keyexpr (no /, *, $, ?, #, @)")]
+    InvalidRoomName(String),
+
+    /// Invalid node configuration
+    #[error("Invalid configuration: {0}")]
+    InvalidConfig(String),
+
     /// Invalid keyexpr pattern
     #[error("Invalid keyexpr: {0}")]
     InvalidKeyexpr(String),
@@ -48,6 +56,10 @@ pub enum ArenaError {
     #[error("Not in client mode")]
     NotClient,
 
+    /// Malformed key expression or payload received from a remote node
+    #[error("Malformed input: {0}")]
+    MalformedInput(String),
+
     /// Serialization/deserialization error
     #[error("Serialization error: {0}")]
     Serialization(String),
@@ -16,12 +16,25 @@
 //! - Game state synchronization via pub/sub
 //! - Liveliness tracking for connection monitoring
 //! - Support for custom game engines via trait
+//! - Tick-driven engines run by the library, without threading code in the game
+//! - Async engines spawned on the node's runtime and cancelled with the host state
+//! - Engine crash detection with automatic restart from the last published state
 //! - Zenoh session extension trait for easy node creation
+//! - Pluggable network transport with an in-memory simulated network for testing
+//! - Protocol and game version negotiation during the handshake
+//! - Multiple rooms (engine instances) hosted by a single node
+//! - Numbered player slots and teams, stable across reconnects
+//! - Optional matchmaker node grouping players by rating, region and party
+//! - Selectable wire codecs, including serde-based JSON/bincode/postcard behind cargo features
+//! - Chat between the members of a session, relayed by the host
+//! - Typed engine events delivered reliably to all or some players, apart from the state
+//! - Arena observer listing the nodes, hosts and clients of an arena from their liveliness, with per-link traffic counters
+//! - Optional shared-memory publishing of large states to clients on the same host
 //!
 //! ## Example
 //!
 //! ```rust,no_run
-//! use zenoh_arena::{SessionExt, GameEngine, NodeId, Result};
+//! use zenoh_arena::{SessionExt, GameEngine, NodeId, PlayerSlot, Result, ZenohCodec};
 //!
 //! // Define your game engine
 //! struct MyEngine;
@@ -29,13 +42,13 @@
 //! impl MyEngine {
 //!     fn new(
 //!         _host_id: NodeId,
-//!         input_rx: flume::Receiver<(NodeId, String)>,
+//!         input_rx: flume::Receiver<(NodeId, PlayerSlot, String)>,
 //!         output_tx: flume::Sender<String>,
 //!         _initial_state: Option<String>,
 //!     ) -> Self {
 //!         // Spawn a task to process actions
 //!         std::thread::spawn(move || {
-//!             while let Ok((_node_id, action)) = input_rx.recv() {
+//!             while let Ok((_node_id, _slot, action)) = input_rx.recv() {
 //!                 let state = format!("Processed: {}", action);
 //!                 let _ = output_tx.send(state);
 //!             }
@@ -48,6 +61,8 @@
 //! impl GameEngine for MyEngine {
 //!     type Action = String;
 //!     type State = String;
+//!     type Event = ();
+//!     type Codec = ZenohCodec;
 //!     
 //!     fn max_clients(&self) -> Option<usize> {
 //!         None // Unlimited clients
@@ -71,13 +86,40 @@
 //!     println!("Node ID: {}", node.id());
 //! }
 //! ```
+pub mod codec;
 pub(crate) mod network;
 pub(crate) mod node;
 pub(crate) mod error;
 
 // Re-exports external API
+pub use codec::{Codec, ZenohCodec};
+#[cfg(feature = "bincode")]
+pub use codec::BincodeCodec;
+#[cfg(feature = "json")]
+pub use codec::JsonCodec;
+#[cfg(feature = "postcard")]
+pub use codec::PostcardCodec;
 pub use error::{ArenaError, Result};
-pub use node::game_engine::{EngineFactory, GameEngine};
+pub use node::async_engine::{async_engine, AsyncDriven, AsyncGameEngine};
+pub use node::game_engine::{EngineEvent, EngineExit, EngineFactory, EngineThread, GameEngine};
 pub use node::arena_node::{Node, NodeCommand};
+pub use node::matchmaker::{
+    MatchProposal, MatchRules, Matchmaker, MatchmakerBuilder, QueueEntry, StandardMatchRules,
+};
+pub use node::observer::{
+    ArenaObserver, ArenaObserverBuilder, ArenaRoster, LinkTraffic, ObservedNode, ObservedRole, ProbedHost,
+    RosterChange,
+};
 pub use node::session_ext::{NodeBuilder, SessionExt};
-pub use node::types::{NodeId, NodeInfo, NodeRole, NodeState, StepResult};
\ No newline at end of file
+pub use node::tick_engine::{tick_engine, TickDriven, TickEngine, Ticking};
+pub use network::{
+    ChatMessage, HandshakeInfo, LinkConditions, LinkQos, LinkType, MatchAssignment, MatchTicket, MemoryNetwork, MemorySessionId, MemoryTransport, QueryResponder, Transport,
+    TransportPublisher, TransportQuery, TransportReceiver, TransportReply, TransportSample,
+    TransportToken, MAX_CHAT_LEN, PROTOCOL_VERSION,
+};
+pub use node::types::{
+    ActionBuffering, ActionLimits, ActionViolations, Backpressure, EngineQueue, EngineQueues, MergePolicy, NodeId, NodeInfo, NodeRole, NodeState,
+    PlayerSlot, QueueStats, RestartPolicy, RoomId, RoomInfo, StateRates, StepResult, ViolationPolicy,
+};
+#[cfg(feature = "shared-memory")]
+pub use node::types::SharedMemory;
\ No newline at end of file
@@ -0,0 +1,191 @@
+//! Chat messages exchanged between the members of a session
+//!
+//! ## Chat Protocol
+//!
+//! - A client sends its [`ChatRequest`]s to its host on `<prefix>/chat/<client_id>/<host_id>`
+//! - The host stamps messages with their sender and time and relays them as
+//!   [`ChatMessage`]s: to everybody on `<prefix>/chat/<host_id>/*`, or to a single member
+//!   on `<prefix>/chat/<host_id>/<member_id>`
+//! - Right after connecting, a client asks for the chat history; the host replies with the
+//!   last messages sent to everybody on the client's own link
+//!
+//! Chat links are not scoped to rooms: all the clients of a host share its chat. Payloads
+//! carry the session id of the host like actions and states.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use zenoh_ext::{Deserialize, Serialize};
+
+use crate::node::types::NodeId;
+
+/// Maximum length in bytes of the text of a chat message
+pub const MAX_CHAT_LEN: usize = 1024;
+
+/// Chat message relayed by a host to the members of its session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatMessage {
+    /// Member that sent the message
+    pub sender: NodeId,
+    /// Member the message was sent to (None for everybody)
+    pub recipient: Option<NodeId>,
+    /// Time the host relayed the message
+    pub timestamp: SystemTime,
+    /// Text of the message
+    pub text: String,
+}
+
+/// Request sent by a client to the chat of its host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatRequest {
+    /// Relay a message to everybody, or to a single member
+    Message {
+        /// Member to send the message to (None for everybody)
+        recipient: Option<NodeId>,
+        /// Text of the message
+        text: String,
+    },
+    /// Send the chat history to the client
+    History,
+}
+
+/// Tag of a [`ChatRequest::Message`] payload
+const REQUEST_MESSAGE: u8 = 0;
+/// Tag of a [`ChatRequest::History`] payload
+const REQUEST_HISTORY: u8 = 1;
+
+fn serialize_recipient(recipient: &Option<NodeId>, serializer: &mut zenoh_ext::ZSerializer) {
+    // Node ids are never empty, the empty string stands for everybody
+    recipient
+        .as_ref()
+        .map(|id| id.as_str().to_string())
+        .unwrap_or_default()
+        .serialize(serializer);
+}
+
+fn deserialize_recipient(
+    deserializer: &mut zenoh_ext::ZDeserializer,
+) -> Result<Option<NodeId>, zenoh_ext::ZDeserializeError> {
+    let name = String::deserialize(deserializer)?;
+    if name.is_empty() {
+        return Ok(None);
+    }
+    NodeId::from_name(name)
+        .map(Some)
+        .map_err(|_| zenoh_ext::ZDeserializeError)
+}
+
+fn deserialize_text(
+    deserializer: &mut zenoh_ext::ZDeserializer,
+) -> Result<String, zenoh_ext::ZDeserializeError> {
+    let text = String::deserialize(deserializer)?;
+    if text.len() > MAX_CHAT_LEN {
+        return Err(zenoh_ext::ZDeserializeError);
+    }
+    Ok(text)
+}
+
+impl zenoh_ext::Serialize for ChatMessage {
+    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
+        self.sender.as_str().to_string().serialize(serializer);
+        serialize_recipient(&self.recipient, serializer);
+        let millis = self
+            .timestamp
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        millis.serialize(serializer);
+        self.text.serialize(serializer);
+    }
+}
+
+impl zenoh_ext::Deserialize for ChatMessage {
+    fn deserialize(
+        deserializer: &mut zenoh_ext::ZDeserializer,
+    ) -> Result<Self, zenoh_ext::ZDeserializeError> {
+        let sender = NodeId::from_name(String::deserialize(deserializer)?)
+            .map_err(|_| zenoh_ext::ZDeserializeError)?;
+        let recipient = deserialize_recipient(deserializer)?;
+        let millis = u64::deserialize(deserializer)?;
+        let text = deserialize_text(deserializer)?;
+        Ok(Self {
+            sender,
+            recipient,
+            timestamp: UNIX_EPOCH + Duration::from_millis(millis),
+            text,
+        })
+    }
+}
+
+impl zenoh_ext::Serialize for ChatRequest {
+    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
+        match self {
+            ChatRequest::Message { recipient, text } => {
+                REQUEST_MESSAGE.serialize(serializer);
+                serialize_recipient(recipient, serializer);
+                text.serialize(serializer);
+            }
+            ChatRequest::History => REQUEST_HISTORY.serialize(serializer),
+        }
+    }
+}
+
+impl zenoh_ext::Deserialize for ChatRequest {
+    fn deserialize(
+        deserializer: &mut zenoh_ext::ZDeserializer,
+    ) -> Result<Self, zenoh_ext::ZDeserializeError> {
+        match u8::deserialize(deserializer)? {
+            REQUEST_MESSAGE => {
+                let recipient = deserialize_recipient(deserializer)?;
+                let text = deserialize_text(deserializer)?;
+                Ok(ChatRequest::Message { recipient, text })
+            }
+            REQUEST_HISTORY => Ok(ChatRequest::History),
+            _ => Err(zenoh_ext::ZDeserializeError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_roundtrip() {
+        let message = ChatMessage {
+            sender: NodeId::from_name("alpha".to_string()).unwrap(),
+            recipient: Some(NodeId::from_name("bravo".to_string()).unwrap()),
+            timestamp: UNIX_EPOCH + Duration::from_millis(1_700_000_000_123),
+            text: "gg".to_string(),
+        };
+        let decoded: ChatMessage = zenoh_ext::z_deserialize(&zenoh_ext::z_serialize(&message)).unwrap();
+        assert_eq!(decoded, message);
+    }
+
+    #[test]
+    fn test_request_roundtrip() {
+        for request in [
+            ChatRequest::Message {
+                recipient: None,
+                text: "hello".to_string(),
+            },
+            ChatRequest::History,
+        ] {
+            let decoded: ChatRequest = zenoh_ext::z_deserialize(&zenoh_ext::z_serialize(&request)).unwrap();
+            assert_eq!(decoded, request);
+        }
+    }
+
+    #[test]
+    fn test_request_rejects_invalid_input() {
+        let too_long = ChatRequest::Message {
+            recipient: None,
+            text: "x".repeat(MAX_CHAT_LEN + 1),
+        };
+        assert!(zenoh_ext::z_deserialize::<ChatRequest>(&zenoh_ext::z_serialize(&too_long)).is_err());
+
+        let glob_recipient = zenoh_ext::z_serialize(&(REQUEST_MESSAGE, "*".to_string(), "hi".to_string()));
+        assert!(zenoh_ext::z_deserialize::<ChatRequest>(&glob_recipient).is_err());
+
+        assert!(zenoh_ext::z_deserialize::<ChatRequest>(&zenoh_ext::z_serialize(&7u8)).is_err());
+    }
+}
@@ -0,0 +1,316 @@
+//! Version information exchanged during the handshake
+//!
+//! Every handshake query and discovery reply carries a [`HandshakeInfo`] payload with the
+//! zenoh-arena protocol version and the game identifier and version declared by the engine.
+//! Hosts reject clients whose info does not match their own, and clients skip hosts
+//! advertising incompatible info during discovery.
+//!
+//! The info also carries a room: the room requested by a client in its connection
+//! query, and the room assigned by the host in its reply. The reply also carries the
+//! player slot assigned to the client.
+//!
+//! Discovery replies carry the number of clients of the host, which clients and other
+//! hosts use to rank hosts.
+//!
+//! Host replies also carry the session id of the host: a random id drawn each time a node
+//! starts hosting. Actions and states of the connection are tagged with it, so that
+//! traffic of an earlier hosting session is ignored.
+
+use crate::error::{ArenaError, Result};
+use crate::node::types::{PlayerSlot, RoomId};
+use zenoh::bytes::ZBytes;
+use zenoh_ext::{ZDeserializeError, ZDeserializer};
+
+/// Version of the zenoh-arena wire protocol
+///
+/// Incremented whenever the handshake or link payload layout changes incompatibly.
+pub const PROTOCOL_VERSION: u32 = 5;
+
+/// Maximum length in bytes of a string in a handshake payload
+const MAX_STRING_LEN: usize = 256;
+
+/// Deserialize a string encoded like zenoh-ext does, bounding its length first
+///
+/// The length prefix is a LEB128 varint.
+fn deserialize_string(deserializer: &mut ZDeserializer) -> Result<String> {
+    let malformed = |e: ZDeserializeError| {
+        ArenaError::MalformedInput(format!("Failed to deserialize handshake info: {}", e))
+    };
+    let mut len: u64 = 0;
+    for shift in (0..64).step_by(7) {
+        let byte: u8 = deserializer.deserialize().map_err(malformed)?;
+        len |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        if shift >= 63 {
+            return Err(ArenaError::MalformedInput("Handshake string length overflows".to_string()));
+        }
+    }
+    if len > MAX_STRING_LEN as u64 {
+        return Err(ArenaError::MalformedInput(format!(
+            "Handshake string of {} bytes exceeds {} bytes",
+            len, MAX_STRING_LEN
+        )));
+    }
+    let bytes = (0..len)
+        .map(|_| deserializer.deserialize::<u8>())
+        .collect::<std::result::Result<Vec<u8>, _>>()
+        .map_err(malformed)?;
+    String::from_utf8(bytes)
+        .map_err(|e| ArenaError::MalformedInput(format!("Invalid handshake string: {}", e)))
+}
+
+/// Protocol and game version information of a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandshakeInfo {
+    /// zenoh-arena protocol version
+    pub protocol_version: u32,
+    /// Game identifier declared by the engine
+    pub game_id: String,
+    /// Game version declared by the engine
+    pub game_version: u32,
+    /// Requested (client) or assigned (host) room, None if unspecified
+    pub room: Option<RoomId>,
+    /// Player slot assigned by the host, None in client requests
+    pub slot: Option<PlayerSlot>,
+    /// Number of clients connected to the host (discovery replies only)
+    pub players: u32,
+    /// Session id of the host, 0 in client requests
+    pub session: u64,
+}
+
+impl HandshakeInfo {
+    /// Create handshake info for the current protocol version
+    pub fn new(game_id: impl Into<String>, game_version: u32) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            game_id: game_id.into(),
+            game_version,
+            room: None,
+            slot: None,
+            players: 0,
+            session: 0,
+        }
+    }
+
+    /// Set the requested or assigned room
+    pub fn with_room(mut self, room: Option<RoomId>) -> Self {
+        self.room = room;
+        self
+    }
+
+    /// Set the assigned player slot
+    pub fn with_slot(mut self, slot: Option<PlayerSlot>) -> Self {
+        self.slot = slot;
+        self
+    }
+
+    /// Set the number of clients connected to the host
+    pub fn with_players(mut self, players: u32) -> Self {
+        self.players = players;
+        self
+    }
+
+    /// Set the session id of the host
+    pub fn with_session(mut self, session: u64) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// Check whether a remote node can join this one
+    ///
+    /// Returns a human readable reason if the remote info is incompatible.
+    /// The room, slot, players and session are not part of the compatibility check.
+    pub fn check_compatible(&self, remote: &HandshakeInfo) -> std::result::Result<(), String> {
+        if remote.protocol_version != self.protocol_version {
+            return Err(format!(
+                "Protocol version mismatch: expected {}, found {}",
+                self.protocol_version, remote.protocol_version
+            ));
+        }
+        if remote.game_id != self.game_id {
+            return Err(format!(
+                "Game mismatch: expected '{}', found '{}'",
+                self.game_id, remote.game_id
+            ));
+        }
+        if remote.game_version != self.game_version {
+            return Err(format!(
+                "Game version mismatch for '{}': expected {}, found {}",
+                self.game_id, self.game_version, remote.game_version
+            ));
+        }
+        Ok(())
+    }
+
+    /// Encode the info into a handshake payload
+    pub fn to_payload(&self) -> ZBytes {
+        let room = self.room.as_ref().map(RoomId::as_str).unwrap_or_default();
+        let (has_slot, slot_index, slot_team) = match self.slot {
+            Some(slot) => (true, slot.index, slot.team),
+            None => (false, 0, 0),
+        };
+        zenoh_ext::z_serialize(&(
+            self.protocol_version,
+            &self.game_id,
+            self.game_version,
+            room,
+            has_slot,
+            slot_index,
+            slot_team,
+            self.players,
+            self.session,
+        ))
+    }
+
+    /// Decode the info from a handshake payload
+    ///
+    /// Fields are decoded one by one, and the length of strings is checked before
+    /// anything is allocated, so that a forged length cannot exhaust memory.
+    pub fn from_payload(payload: &ZBytes) -> Result<Self> {
+        let malformed = |e: ZDeserializeError| {
+            ArenaError::MalformedInput(format!("Failed to deserialize handshake info: {}", e))
+        };
+        let mut deserializer = ZDeserializer::new(payload);
+        let protocol_version: u32 = deserializer.deserialize().map_err(malformed)?;
+        let game_id = deserialize_string(&mut deserializer)?;
+        let game_version: u32 = deserializer.deserialize().map_err(malformed)?;
+        let room = deserialize_string(&mut deserializer)?;
+        let has_slot: bool = deserializer.deserialize().map_err(malformed)?;
+        let slot_index: u32 = deserializer.deserialize().map_err(malformed)?;
+        let slot_team: u32 = deserializer.deserialize().map_err(malformed)?;
+        let players: u32 = deserializer.deserialize().map_err(malformed)?;
+        let session: u64 = deserializer.deserialize().map_err(malformed)?;
+        if !deserializer.done() {
+            return Err(ArenaError::MalformedInput(
+                "Trailing bytes after handshake info".to_string(),
+            ));
+        }
+        let room = if room.is_empty() {
+            None
+        } else {
+            Some(RoomId::new(room).map_err(|e| ArenaError::MalformedInput(format!("Invalid handshake room: {}", e)))?)
+        };
+        let slot = has_slot.then_some(PlayerSlot {
+            index: slot_index,
+            team: slot_team,
+        });
+        Ok(Self {
+            protocol_version,
+            game_id,
+            game_version,
+            room,
+            slot,
+            players,
+            session,
+        })
+    }
+
+    /// Decode the info from an optional payload
+    ///
+    /// Missing or malformed payloads are reported as [`ArenaError::MalformedInput`].
+    pub(crate) fn decode(payload: Option<&ZBytes>) -> Result<Self> {
+        let payload = payload.ok_or_else(|| ArenaError::MalformedInput("Missing handshake info".to_string()))?;
+        Self::from_payload(payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let info = HandshakeInfo::new("tetris", 3);
+        let decoded = HandshakeInfo::from_payload(&info.to_payload()).unwrap();
+        assert_eq!(decoded, info);
+        assert_eq!(decoded.protocol_version, PROTOCOL_VERSION);
+
+        let info = info.with_room(Some(RoomId::new("lobby").unwrap()));
+        let decoded = HandshakeInfo::from_payload(&info.to_payload()).unwrap();
+        assert_eq!(decoded.room, Some(RoomId::new("lobby").unwrap()));
+
+        let info = info.with_slot(Some(PlayerSlot::new(3, 2)));
+        let decoded = HandshakeInfo::from_payload(&info.to_payload()).unwrap();
+        assert_eq!(decoded.slot, Some(PlayerSlot { index: 3, team: 1 }));
+
+        let info = info.with_players(5);
+        let decoded = HandshakeInfo::from_payload(&info.to_payload()).unwrap();
+        assert_eq!(decoded.players, 5);
+
+        let info = info.with_session(u64::MAX);
+        let decoded = HandshakeInfo::from_payload(&info.to_payload()).unwrap();
+        assert_eq!(decoded.session, u64::MAX);
+    }
+
+    #[test]
+    fn test_check_compatible() {
+        let info = HandshakeInfo::new("tetris", 3);
+        assert!(info.check_compatible(&HandshakeInfo::new("tetris", 3)).is_ok());
+        let in_room = HandshakeInfo::new("tetris", 3).with_room(Some(RoomId::new("r1").unwrap()));
+        assert!(info.check_compatible(&in_room).is_ok());
+
+        let reason = info.check_compatible(&HandshakeInfo::new("bonjour", 3)).unwrap_err();
+        assert!(reason.contains("Game mismatch"));
+
+        let reason = info.check_compatible(&HandshakeInfo::new("tetris", 4)).unwrap_err();
+        assert!(reason.contains("Game version mismatch"));
+
+        let mut other = HandshakeInfo::new("tetris", 3);
+        other.protocol_version += 1;
+        let reason = info.check_compatible(&other).unwrap_err();
+        assert!(reason.contains("Protocol version mismatch"));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        let info = HandshakeInfo::new("tetris", 3);
+        assert!(matches!(HandshakeInfo::decode(None), Err(ArenaError::MalformedInput(_))));
+        assert!(matches!(
+            HandshakeInfo::decode(Some(&ZBytes::from(vec![1u8]))),
+            Err(ArenaError::MalformedInput(_))
+        ));
+        assert_eq!(HandshakeInfo::decode(Some(&info.to_payload())).unwrap(), info);
+
+        // A forged game id length is rejected before allocating
+        let mut bytes = zenoh_ext::z_serialize(&PROTOCOL_VERSION).to_bytes().to_vec();
+        bytes.extend([0xff; 9]);
+        bytes.push(0x01);
+        assert!(matches!(
+            HandshakeInfo::decode(Some(&ZBytes::from(bytes))),
+            Err(ArenaError::MalformedInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_random_payloads() {
+        use rand::{Rng, SeedableRng};
+
+        // No payload, however garbled, makes decoding panic
+        let mut rng = rand::rngs::StdRng::seed_from_u64(41);
+        let valid = HandshakeInfo::new("tetris", 3)
+            .with_room(Some(RoomId::new("lobby").unwrap()))
+            .to_payload()
+            .to_bytes()
+            .to_vec();
+        for _ in 0..2000 {
+            let bytes: Vec<u8> = if rng.random_bool(0.5) {
+                let len = rng.random_range(0..64);
+                (0..len).map(|_| rng.random()).collect()
+            } else {
+                // Corrupt and truncate a valid payload
+                let mut bytes = valid.clone();
+                for _ in 0..rng.random_range(1..4) {
+                    let i = rng.random_range(0..bytes.len());
+                    bytes[i] = rng.random();
+                }
+                bytes.truncate(rng.random_range(0..=bytes.len()));
+                bytes
+            };
+            if let Err(e) = HandshakeInfo::decode(Some(&ZBytes::from(bytes))) {
+                assert!(matches!(e, ArenaError::MalformedInput(_)), "{e}");
+            }
+        }
+    }
+}
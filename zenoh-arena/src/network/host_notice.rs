@@ -0,0 +1,79 @@
+//! Notices sent by a host to its clients
+//!
+//! A host publishes [`HostNotice`]s about the connection itself, apart from the game
+//! traffic: to all its clients on `<prefix>/notice/<host_id>/*`, or to a single client on
+//! `<prefix>/notice/<host_id>/<client_id>`. Notice links are not scoped to rooms, and
+//! payloads carry the session id of the host like actions and states.
+
+use crate::node::types::NodeId;
+
+/// Notice sent by a host to its clients
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostNotice {
+    /// The host yields to another host, which the clients should join directly
+    HandOff {
+        /// Host taking over the clients
+        host: NodeId,
+    },
+    /// The host dropped the client for breaking the action limits
+    Kicked,
+}
+
+/// Tag of a [`HostNotice::HandOff`] payload
+const NOTICE_HAND_OFF: u8 = 0;
+/// Tag of a [`HostNotice::Kicked`] payload
+const NOTICE_KICKED: u8 = 1;
+
+impl zenoh_ext::Serialize for HostNotice {
+    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
+        match self {
+            HostNotice::HandOff { host } => {
+                NOTICE_HAND_OFF.serialize(serializer);
+                host.as_str().to_string().serialize(serializer);
+            }
+            HostNotice::Kicked => NOTICE_KICKED.serialize(serializer),
+        }
+    }
+}
+
+impl zenoh_ext::Deserialize for HostNotice {
+    fn deserialize(
+        deserializer: &mut zenoh_ext::ZDeserializer,
+    ) -> Result<Self, zenoh_ext::ZDeserializeError> {
+        match u8::deserialize(deserializer)? {
+            NOTICE_HAND_OFF => {
+                let host = NodeId::from_name(String::deserialize(deserializer)?)
+                    .map_err(|_| zenoh_ext::ZDeserializeError)?;
+                Ok(HostNotice::HandOff { host })
+            }
+            NOTICE_KICKED => Ok(HostNotice::Kicked),
+            _ => Err(zenoh_ext::ZDeserializeError),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notice_roundtrip() {
+        for notice in [
+            HostNotice::HandOff {
+                host: NodeId::from_name("alpha".to_string()).unwrap(),
+            },
+            HostNotice::Kicked,
+        ] {
+            let decoded: HostNotice = zenoh_ext::z_deserialize(&zenoh_ext::z_serialize(&notice)).unwrap();
+            assert_eq!(decoded, notice);
+        }
+    }
+
+    #[test]
+    fn test_notice_rejects_invalid_input() {
+        let glob_host = zenoh_ext::z_serialize(&(NOTICE_HAND_OFF, "*".to_string()));
+        assert!(zenoh_ext::z_deserialize::<HostNotice>(&glob_host).is_err());
+
+        assert!(zenoh_ext::z_deserialize::<HostNotice>(&zenoh_ext::z_serialize(&7u8)).is_err());
+    }
+}
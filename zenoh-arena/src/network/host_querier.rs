@@ -16,6 +16,7 @@
 //! - This acts as a connection confirmation: "I accept your specific connection request"
 //! - If response is Ok, connection is established with that host
 //! - If no host responds positively, client returns None (will become host itself)
+//! - Hosts are tried best ranked first: most clients, then smallest id
 //!
 //! ## Queryable Implementation (Future)
 //!
@@ -37,16 +38,103 @@
 //!
 //! This design allows a single queryable to handle both phases efficiently.
 //!
+//! ## Version Negotiation
+//!
+//! Both queries carry the client's [`HandshakeInfo`] as payload. Hosts answer discovery
+//! with their own info, and the client only attempts connections to hosts whose info
+//! matches. Incompatible hosts are skipped, so a node never joins a game it cannot decode.
+//!
+//! The room in the client's info is the requested room (None lets the host choose);
+//! the host's accept reply carries the assigned room and player slot.
+//!
 //! ## Handshake Semantics
 //!
 //! - `node_src` represents the **requesting side** (client)
 //! - `node_dst` represents the **response side** (host)
 
-use crate::error::Result;
+use crate::error::{ArenaError, Result};
+use crate::network::handshake_info::HandshakeInfo;
 use crate::network::keyexpr::{KeyexprLink, LinkType};
-use crate::node::types::NodeId;
+use crate::network::transport::{LinkQos, Transport, TransportReply};
+use crate::node::types::{NodeId, PlayerSlot, RoomId};
 use zenoh::key_expr::KeyExpr;
 
+/// Connection accepted by a host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostConnection {
+    /// ID of the host
+    pub host_id: NodeId,
+    /// Room assigned by the host (None if the host does not serve rooms)
+    pub room: Option<RoomId>,
+    /// Player slot assigned by the host
+    pub slot: PlayerSlot,
+    /// Session id of the host, tagging the traffic of the connection
+    pub session: u64,
+}
+
+/// Host found by discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredHost {
+    /// ID of the host
+    pub host_id: NodeId,
+    /// Number of clients connected to the host
+    pub players: u32,
+}
+
+impl DiscoveredHost {
+    /// Whether this host ranks above the host `host_id` with `players` clients
+    ///
+    /// Hosts with more clients rank higher; ties are broken by the smaller id, so that
+    /// all nodes agree on the ranking.
+    pub fn outranks(&self, host_id: &NodeId, players: u32) -> bool {
+        (self.players, std::cmp::Reverse(self.host_id.as_str()))
+            > (players, std::cmp::Reverse(host_id.as_str()))
+    }
+}
+
+/// Discovery query in progress
+///
+/// Created by [`HostQuerier::start_discovery`]. Replies received so far are kept when
+/// [`finish`](Self::finish) is cancelled, so a discovery can outlive the loop polling it.
+#[derive(Debug)]
+pub struct Discovery {
+    prefix: KeyExpr<'static>,
+    client_id: NodeId,
+    info: HandshakeInfo,
+    replies: flume::Receiver<TransportReply>,
+    hosts: Vec<DiscoveredHost>,
+}
+
+impl Discovery {
+    /// Wait for the query to be finalized
+    ///
+    /// Returns the hosts advertising compatible info, best ranked first (see
+    /// [`DiscoveredHost::outranks`]). Cancel safe.
+    pub async fn finish(&mut self) -> Vec<DiscoveredHost> {
+        // Collect all host IDs from discovery responses
+        while let Ok(reply) = self.replies.recv_async().await {
+            match HostQuerier::parse_discovery_reply(reply, &self.prefix, &self.client_id, &self.info) {
+                Ok(Some(host)) => {
+                    tracing::debug!("Discovered host: {}", host.host_id);
+                    self.hosts.push(host);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    tracing::debug!("Dropping discovery reply: {}", e);
+                }
+            }
+        }
+
+        let mut hosts = std::mem::take(&mut self.hosts);
+        hosts.sort_by(|a, b| {
+            b.players
+                .cmp(&a.players)
+                .then_with(|| a.host_id.as_str().cmp(b.host_id.as_str()))
+        });
+        hosts
+    }
+}
+
 /// Helper for connecting to available hosts
 ///
 /// Implements the two-phase connection protocol:
@@ -56,112 +144,219 @@ use zenoh::key_expr::KeyExpr;
 pub struct HostQuerier;
 
 impl HostQuerier {
+    /// Discover the hosts of the arena
+    ///
+    /// Queries `<prefix>/handshake/<client_id>/*` (specific node_src, glob on node_dst).
+    /// All available hosts respond to this glob pattern. Returns the hosts advertising
+    /// compatible `info`, best ranked first (see [`DiscoveredHost::outranks`]).
+    pub async fn discover(
+        transport: &dyn Transport,
+        prefix: impl Into<KeyExpr<'static>>,
+        client_id: NodeId,
+        info: &HandshakeInfo,
+        qos: LinkQos,
+    ) -> Result<Vec<DiscoveredHost>> {
+        let mut discovery = Self::start_discovery(transport, prefix, client_id, info, qos).await?;
+        Ok(discovery.finish().await)
+    }
+
+    /// Send the discovery query without waiting for the replies
+    ///
+    /// The replies are collected by [`Discovery::finish`], which can be polled across
+    /// several calls.
+    pub async fn start_discovery(
+        transport: &dyn Transport,
+        prefix: impl Into<KeyExpr<'static>>,
+        client_id: NodeId,
+        info: &HandshakeInfo,
+        qos: LinkQos,
+    ) -> Result<Discovery> {
+        let prefix = prefix.into();
+        let discover_keyexpr = KeyexprLink::new(prefix.clone(), LinkType::Handshake, Some(client_id.clone()), None);
+        let discover_keyexpr: KeyExpr = discover_keyexpr.into();
+        let replies = transport.get(discover_keyexpr, Some(info.to_payload()), qos).await?;
+        Ok(Discovery {
+            prefix,
+            client_id,
+            info: info.clone(),
+            replies,
+            hosts: Vec::new(),
+        })
+    }
+
+    /// Parse a discovery reply
+    ///
+    /// Returns None for error replies and hosts with incompatible `info`, and
+    /// [`ArenaError::MalformedInput`] for replies that cannot be parsed or are not
+    /// addressed to `client_id`.
+    fn parse_discovery_reply(
+        reply: TransportReply,
+        prefix: &KeyExpr<'static>,
+        client_id: &NodeId,
+        info: &HandshakeInfo,
+    ) -> Result<Option<DiscoveredHost>> {
+        let sample = match reply {
+            Ok(sample) => sample,
+            Err(e) => {
+                tracing::debug!("Discovery reply error: {}", e.try_to_string().unwrap_or_default());
+                return Ok(None);
+            }
+        };
+        let parsed = KeyexprLink::parse(&sample.key_expr, prefix).map_err(|e| {
+            ArenaError::MalformedInput(format!("Invalid discovery reply keyexpr '{}': {}", sample.key_expr.as_str(), e))
+        })?;
+        let host_id = match (parsed.link_type(), parsed.node_src(), parsed.node_dst()) {
+            (LinkType::Handshake, Some(src), Some(host_id)) if src == client_id => host_id.clone(),
+            _ => {
+                return Err(ArenaError::MalformedInput(format!(
+                    "Unexpected discovery reply keyexpr: {}",
+                    sample.key_expr.as_str()
+                )))
+            }
+        };
+        let host_info = HandshakeInfo::decode(Some(&sample.payload))?;
+        // Skip hosts running an incompatible protocol or game version
+        if let Err(reason) = info.check_compatible(&host_info) {
+            tracing::info!("Skipping incompatible host {}: {}", host_id, reason);
+            return Ok(None);
+        }
+        Ok(Some(DiscoveredHost {
+            host_id,
+            players: host_info.players,
+        }))
+    }
+
     /// Connect to an available host
     ///
     /// Performs two-phase discovery and connection:
     ///
     /// **Phase 1: Host Discovery**
-    /// - Queries `<prefix>/handshake/<client_id>/*` (specific node_src, glob on node_dst)
-    /// - All available hosts respond to this glob pattern
-    /// - Collects all discovered host IDs
+    /// - Discovers the hosts advertising compatible `info` with [`discover`](Self::discover)
     ///
     /// **Phase 2: Connection Establishment**
-    /// - For each discovered host, queries `<prefix>/handshake/<client_id>/<host_id>`
+    /// - For each discovered host, best ranked first, queries `<prefix>/handshake/<client_id>/<host_id>`
     /// - Host confirms it accepts this specific connection request
-    /// - Returns the ID of the first host that accepts the connection
+    /// - Returns the first host that accepts the connection with the assigned room and slot
+    ///
+    /// The `excluded` host, if any, is skipped.
     ///
     /// Returns:
-    /// - `Ok(Some(host_id))` - Successfully connected to a host
+    /// - `Ok(Some(connection))` - Successfully connected to a host
     /// - `Ok(None)` - No hosts available, client should become host
     /// - `Err(_)` - Zenoh query error
     pub async fn connect(
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         prefix: impl Into<KeyExpr<'static>>,
         client_id: NodeId,
-    ) -> Result<Option<NodeId>> {
+        info: &HandshakeInfo,
+        qos: LinkQos,
+        excluded: Option<&NodeId>,
+    ) -> Result<Option<HostConnection>> {
         tracing::debug!("Discovering available hosts...");
 
         let prefix = prefix.into();
 
         // Phase 1: Discover all available hosts
-        // Query: <prefix>/handshake/<client_id>/* (specific node_src, glob on node_dst)
-        // This queries all hosts in the arena, asking them to confirm presence
-        let discover_keyexpr = KeyexprLink::new(prefix.clone(), LinkType::Handshake, Some(client_id.clone()), None);
-        let discover_keyexpr: KeyExpr = discover_keyexpr.into();
-        let discovery_replies = session.get(discover_keyexpr).await?;
-
-        let mut host_ids: Vec<NodeId> = Vec::new();
+        let mut hosts = Self::discover(transport, prefix.clone(), client_id.clone(), info, qos).await?;
+        hosts.retain(|host| Some(&host.host_id) != excluded);
 
-        // Collect all host IDs from discovery responses
-        while let Ok(reply) = discovery_replies.recv_async().await {
-            // Parse the reply to extract host_id from key expression
-            match reply.result() {
-                Ok(sample) => {
-                    let keyexpr = sample.key_expr().clone();
-                    match KeyexprLink::try_from(keyexpr.clone()) {
-                        Ok(parsed) => {
-                            if let Some(host_id) = parsed.node_dst() {
-                                tracing::debug!("Discovered host: {}", host_id);
-                                host_ids.push(host_id.clone());
-                            }
-                        }
-                        Err(e) => {
-                            tracing::debug!("Failed to parse keyexpr {}: {}", keyexpr.as_str(), e);
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::debug!("Discovery reply error: {}", e);
-                }
-            }
-        }
-
-        if host_ids.is_empty() {
+        if hosts.is_empty() {
             tracing::info!("No hosts discovered");
             return Ok(None);
         }
 
         tracing::info!(
             "Discovered {} host(s), attempting connections",
-            host_ids.len()
+            hosts.len()
         );
 
         // Phase 2: Try connecting to each discovered host
         // Query: <prefix>/handshake/<client_id>/<host_id> (specific node_src and node_dst)
         // This requests the specific host to confirm it accepts this client's connection
-        for host_id in host_ids {
-            let connect_keyexpr = KeyexprLink::new(
-                prefix.clone(),
-                LinkType::Handshake,
-                Some(client_id.clone()),
-                Some(host_id.clone()),
-            );
-            let connect_keyexpr: KeyExpr = connect_keyexpr.into();
-
-            match session.get(connect_keyexpr).await {
-                Ok(connection_replies) => {
-                    // Try to receive a positive response
-                    match connection_replies.recv_async().await {
-                        Ok(_reply) => {
-                            // Positive response received, connection established
-                            tracing::info!("Successfully connected to host: {}", host_id);
-                            return Ok(Some(host_id));
-                        }
-                        Err(_) => {
-                            // Host rejected or no response
-                            tracing::debug!("No response from host {}", host_id);
-                            continue;
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::debug!("Connection query to host {} failed: {}", host_id, e);
-                    continue;
-                }
+        for host in hosts {
+            if let Some(connection) =
+                Self::connect_to(transport, prefix.clone(), client_id.clone(), host.host_id, info, qos).await
+            {
+                return Ok(Some(connection));
             }
         }
 
         tracing::info!("No host accepted connection");
         Ok(None)
     }
+
+    /// Connect to a specific host, skipping discovery
+    ///
+    /// Performs only the connection phase of the handshake, querying
+    /// `<prefix>/handshake/<client_id>/<host_id>`. Used when the host is already known,
+    /// e.g. assigned by a matchmaker.
+    ///
+    /// Returns the accepted connection if the host accepted it, or None if it rejected it
+    /// or did not respond.
+    pub async fn connect_to(
+        transport: &dyn Transport,
+        prefix: impl Into<KeyExpr<'static>>,
+        client_id: NodeId,
+        host_id: NodeId,
+        info: &HandshakeInfo,
+        qos: LinkQos,
+    ) -> Option<HostConnection> {
+        let connect_keyexpr = KeyexprLink::new(
+            prefix,
+            LinkType::Handshake,
+            Some(client_id),
+            Some(host_id.clone()),
+        );
+        let connect_keyexpr: KeyExpr = connect_keyexpr.into();
+
+        match transport.get(connect_keyexpr, Some(info.to_payload()), qos).await {
+            Ok(connection_replies) => {
+                // Try to receive a positive response
+                match connection_replies.recv_async().await {
+                    Ok(Ok(reply)) => {
+                        // Positive response received, connection established
+                        let host_info = match HandshakeInfo::decode(Some(&reply.payload)) {
+                            Ok(host_info) => host_info,
+                            Err(e) => {
+                                tracing::debug!("Malformed accept reply from host {}: {}", host_id, e);
+                                return None;
+                            }
+                        };
+                        if let Err(reason) = info.check_compatible(&host_info) {
+                            tracing::debug!("Incompatible accept reply from host {}: {}", host_id, reason);
+                            return None;
+                        }
+                        let Some(slot) = host_info.slot else {
+                            tracing::debug!("Accept reply from host {} without player slot", host_id);
+                            return None;
+                        };
+                        tracing::info!("Successfully connected to host: {} ({})", host_id, slot);
+                        Some(HostConnection {
+                            host_id,
+                            room: host_info.room,
+                            slot,
+                            session: host_info.session,
+                        })
+                    }
+                    Ok(Err(reason)) => {
+                        tracing::debug!(
+                            "Host {} rejected connection: {}",
+                            host_id,
+                            reason.try_to_string().unwrap_or_default()
+                        );
+                        None
+                    }
+                    Err(_) => {
+                        // Host rejected or no response
+                        tracing::debug!("No response from host {}", host_id);
+                        None
+                    }
+                }
+            }
+            Err(e) => {
+                tracing::debug!("Connection query to host {} failed: {}", host_id, e);
+                None
+            }
+        }
+    }
 }
@@ -22,76 +22,102 @@
 //! - If it matches `*/<host_id>` pattern with specific client_id → Connection request (pushed to channel)
 //! - If it matches `*/<host_id>` pattern with glob client_id → Discovery request (replied immediately)
 //!
+//! ## Version Negotiation
+//!
+//! Both query kinds carry the client's [`HandshakeInfo`] as payload. Incompatible
+//! discovery queries are answered with an error reply carrying the reason, compatible
+//! ones with the host's own [`HandshakeInfo`] including its current number of clients
+//! (see [`HostQueryable::set_players`]). Incompatible connection requests are
+//! rejected with the reason and never reach the host handler.
+//!
+//! The room requested by the client is exposed by [`HostRequest::requested_room`], and
+//! the room assigned by the host is sent back in the accept reply.
+//!
 //! ## Handshake Semantics
 //!
 //! - `node_src` represents the **requesting side** (client)
 //! - `node_dst` represents the **response side** (host)
 
-use crate::error::Result;
+use crate::error::{ArenaError, Result};
+use crate::network::handshake_info::HandshakeInfo;
 use crate::network::keyexpr::{KeyexprLink, LinkType};
-use crate::node::types::NodeId;
+use crate::network::transport::{LinkQos, Transport, TransportQuery, TransportReceiver};
+use crate::node::types::{NodeId, PlayerSlot, RoomId};
+use std::sync::atomic::{AtomicU32, Ordering};
+use zenoh::bytes::ZBytes;
 use zenoh::key_expr::KeyExpr;
-use zenoh::query::{Query, Queryable};
 
 /// Request from a client for host to accept connection
 ///
-/// Wraps a Zenoh Query with methods to accept or reject the connection request.
+/// Wraps a transport query with methods to accept or reject the connection request.
 /// The host handler calls either `accept()` or `reject()` to respond to the client.
 #[derive(Debug, Clone)]
 pub struct HostRequest {
-    query: Query,
+    query: TransportQuery,
     client_id: NodeId,
+    /// Host handshake info sent back on accept
+    host_info: HandshakeInfo,
+    /// Room requested by the client
+    requested_room: Option<RoomId>,
 }
 
 impl HostRequest {
-    /// Create a new NodeRequest from a Query and client_id
+    /// Create a new HostRequest from a query on `prefix`, client_id, the host's handshake info and the room requested by the client
     ///
-    /// # Panics
-    ///
-    /// Panics if query keyexpr is not KeyexprLink with Handshake link_type, Some node_dst (host_id), and matching node_src (client_id).
-    pub fn new(query: Query, client_id: NodeId) -> Self {
-        let parsed =
-            KeyexprLink::try_from(query.key_expr().clone()).expect("Invalid KeyexprLink");
-        assert_eq!(
-            parsed.link_type(),
-            LinkType::Handshake,
-            "Expected Handshake link_type in query keyexpr: {}",
-            query.key_expr().as_str()
-        );
-        assert!(
-            parsed.node_dst().is_some(),
-            "Expected specific node_dst in query keyexpr: {}",
-            query.key_expr().as_str()
-        );
-        assert_eq!(
-            parsed.node_src().as_ref().unwrap_or_else(|| panic!(
-                "Expected specific node_src in query keyexpr: {}",
+    /// Returns [`ArenaError::MalformedInput`] unless the query keyexpr is a Handshake
+    /// [`KeyexprLink`] under `prefix` with a specific node_dst (host_id) and node_src equal to `client_id`.
+    pub fn new(
+        query: TransportQuery,
+        prefix: &KeyExpr<'static>,
+        client_id: NodeId,
+        host_info: HandshakeInfo,
+        requested_room: Option<RoomId>,
+    ) -> Result<Self> {
+        let parsed = KeyexprLink::parse(query.key_expr(), prefix).map_err(|e| {
+            ArenaError::MalformedInput(format!("Invalid connection query keyexpr: {}", e))
+        })?;
+        if parsed.link_type() != LinkType::Handshake {
+            return Err(ArenaError::MalformedInput(format!(
+                "Expected Handshake link_type in query keyexpr: {}",
                 query.key_expr().as_str()
-            )),
-            &client_id,
-            "Client ID mismatch: expected '{}', found '{}'",
-            client_id,
-            parsed.node_src().as_ref().unwrap()
-        );
+            )));
+        }
+        if parsed.node_dst().is_none() {
+            return Err(ArenaError::MalformedInput(format!(
+                "Expected specific node_dst in query keyexpr: {}",
+                query.key_expr().as_str()
+            )));
+        }
+        if parsed.node_src().as_ref() != Some(&client_id) {
+            return Err(ArenaError::MalformedInput(format!(
+                "Client ID mismatch: expected '{}' in query keyexpr: {}",
+                client_id,
+                query.key_expr().as_str()
+            )));
+        }
 
-        Self { query, client_id }
+        Ok(Self {
+            query,
+            client_id,
+            host_info,
+            requested_room,
+        })
     }
 
     /// Accept the connection request
     ///
-    /// Sends a positive reply (ok) with empty payload to the querying client.
-    /// This confirms that the host accepts this client's connection.
+    /// Sends a positive reply (ok) with the host's handshake info to the querying client.
+    /// This confirms that the host accepts this client's connection into `room`
+    /// (None if the host does not serve rooms) as player `slot`.
     ///
     /// Returns the client ID.
-    pub async fn accept(self) -> Result<NodeId> {
-        let keyexpr = self.query.key_expr();
+    pub async fn accept(self, room: Option<RoomId>, slot: PlayerSlot) -> Result<NodeId> {
+        let keyexpr = self.query.key_expr().clone();
+        let payload = self.host_info.with_room(room).with_slot(Some(slot)).to_payload();
 
         // Reply to the same keyexpr from the query. This is safe because NodeRequest
         // is only created for connection requests with specific client_id (no globs).
-        self.query
-            .reply(keyexpr, "")
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        self.query.reply(keyexpr, payload).await?;
 
         Ok(self.client_id)
     }
@@ -101,10 +127,7 @@ impl HostRequest {
     /// Sends an error reply to the querying client.
     /// This tells the client this host cannot accept the connection.
     pub async fn reject(self, reason: &str) -> Result<()> {
-        self.query
-            .reply_err(reason)
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        self.query.reply_err(ZBytes::from(reason.to_string())).await?;
 
         Ok(())
     }
@@ -113,6 +136,16 @@ impl HostRequest {
     pub fn client_id(&self) -> &NodeId {
         &self.client_id
     }
+
+    /// Get the transport session the request was sent from, if the transport reports it
+    pub fn source(&self) -> Option<&str> {
+        self.query.source()
+    }
+
+    /// Get the room requested by the client (None lets the host assign one)
+    pub fn requested_room(&self) -> Option<&RoomId> {
+        self.requested_room.as_ref()
+    }
 }
 
 /// Wrapper for host discovery and connection requests
@@ -124,115 +157,170 @@ impl HostRequest {
 ///   → Returns NodeRequest for host to accept/reject
 #[derive(Debug)]
 pub struct HostQueryable {
-    /// The zenoh queryable that receives queries
-    queryable: Queryable<zenoh::handlers::FifoChannelHandler<Query>>,
+    /// The transport queryable that receives queries
+    queryable: TransportReceiver<TransportQuery>,
     /// Node ID for formatting replies
     node_id: NodeId,
     /// Prefix for formatting replies
     prefix: KeyExpr<'static>,
+    /// Version information clients must match
+    info: HandshakeInfo,
+    /// Number of clients advertised in discovery replies
+    players: AtomicU32,
 }
 
 impl HostQueryable {
     /// Declare a new queryable for a host node
     ///
     /// Declares queryable on `<prefix>/handshake/*/<host_id>` pattern.
+    /// Only clients whose handshake payload is compatible with `info` are accepted.
     pub async fn declare(
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         prefix: impl Into<KeyExpr<'static>>,
         node_id: NodeId,
+        info: HandshakeInfo,
+        qos: LinkQos,
     ) -> Result<Self> {
         let prefix = prefix.into();
         // Declare on pattern: <prefix>/handshake/*/<host_id>
         let host_client_keyexpr = KeyexprLink::new(prefix.clone(), LinkType::Handshake, None, Some(node_id.clone()));
         let keyexpr: KeyExpr = host_client_keyexpr.into();
 
-        // Declare queryable without callback
-        let queryable = session
-            .declare_queryable(&keyexpr)
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        let queryable = transport.declare_queryable(keyexpr, qos).await?;
 
         Ok(Self {
             queryable,
             node_id,
             prefix,
+            info,
+            players: AtomicU32::new(0),
         })
     }
 
+    /// Set the number of clients advertised in discovery replies
+    pub fn set_players(&self, players: u32) {
+        self.players.store(players, Ordering::Relaxed);
+    }
+
     /// Wait for and retrieve the next connection request
     ///
     /// Loops receiving queries from the queryable. For each query:
-    /// - If it's a discovery query (glob node_src): replies with ok
-    /// - If it's a connection query (specific node_src): returns HostRequest
+    /// - If it's a discovery query (glob node_dst): replies with ok
+    /// - If it's a connection query (specific node_src and node_dst): returns HostRequest
+    ///
+    /// Queries with incompatible handshake info are answered with an error reply.
+    /// Malformed queries (keyexpr or handshake payload that cannot be parsed, or a
+    /// connection query addressed to another host) are answered with an error reply
+    /// where possible and returned as [`ArenaError::MalformedInput`], so that the host
+    /// can account for them; the queryable can be awaited again afterwards.
     pub async fn expect_connection(&self) -> Result<HostRequest> {
         loop {
             // Receive next query from queryable
-            let query = self.queryable.recv_async().await.map_err(|_| {
-                crate::error::ArenaError::Internal("Queryable channel closed".to_string())
-            })?;
-
-            // Parse the incoming query keyexpr to determine if it's discovery or connection
-            let query_keyexpr = query.key_expr().clone();
-
-            // Try to parse as KeyexprLink to extract node_src and node_dst
-            match KeyexprLink::try_from(query_keyexpr.clone()) {
-                Ok(parsed) => {
-                    if parsed.link_type() != LinkType::Handshake {
-                        tracing::debug!("Invalid link_type: expected Handshake, got {:?}", parsed.link_type());
-                        continue;
+            let query = self.queryable.recv_async().await?;
+            if let Some(request) = self.handle_query(query).await? {
+                return Ok(request);
+            }
+        }
+    }
+
+    /// Answer a discovery query, or turn a connection query into a HostRequest
+    async fn handle_query(&self, query: TransportQuery) -> Result<Option<HostRequest>> {
+        // Parse the incoming query keyexpr to determine if it's discovery or connection
+        let query_keyexpr = query.key_expr().clone();
+        let parsed = KeyexprLink::parse(&query_keyexpr, &self.prefix).map_err(|e| {
+            ArenaError::MalformedInput(format!("Invalid handshake query keyexpr '{}': {}", query_keyexpr.as_str(), e))
+        })?;
+        if parsed.link_type() != LinkType::Handshake {
+            return Err(ArenaError::MalformedInput(format!(
+                "Expected Handshake link_type, got {:?}: {}",
+                parsed.link_type(),
+                query_keyexpr.as_str()
+            )));
+        }
+
+        match (parsed.node_src(), parsed.node_dst()) {
+            (Some(client_id), Some(host_id)) => {
+                if host_id != &self.node_id {
+                    Self::reply_malformed(&query, "Connection request addressed to another host").await;
+                    return Err(ArenaError::MalformedInput(format!(
+                        "Host ID mismatch: expected '{}', found '{}'",
+                        self.node_id, host_id
+                    )));
+                }
+                let client_info = match HandshakeInfo::decode(query.payload()) {
+                    Ok(client_info) => client_info,
+                    Err(e) => {
+                        Self::reply_malformed(&query, &e.to_string()).await;
+                        return Err(e);
+                    }
+                };
+                // Reject clients running an incompatible protocol or game version
+                if let Err(reason) = self.info.check_compatible(&client_info) {
+                    tracing::info!(
+                        "Rejecting incompatible client '{}': {}",
+                        client_id,
+                        reason
+                    );
+                    if let Err(e) = query.reply_err(ZBytes::from(reason)).await {
+                        tracing::debug!("Failed to reject connection query: {}", e);
+                    }
+                    return Ok(None);
+                }
+                // Connection request (specific node_src and node_dst): return it
+                let client_id = client_id.clone();
+                HostRequest::new(query, &self.prefix, client_id, self.info.clone(), client_info.room).map(Some)
+            }
+            (Some(client_id), None) => {
+                // request from specific client_id but glob node_dst - correct discovery case
+                // Trace and reply with ok, confirming presence
+                tracing::debug!(
+                    "Discovery request from node_src '{}' with glob node_dst: {}",
+                    client_id,
+                    query_keyexpr.as_str()
+                );
+                let client_info = match HandshakeInfo::decode(query.payload()) {
+                    Ok(client_info) => client_info,
+                    Err(e) => {
+                        Self::reply_malformed(&query, &e.to_string()).await;
+                        return Err(e);
                     }
-                    
-                    match (parsed.node_src(), parsed.node_dst()) {
-                        (Some(client_id), Some(host_id)) => {
-                            assert_eq!(
-                                host_id, &self.node_id,
-                                "Host ID mismatch: expected '{}', found '{}'",
-                                self.node_id, host_id
-                            );
-                            // Connection request (specific node_src and node_dst): return it
-                            return Ok(HostRequest::new(query, client_id.clone()));
-                        }
-                        (None, Some(host_id)) => {
-                            // ignore invalid case: glob node_src but specific node_dst
-                            tracing::debug!(
-                                "Invalid query with glob node_src but specific node_dst '{}': {}",
-                                host_id,
-                                query_keyexpr.as_str()
-                            );
-                        }
-                        (Some(client_id), None) => {
-                            // request from specific client_id but glob node_dst - correct discovery case
-                            // Trace and reply with ok, confirming presence
-                            tracing::debug!(
-                                "Discovery request from node_src '{}' with glob node_dst: {}",
-                                client_id,
-                                query_keyexpr.as_str()
-                            );
-                            let reply_host_client = KeyexprLink::new(
-                                self.prefix.clone(),
-                                LinkType::Handshake,
-                                Some(client_id.clone()),
-                                Some(self.node_id.clone()),
-                            );
-                            let reply_keyexpr: KeyExpr = reply_host_client.into();
-                            if let Err(e) = query.reply(&reply_keyexpr, "").await {
-                                tracing::debug!("Failed to reply to discovery query: {}", e);
-                            }
-                        }
-                        (None, None) => {
-                            // ignore invalid case: glob node_src and glob node_dst
-                            tracing::debug!(
-                                "Invalid query with glob node_src and glob node_dst: {}",
-                                query_keyexpr.as_str()
-                            );
-                        }
+                };
+                if let Err(reason) = self.info.check_compatible(&client_info) {
+                    tracing::debug!(
+                        "Incompatible discovery request from '{}': {}",
+                        client_id,
+                        reason
+                    );
+                    if let Err(e) = query.reply_err(ZBytes::from(reason)).await {
+                        tracing::debug!("Failed to reply to discovery query: {}", e);
                     }
+                    return Ok(None);
                 }
-                Err(_) => {
-                    // Failed to parse keyexpr, ignore and continue
-                    tracing::debug!("Failed to parse query keyexpr: {}", query_keyexpr.as_str());
+                let reply_host_client = KeyexprLink::new(
+                    self.prefix.clone(),
+                    LinkType::Handshake,
+                    Some(client_id.clone()),
+                    Some(self.node_id.clone()),
+                );
+                let reply_keyexpr: KeyExpr = reply_host_client.into();
+                let info = self.info.clone().with_players(self.players.load(Ordering::Relaxed));
+                if let Err(e) = query.reply(reply_keyexpr, info.to_payload()).await {
+                    tracing::debug!("Failed to reply to discovery query: {}", e);
                 }
+                Ok(None)
             }
+            // Glob node_src: the requesting client is unknown
+            (None, _) => Err(ArenaError::MalformedInput(format!(
+                "Handshake query with glob node_src: {}",
+                query_keyexpr.as_str()
+            ))),
+        }
+    }
+
+    /// Answer a malformed query with an error reply
+    async fn reply_malformed(query: &TransportQuery, reason: &str) {
+        if let Err(e) = query.reply_err(ZBytes::from(reason.to_string())).await {
+            tracing::debug!("Failed to reply to malformed handshake query: {}", e);
         }
     }
 }
@@ -1,9 +1,74 @@
 //! Key expression types for host discovery and connection
 
 use crate::error::ArenaError;
-use crate::node::types::NodeId;
+use crate::node::types::{NodeId, RoomId};
 use zenoh::key_expr::KeyExpr;
 
+/// Keyexpr chunk introducing a room segment: `<prefix>/room/<room_id>/...`
+const ROOM_CHUNK: &str = "room";
+
+/// Format `<prefix>` or `<prefix>/room/<room_id>`
+fn format_prefix(prefix: &KeyExpr<'static>, room: &Option<RoomId>) -> String {
+    match room {
+        Some(room) => format!("{}/{}/{}", prefix.as_str(), ROOM_CHUNK, room.as_str()),
+        None => prefix.as_str().to_string(),
+    }
+}
+
+/// Split leading keyexpr chunks into the prefix and an optional trailing room segment
+///
+/// The room segment is only recognized when a non-empty prefix precedes it. Without
+/// the configured prefix this is a guess: a prefix ending in `room/<x>` reads as a
+/// room, so parse with `KeyexprNode::parse` or `KeyexprLink::parse` when the prefix is known.
+fn parse_prefix(parts: &[&str]) -> Result<(KeyExpr<'static>, Option<RoomId>), ArenaError> {
+    let (prefix_parts, room) = if parts.len() >= 3 && parts[parts.len() - 2] == ROOM_CHUNK {
+        (&parts[..parts.len() - 2], Some(RoomId::new(parts[parts.len() - 1])?))
+    } else {
+        (parts, None)
+    };
+    let prefix = KeyExpr::try_from(prefix_parts.join("/"))?.into_owned();
+    Ok((prefix, room))
+}
+
+/// Split a keyexpr published under a known `prefix` into its optional room and the
+/// `tail_len` chunks that follow the room segment
+///
+/// The room segment is anchored right after `prefix`, so a prefix that itself
+/// contains a `room/<id>` segment is not mistaken for a room.
+fn strip_prefix<'a>(
+    keyexpr: &'a KeyExpr<'_>,
+    prefix: &KeyExpr<'_>,
+    tail_len: usize,
+) -> Result<(Option<RoomId>, Vec<&'a str>), ArenaError> {
+    let invalid = || ArenaError::InvalidKeyexpr(format!(
+        "Keyexpr {} does not match prefix {}",
+        keyexpr.as_str(),
+        prefix.as_str()
+    ));
+    let rest = keyexpr
+        .as_str()
+        .strip_prefix(prefix.as_str())
+        .and_then(|rest| rest.strip_prefix('/'))
+        .ok_or_else(invalid)?;
+    let parts: Vec<&str> = rest.split('/').collect();
+    if parts.len() == tail_len + 2 && parts[0] == ROOM_CHUNK {
+        Ok((Some(RoomId::new(parts[1])?), parts[2..].to_vec()))
+    } else if parts.len() == tail_len {
+        Ok((None, parts))
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Parse a node id chunk, `*` meaning wildcard
+fn parse_node_id(chunk: &str) -> Result<Option<NodeId>, ArenaError> {
+    if chunk == "*" {
+        Ok(None)
+    } else {
+        Ok(Some(NodeId::from_name(chunk.to_string())?))
+    }
+}
+
 /// Node type in the keyexpr
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeType {
@@ -13,6 +78,8 @@ pub enum NodeType {
     Client,
     /// Host node type
     Host,
+    /// Matchmaker node type
+    Matchmaker,
 }
 
 impl NodeType {
@@ -22,6 +89,7 @@ impl NodeType {
             NodeType::Node => "node",
             NodeType::Client => "client",
             NodeType::Host => "host",
+            NodeType::Matchmaker => "matchmaker",
         }
     }
 
@@ -31,6 +99,7 @@ impl NodeType {
             "node" => Ok(NodeType::Node),
             "client" => Ok(NodeType::Client),
             "host" => Ok(NodeType::Host),
+            "matchmaker" => Ok(NodeType::Matchmaker),
             _ => Err(ArenaError::InvalidKeyexpr(format!(
                 "Invalid node type: {}",
                 s
@@ -40,7 +109,7 @@ impl NodeType {
 }
 
 /// Link type for link keyexpr
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum LinkType {
     /// Handshake link type (for discovery and connection)
     Handshake,
@@ -48,6 +117,18 @@ pub enum LinkType {
     Action,
     /// State link type (for query/reply)
     State,
+    /// Matchmaking ticket link type (from a node to the matchmaker)
+    Ticket,
+    /// Match assignment link type (from the matchmaker to a node)
+    Match,
+    /// Chat link type (between a host and its clients)
+    Chat,
+    /// Engine event link type (from a host to its clients)
+    Event,
+    /// Connection liveliness type (from a client to its host), read by observers
+    Connection,
+    /// Notice link type (from a host to its clients)
+    Notice,
 }
 
 impl LinkType {
@@ -57,15 +138,28 @@ impl LinkType {
             LinkType::Handshake => "handshake",
             LinkType::Action => "action",
             LinkType::State => "state",
+            LinkType::Ticket => "ticket",
+            LinkType::Match => "match",
+            LinkType::Chat => "chat",
+            LinkType::Event => "event",
+            LinkType::Connection => "connection",
+            LinkType::Notice => "notice",
         }
     }
 
     /// Parse a link type from a string
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Result<Self, ArenaError> {
         match s {
             "handshake" => Ok(LinkType::Handshake),
             "action" => Ok(LinkType::Action),
             "state" => Ok(LinkType::State),
+            "ticket" => Ok(LinkType::Ticket),
+            "match" => Ok(LinkType::Match),
+            "chat" => Ok(LinkType::Chat),
+            "event" => Ok(LinkType::Event),
+            "connection" => Ok(LinkType::Connection),
+            "notice" => Ok(LinkType::Notice),
             _ => Err(ArenaError::InvalidKeyexpr(format!(
                 "Invalid link type: {}",
                 s
@@ -75,10 +169,12 @@ impl LinkType {
 }
 
 /// Keyexpr for single node operations
-/// Format: `<prefix>/<node_type>/<node_id|*>`
+/// Format: `<prefix>/<node_type>/<node_id|*>`, or
+/// `<prefix>/room/<room_id>/<node_type>/<node_id|*>` when scoped to a room
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyexprNode {
     prefix: KeyExpr<'static>,
+    room: Option<RoomId>,
     node_type: NodeType,
     node: Option<NodeId>,
 }
@@ -92,16 +188,42 @@ impl KeyexprNode {
     ) -> Self {
         Self {
             prefix: prefix.into(),
+            room: None,
             node_type,
             node,
         }
     }
 
+    /// Scope the keyexpr to a room (None means no room segment)
+    pub fn with_room(mut self, room: Option<RoomId>) -> Self {
+        self.room = room;
+        self
+    }
+
+    /// Parse a node keyexpr declared under a known `prefix`
+    ///
+    /// Unlike `try_from`, which has to guess where the prefix ends, a prefix
+    /// containing a `room/<id>` segment is never parsed as a room.
+    pub fn parse(keyexpr: &KeyExpr<'_>, prefix: &KeyExpr<'_>) -> Result<Self, ArenaError> {
+        let (room, parts) = strip_prefix(keyexpr, prefix, 2)?;
+        Ok(Self {
+            prefix: prefix.clone().into_owned(),
+            room,
+            node_type: NodeType::from_str(parts[0])?,
+            node: parse_node_id(parts[1])?,
+        })
+    }
+
     /// Get the prefix
     pub fn prefix(&self) -> &KeyExpr<'static> {
         &self.prefix
     }
 
+    /// Get the room (None means the keyexpr has no room segment)
+    pub fn room(&self) -> &Option<RoomId> {
+        &self.room
+    }
+
     /// Get the node type
     pub fn node_type(&self) -> NodeType {
         self.node_type
@@ -121,7 +243,7 @@ impl From<KeyexprNode> for KeyExpr<'static> {
         };
         let keyexpr_str = format!(
             "{}/{}/{}",
-            keyexpr.prefix.as_str(),
+            format_prefix(&keyexpr.prefix, &keyexpr.room),
             keyexpr.node_type.as_str(),
             node_str
         );
@@ -145,22 +267,17 @@ impl TryFrom<KeyExpr<'_>> for KeyexprNode {
         let node_type_str = parts[parts.len() - 2];
         let node_type = NodeType::from_str(node_type_str)?;
 
-        let node_str = parts[parts.len() - 1];
-        let node = if node_str == "*" {
-            None
-        } else {
-            Some(NodeId::from_name(node_str.to_string())?)
-        };
+        let node = parse_node_id(parts[parts.len() - 1])?;
 
-        let prefix_str = parts[..parts.len() - 2].join("/");
-        let prefix = KeyExpr::try_from(prefix_str)?.into_owned();
+        let (prefix, room) = parse_prefix(&parts[..parts.len() - 2])?;
 
-        Ok(Self { prefix, node_type, node })
+        Ok(Self { prefix, room, node_type, node })
     }
 }
 
 /// Keyexpr for link operations (handshake, pub/sub, query/reply)
-/// Format: `<prefix>/<link_type>/<node_src|*>/<node_dst|*>`
+/// Format: `<prefix>/<link_type>/<node_src|*>/<node_dst|*>`, or
+/// `<prefix>/room/<room_id>/<link_type>/<node_src|*>/<node_dst|*>` when scoped to a room
 ///
 /// # Handshake Protocol Semantics
 ///
@@ -181,6 +298,7 @@ impl TryFrom<KeyExpr<'_>> for KeyexprNode {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct KeyexprLink {
     prefix: KeyExpr<'static>,
+    room: Option<RoomId>,
     link_type: LinkType,
     node_src: Option<NodeId>,
     node_dst: Option<NodeId>,
@@ -196,17 +314,44 @@ impl KeyexprLink {
     ) -> Self {
         Self {
             prefix: prefix.into(),
+            room: None,
             link_type,
             node_src,
             node_dst,
         }
     }
 
+    /// Scope the keyexpr to a room (None means no room segment)
+    pub fn with_room(mut self, room: Option<RoomId>) -> Self {
+        self.room = room;
+        self
+    }
+
+    /// Parse a link keyexpr declared under a known `prefix`
+    ///
+    /// Unlike `try_from`, which has to guess where the prefix ends, a prefix
+    /// containing a `room/<id>` segment is never parsed as a room.
+    pub fn parse(keyexpr: &KeyExpr<'_>, prefix: &KeyExpr<'_>) -> Result<Self, ArenaError> {
+        let (room, parts) = strip_prefix(keyexpr, prefix, 3)?;
+        Ok(Self {
+            prefix: prefix.clone().into_owned(),
+            room,
+            link_type: LinkType::from_str(parts[0])?,
+            node_src: parse_node_id(parts[1])?,
+            node_dst: parse_node_id(parts[2])?,
+        })
+    }
+
     /// Get the prefix
     pub fn prefix(&self) -> &KeyExpr<'static> {
         &self.prefix
     }
 
+    /// Get the room (None means the keyexpr has no room segment)
+    pub fn room(&self) -> &Option<RoomId> {
+        &self.room
+    }
+
     /// Get the link type
     pub fn link_type(&self) -> LinkType {
         self.link_type
@@ -235,7 +380,7 @@ impl From<KeyexprLink> for KeyExpr<'static> {
         };
         let keyexpr_str = format!(
             "{}/{}/{}/{}",
-            keyexpr.prefix.as_str(),
+            format_prefix(&keyexpr.prefix, &keyexpr.room),
             keyexpr.link_type.as_str(),
             src_str,
             dst_str
@@ -260,25 +405,14 @@ impl TryFrom<KeyExpr<'_>> for KeyexprLink {
         let link_type_str = parts[parts.len() - 3];
         let link_type = LinkType::from_str(link_type_str)?;
 
-        let src_str = parts[parts.len() - 2];
-        let node_src = if src_str == "*" {
-            None
-        } else {
-            Some(NodeId::from_name(src_str.to_string())?)
-        };
+        let node_src = parse_node_id(parts[parts.len() - 2])?;
+        let node_dst = parse_node_id(parts[parts.len() - 1])?;
 
-        let dst_str = parts[parts.len() - 1];
-        let node_dst = if dst_str == "*" {
-            None
-        } else {
-            Some(NodeId::from_name(dst_str.to_string())?)
-        };
-
-        let prefix_str = parts[..parts.len() - 3].join("/");
-        let prefix = KeyExpr::try_from(prefix_str)?.into_owned();
+        let (prefix, room) = parse_prefix(&parts[..parts.len() - 3])?;
 
         Ok(Self {
             prefix,
+            room,
             link_type,
             node_src,
             node_dst,
@@ -430,11 +564,103 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_link_keyexpr_room_roundtrip() {
+        let prefix = KeyExpr::try_from("arena/game1").unwrap();
+        let src = NodeId::from_name("host1".to_string()).unwrap();
+        let room = RoomId::new("lobby").unwrap();
+
+        let link_keyexpr = KeyexprLink::new(prefix, LinkType::State, Some(src.clone()), None)
+            .with_room(Some(room.clone()));
+        let keyexpr: KeyExpr = link_keyexpr.into();
+
+        assert_eq!(keyexpr.as_str(), "arena/game1/room/lobby/state/host1/*");
+
+        let parsed = KeyexprLink::try_from(keyexpr).unwrap();
+        assert_eq!(parsed.prefix().as_str(), "arena/game1");
+        assert_eq!(parsed.room(), &Some(room));
+        assert_eq!(parsed.link_type(), LinkType::State);
+        assert_eq!(parsed.node_src(), &Some(src));
+        assert_eq!(parsed.node_dst(), &None);
+    }
+
+    #[test]
+    fn test_node_keyexpr_room_roundtrip() {
+        let prefix = KeyExpr::try_from("arena").unwrap();
+        let node_id = NodeId::from_name("client1".to_string()).unwrap();
+        let room = RoomId::new("r2").unwrap();
+
+        let node_keyexpr = KeyexprNode::new(prefix, NodeType::Client, Some(node_id.clone()))
+            .with_room(Some(room.clone()));
+        let keyexpr: KeyExpr = node_keyexpr.into();
+
+        assert_eq!(keyexpr.as_str(), "arena/room/r2/client/client1");
+
+        let parsed = KeyexprNode::try_from(keyexpr).unwrap();
+        assert_eq!(parsed.prefix().as_str(), "arena");
+        assert_eq!(parsed.room(), &Some(room));
+        assert_eq!(parsed.node(), &Some(node_id));
+
+        // A prefix consisting of the room chunk alone is not a room segment
+        let parsed = KeyexprNode::try_from(KeyExpr::try_from("room/r2/host/h1").unwrap()).unwrap();
+        assert_eq!(parsed.prefix().as_str(), "room/r2");
+        assert_eq!(parsed.room(), &None);
+    }
+
+    #[test]
+    fn test_keyexpr_parse_prefix_with_room_segment() {
+        let prefix = KeyExpr::try_from("games/room/x").unwrap();
+        let host = NodeId::from_name("host1".to_string()).unwrap();
+        let room = RoomId::new("lobby").unwrap();
+
+        // Without a room, the prefix's own `room/x` is not read as one
+        let keyexpr: KeyExpr =
+            KeyexprLink::new(prefix.clone(), LinkType::State, Some(host.clone()), None).into();
+        assert_eq!(keyexpr.as_str(), "games/room/x/state/host1/*");
+        let parsed = KeyexprLink::parse(&keyexpr, &prefix).unwrap();
+        assert_eq!(parsed.prefix(), &prefix);
+        assert_eq!(parsed.room(), &None);
+        assert_eq!(parsed.node_src(), &Some(host.clone()));
+        let formatted: KeyExpr = parsed.into();
+        assert_eq!(formatted, keyexpr);
+
+        let keyexpr: KeyExpr = KeyexprLink::new(prefix.clone(), LinkType::Action, None, Some(host.clone()))
+            .with_room(Some(room.clone()))
+            .into();
+        assert_eq!(keyexpr.as_str(), "games/room/x/room/lobby/action/*/host1");
+        let parsed = KeyexprLink::parse(&keyexpr, &prefix).unwrap();
+        assert_eq!(parsed.prefix(), &prefix);
+        assert_eq!(parsed.room(), &Some(room.clone()));
+        assert_eq!(parsed.link_type(), LinkType::Action);
+        assert_eq!(parsed.node_dst(), &Some(host.clone()));
+        let formatted: KeyExpr = parsed.into();
+        assert_eq!(formatted, keyexpr);
+
+        for room in [None, Some(room)] {
+            let keyexpr: KeyExpr = KeyexprNode::new(prefix.clone(), NodeType::Host, Some(host.clone()))
+                .with_room(room.clone())
+                .into();
+            let parsed = KeyexprNode::parse(&keyexpr, &prefix).unwrap();
+            assert_eq!(parsed.prefix(), &prefix);
+            assert_eq!(parsed.room(), &room);
+            assert_eq!(parsed.node(), &Some(host.clone()));
+            let formatted: KeyExpr = parsed.into();
+            assert_eq!(formatted, keyexpr);
+        }
+
+        // Keyexprs outside the prefix are rejected
+        let other = KeyExpr::try_from("games/room/y/state/host1/*").unwrap();
+        assert!(KeyexprLink::parse(&other, &prefix).is_err());
+        let other = KeyExpr::try_from("games/room/xy/host/host1").unwrap();
+        assert!(KeyexprNode::parse(&other, &prefix).is_err());
+    }
+
     #[test]
     fn test_node_type_parsing() {
         assert_eq!(NodeType::from_str("node").unwrap(), NodeType::Node);
         assert_eq!(NodeType::from_str("host").unwrap(), NodeType::Host);
         assert_eq!(NodeType::from_str("client").unwrap(), NodeType::Client);
+        assert_eq!(NodeType::from_str("matchmaker").unwrap(), NodeType::Matchmaker);
         assert!(NodeType::from_str("invalid").is_err());
     }
 
@@ -443,6 +669,84 @@ mod tests {
         assert_eq!(LinkType::from_str("handshake").unwrap(), LinkType::Handshake);
         assert_eq!(LinkType::from_str("action").unwrap(), LinkType::Action);
         assert_eq!(LinkType::from_str("state").unwrap(), LinkType::State);
+        assert_eq!(LinkType::from_str("ticket").unwrap(), LinkType::Ticket);
+        assert_eq!(LinkType::from_str("match").unwrap(), LinkType::Match);
+        assert_eq!(LinkType::from_str("chat").unwrap(), LinkType::Chat);
+        assert_eq!(LinkType::from_str("event").unwrap(), LinkType::Event);
+        assert_eq!(LinkType::from_str("connection").unwrap(), LinkType::Connection);
+        assert_eq!(LinkType::from_str("notice").unwrap(), LinkType::Notice);
         assert!(LinkType::from_str("invalid").is_err());
     }
+
+    /// Random keyexpr-like strings mixing protocol chunks, wildcards and odd characters
+    fn random_keyexprs(seed: u64, count: usize) -> Vec<String> {
+        use rand::{Rng, SeedableRng};
+
+        const CHUNKS: &[&str] = &[
+            "arena", "game1", "room", "lobby", "handshake", "action", "state", "ticket", "match",
+            "node", "client", "host", "matchmaker", "h1", "c1", "*", "**", "$*", "a$*b", "@x",
+            "", " ", "é", "a:b", "?", "#", "-", ".", "room*",
+        ];
+        let mut rng = rand::rngs::StdRng::seed_from_u64(seed);
+        (0..count)
+            .map(|_| {
+                let len = rng.random_range(0..9);
+                (0..len)
+                    .map(|_| {
+                        if rng.random_bool(0.1) {
+                            let chars = rng.random_range(1..6);
+                            (0..chars).map(|_| rng.random_range(' '..='~')).collect()
+                        } else {
+                            CHUNKS[rng.random_range(0..CHUNKS.len())].to_string()
+                        }
+                    })
+                    .collect::<Vec<String>>()
+                    .join("/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_link_keyexpr_parsing_never_panics() {
+        let prefix = KeyExpr::try_from("arena").unwrap();
+        let mut parsed_count = 0;
+        for input in random_keyexprs(1, 20_000) {
+            let Ok(keyexpr) = KeyExpr::try_from(input) else {
+                continue;
+            };
+            // Anything that parses maps back to the same keyexpr
+            if let Ok(parsed) = KeyexprLink::try_from(keyexpr.clone()) {
+                let formatted: KeyExpr = parsed.into();
+                assert_eq!(formatted.as_str(), keyexpr.as_str());
+                parsed_count += 1;
+            }
+            if let Ok(parsed) = KeyexprLink::parse(&keyexpr, &prefix) {
+                let formatted: KeyExpr = parsed.into();
+                assert_eq!(formatted.as_str(), keyexpr.as_str());
+            }
+        }
+        assert!(parsed_count > 100, "only {parsed_count} inputs parsed");
+    }
+
+    #[test]
+    fn test_node_keyexpr_parsing_never_panics() {
+        let prefix = KeyExpr::try_from("arena").unwrap();
+        let mut parsed_count = 0;
+        for input in random_keyexprs(2, 20_000) {
+            let Ok(keyexpr) = KeyExpr::try_from(input) else {
+                continue;
+            };
+            // Anything that parses maps back to the same keyexpr
+            if let Ok(parsed) = KeyexprNode::try_from(keyexpr.clone()) {
+                let formatted: KeyExpr = parsed.into();
+                assert_eq!(formatted.as_str(), keyexpr.as_str());
+                parsed_count += 1;
+            }
+            if let Ok(parsed) = KeyexprNode::parse(&keyexpr, &prefix) {
+                let formatted: KeyExpr = parsed.into();
+                assert_eq!(formatted.as_str(), keyexpr.as_str());
+            }
+        }
+        assert!(parsed_count > 100, "only {parsed_count} inputs parsed");
+    }
 }
@@ -0,0 +1,139 @@
+//! Messages exchanged with a matchmaker node
+//!
+//! ## Matchmaking Protocol
+//!
+//! - A node waiting for a match publishes its [`MatchTicket`] on
+//!   `<prefix>/ticket/<node_id>/*`, and republishes it periodically until matched
+//! - The matchmaker subscribes to `<prefix>/ticket/*/<matchmaker_id>` and queues the tickets
+//! - When its rules form a match, the matchmaker publishes a [`MatchAssignment`] to every
+//!   participant on `<prefix>/match/<matchmaker_id>/<node_id>`
+//! - The assigned host starts hosting; the other participants connect to it with the
+//!   regular handshake
+//!
+//! Nodes playing together as a party each publish a ticket listing the whole party.
+//! The party is queued once tickets from all of its members have been received.
+
+use crate::node::types::NodeId;
+
+/// Queue entry sent by a node to the matchmaker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchTicket {
+    /// Skill rating of the player
+    pub rating: i32,
+    /// Region tag; only players of the same region are matched by the standard rules
+    pub region: String,
+    /// Other members of the player's party (empty when playing solo)
+    pub party: Vec<NodeId>,
+}
+
+impl MatchTicket {
+    /// Create a solo ticket
+    pub fn new(rating: i32, region: impl Into<String>) -> Self {
+        Self {
+            rating,
+            region: region.into(),
+            party: Vec::new(),
+        }
+    }
+
+    /// Set the other members of the player's party
+    pub fn with_party(mut self, party: Vec<NodeId>) -> Self {
+        self.party = party;
+        self
+    }
+}
+
+/// Match formed by the matchmaker, sent to every participant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchAssignment {
+    /// Participant that hosts the match
+    pub host: NodeId,
+    /// All participants, including the host
+    pub members: Vec<NodeId>,
+}
+
+fn node_ids_to_strings(ids: &[NodeId]) -> Vec<String> {
+    ids.iter().map(|id| id.as_str().to_string()).collect()
+}
+
+fn node_ids_from_strings(
+    names: Vec<String>,
+) -> Result<Vec<NodeId>, zenoh_ext::ZDeserializeError> {
+    names
+        .into_iter()
+        .map(|name| NodeId::from_name(name).map_err(|_| zenoh_ext::ZDeserializeError))
+        .collect()
+}
+
+impl zenoh_ext::Serialize for MatchTicket {
+    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
+        self.rating.serialize(serializer);
+        self.region.serialize(serializer);
+        node_ids_to_strings(&self.party).serialize(serializer);
+    }
+}
+
+impl zenoh_ext::Deserialize for MatchTicket {
+    fn deserialize(
+        deserializer: &mut zenoh_ext::ZDeserializer,
+    ) -> Result<Self, zenoh_ext::ZDeserializeError> {
+        let rating = i32::deserialize(deserializer)?;
+        let region = String::deserialize(deserializer)?;
+        let party = node_ids_from_strings(Vec::<String>::deserialize(deserializer)?)?;
+        Ok(Self {
+            rating,
+            region,
+            party,
+        })
+    }
+}
+
+impl zenoh_ext::Serialize for MatchAssignment {
+    fn serialize(&self, serializer: &mut zenoh_ext::ZSerializer) {
+        self.host.as_str().to_string().serialize(serializer);
+        node_ids_to_strings(&self.members).serialize(serializer);
+    }
+}
+
+impl zenoh_ext::Deserialize for MatchAssignment {
+    fn deserialize(
+        deserializer: &mut zenoh_ext::ZDeserializer,
+    ) -> Result<Self, zenoh_ext::ZDeserializeError> {
+        let host = NodeId::from_name(String::deserialize(deserializer)?)
+            .map_err(|_| zenoh_ext::ZDeserializeError)?;
+        let members = node_ids_from_strings(Vec::<String>::deserialize(deserializer)?)?;
+        Ok(Self { host, members })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticket_roundtrip() {
+        let ticket = MatchTicket::new(1500, "eu")
+            .with_party(vec![NodeId::from_name("friend".to_string()).unwrap()]);
+        let decoded: MatchTicket = zenoh_ext::z_deserialize(&zenoh_ext::z_serialize(&ticket)).unwrap();
+        assert_eq!(decoded, ticket);
+    }
+
+    #[test]
+    fn test_assignment_roundtrip() {
+        let host = NodeId::from_name("host".to_string()).unwrap();
+        let guest = NodeId::from_name("guest".to_string()).unwrap();
+        let assignment = MatchAssignment {
+            host: host.clone(),
+            members: vec![host, guest],
+        };
+        let decoded: MatchAssignment =
+            zenoh_ext::z_deserialize(&zenoh_ext::z_serialize(&assignment)).unwrap();
+        assert_eq!(decoded, assignment);
+    }
+
+    #[test]
+    fn test_assignment_invalid_node_id() {
+        let payload = zenoh_ext::z_serialize(&("bad/name".to_string(), Vec::<String>::new()));
+        assert!(zenoh_ext::z_deserialize::<MatchAssignment>(&payload).is_err());
+    }
+}
@@ -0,0 +1,942 @@
+//! In-memory simulated network implementing [`Transport`]
+//!
+//! A [`MemoryNetwork`] connects any number of [`MemoryTransport`] sessions inside one
+//! process. Message delivery is scheduled on the tokio clock, so tests running with a
+//! paused clock (`#[tokio::test(start_paused = true)]`) are fully deterministic.
+//!
+//! The network can be scripted at runtime:
+//! - latency and jitter per directed link (jitter causes reordering)
+//! - probabilistic loss of published samples (queries, replies and liveliness
+//!   are reliable, as in zenoh)
+//! - partitions between sessions; liveliness tokens on the other side of a
+//!   partition appear as lost and reappear when the partition heals
+//!
+//! Published samples and queries report the [`MemorySessionId`] of the sending session
+//! as their source, which cannot be forged by other sessions.
+//!
+//! Quality of service ([`LinkQos`]) is not simulated.
+//!
+//! All randomness comes from a seeded generator, see [`MemoryNetwork::with_seed`].
+
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use tokio::time::Instant;
+use zenoh::bytes::ZBytes;
+use zenoh::key_expr::KeyExpr;
+
+use crate::error::Result;
+use crate::network::transport::{
+    LinkQos, QueryResponder, Transport, TransportPublisher, TransportQuery, TransportReceiver,
+    TransportReply, TransportSample, TransportToken,
+};
+
+/// Time after which a query without pending responders is finalized
+const QUERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Delivery conditions of a directed link between two sessions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LinkConditions {
+    /// Fixed delivery delay
+    pub latency: Duration,
+    /// Maximum random delay added to `latency`; messages with different jitter may be reordered
+    pub jitter: Duration,
+    /// Probability in `0.0..=1.0` that a published sample is lost
+    pub loss: f64,
+}
+
+impl Default for LinkConditions {
+    fn default() -> Self {
+        Self {
+            latency: Duration::ZERO,
+            jitter: Duration::ZERO,
+            loss: 0.0,
+        }
+    }
+}
+
+/// Identifier of a session attached to a [`MemoryNetwork`]
+pub type MemorySessionId = u64;
+
+type Action = Box<dyn FnOnce() + Send>;
+
+/// Action scheduled for delivery at a given instant; ties are resolved by sequence number
+struct Scheduled {
+    at: Instant,
+    seq: u64,
+    action: Action,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.at == other.at && self.seq == other.seq
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed so that BinaryHeap pops the earliest action first
+        (other.at, other.seq).cmp(&(self.at, self.seq))
+    }
+}
+
+/// Entity registered in the network
+struct Entry<T> {
+    session: MemorySessionId,
+    key_expr: KeyExpr<'static>,
+    sender: T,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum EntityKind {
+    Subscriber,
+    Queryable,
+    Token,
+    LivelinessSubscriber,
+}
+
+struct NetworkState {
+    next_id: u64,
+    next_seq: u64,
+    rng: StdRng,
+    default_conditions: LinkConditions,
+    links: HashMap<(MemorySessionId, MemorySessionId), LinkConditions>,
+    partitions: HashSet<(MemorySessionId, MemorySessionId)>,
+    subscribers: HashMap<u64, Entry<flume::Sender<TransportSample>>>,
+    queryables: HashMap<u64, Entry<flume::Sender<TransportQuery>>>,
+    tokens: HashMap<u64, Entry<()>>,
+    liveliness_subscribers: HashMap<u64, Entry<flume::Sender<TransportSample>>>,
+    queue: BinaryHeap<Scheduled>,
+    pump_running: bool,
+}
+
+impl NetworkState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn reachable(&self, from: MemorySessionId, to: MemorySessionId) -> bool {
+        from == to || !self.partitions.contains(&partition_key(from, to))
+    }
+
+    /// Delivery delay from one session to another; local delivery is immediate
+    fn delay(&mut self, from: MemorySessionId, to: MemorySessionId) -> Duration {
+        if from == to {
+            return Duration::ZERO;
+        }
+        let conditions = self.conditions(from, to);
+        let jitter = if conditions.jitter.is_zero() {
+            Duration::ZERO
+        } else {
+            let nanos = self.rng.random_range(0..=conditions.jitter.as_nanos() as u64);
+            Duration::from_nanos(nanos)
+        };
+        conditions.latency + jitter
+    }
+
+    fn lost(&mut self, from: MemorySessionId, to: MemorySessionId) -> bool {
+        if from == to {
+            return false;
+        }
+        let loss = self.conditions(from, to).loss;
+        loss > 0.0 && self.rng.random_bool(loss.clamp(0.0, 1.0))
+    }
+
+    fn conditions(&self, from: MemorySessionId, to: MemorySessionId) -> LinkConditions {
+        self.links
+            .get(&(from, to))
+            .copied()
+            .unwrap_or(self.default_conditions)
+    }
+
+    fn schedule(&mut self, delay: Duration, action: Action) {
+        self.next_seq += 1;
+        self.queue.push(Scheduled {
+            at: Instant::now() + delay,
+            seq: self.next_seq,
+            action,
+        });
+    }
+
+    /// Schedule liveliness notifications for tokens of `token_session` seen from `observer`
+    fn schedule_liveliness_between(
+        &mut self,
+        token_session: MemorySessionId,
+        observer: MemorySessionId,
+        alive: bool,
+    ) {
+        let mut deliveries = Vec::new();
+        for token in self.tokens.values().filter(|t| t.session == token_session) {
+            for subscriber in self
+                .liveliness_subscribers
+                .values()
+                .filter(|s| s.session == observer && s.key_expr.intersects(&token.key_expr))
+            {
+                let sample = if alive {
+                    TransportSample::put(token.key_expr.clone(), ZBytes::new())
+                } else {
+                    TransportSample::delete(token.key_expr.clone())
+                };
+                deliveries.push((subscriber.sender.clone(), sample));
+            }
+        }
+        for (sender, sample) in deliveries {
+            let delay = self.delay(token_session, observer);
+            self.schedule(delay, Box::new(move || {
+                let _ = sender.send(sample);
+            }));
+        }
+    }
+
+    /// Notify all reachable liveliness subscribers about a token change
+    fn schedule_token_change(&mut self, session: MemorySessionId, key_expr: &KeyExpr<'static>, alive: bool) {
+        let targets: Vec<_> = self
+            .liveliness_subscribers
+            .values()
+            .filter(|s| self.reachable(session, s.session) && s.key_expr.intersects(key_expr))
+            .map(|s| (s.session, s.sender.clone()))
+            .collect();
+        for (observer, sender) in targets {
+            let sample = if alive {
+                TransportSample::put(key_expr.clone(), ZBytes::new())
+            } else {
+                TransportSample::delete(key_expr.clone())
+            };
+            let delay = self.delay(session, observer);
+            self.schedule(delay, Box::new(move || {
+                let _ = sender.send(sample);
+            }));
+        }
+    }
+}
+
+fn partition_key(a: MemorySessionId, b: MemorySessionId) -> (MemorySessionId, MemorySessionId) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+struct NetworkShared {
+    state: Mutex<NetworkState>,
+    notify: tokio::sync::Notify,
+}
+
+/// Simulated network shared by several [`MemoryTransport`] sessions
+///
+/// Cloning the network returns a handle to the same simulated network.
+#[derive(Clone)]
+pub struct MemoryNetwork {
+    shared: Arc<NetworkShared>,
+}
+
+impl std::fmt::Debug for MemoryNetwork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.lock();
+        f.debug_struct("MemoryNetwork")
+            .field("default_conditions", &state.default_conditions)
+            .field("partitions", &state.partitions)
+            .field("pending", &state.queue.len())
+            .finish()
+    }
+}
+
+impl Default for MemoryNetwork {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryNetwork {
+    /// Create a network with ideal links and a fixed random seed
+    pub fn new() -> Self {
+        Self::with_seed(0)
+    }
+
+    /// Create a network with ideal links, using `seed` for loss and jitter decisions
+    pub fn with_seed(seed: u64) -> Self {
+        let state = NetworkState {
+            next_id: 0,
+            next_seq: 0,
+            rng: StdRng::seed_from_u64(seed),
+            default_conditions: LinkConditions::default(),
+            links: HashMap::new(),
+            partitions: HashSet::new(),
+            subscribers: HashMap::new(),
+            queryables: HashMap::new(),
+            tokens: HashMap::new(),
+            liveliness_subscribers: HashMap::new(),
+            queue: BinaryHeap::new(),
+            pump_running: false,
+        };
+        Self {
+            shared: Arc::new(NetworkShared {
+                state: Mutex::new(state),
+                notify: tokio::sync::Notify::new(),
+            }),
+        }
+    }
+
+    /// Attach a new session to the network
+    pub fn open(&self) -> MemoryTransport {
+        let id = self.lock().next_id();
+        MemoryTransport {
+            network: self.clone(),
+            id,
+        }
+    }
+
+    /// Set the conditions used by links without specific conditions
+    pub fn set_default_conditions(&self, conditions: LinkConditions) {
+        self.lock().default_conditions = conditions;
+    }
+
+    /// Set the conditions of the directed link `from -> to`
+    pub fn set_link_conditions(&self, from: &MemoryTransport, to: &MemoryTransport, conditions: LinkConditions) {
+        self.lock().links.insert((from.id, to.id), conditions);
+    }
+
+    /// Cut communication between two sessions in both directions
+    ///
+    /// Liveliness tokens of each side are reported as lost to the other side.
+    pub fn partition(&self, a: &MemoryTransport, b: &MemoryTransport) {
+        let mut state = self.lock();
+        if a.id == b.id || !state.partitions.insert(partition_key(a.id, b.id)) {
+            return;
+        }
+        state.schedule_liveliness_between(a.id, b.id, false);
+        state.schedule_liveliness_between(b.id, a.id, false);
+        self.wake(state);
+    }
+
+    /// Cut communication between a session and every other session
+    pub fn isolate(&self, session: &MemoryTransport) {
+        for other in self.session_ids() {
+            if other != session.id {
+                self.partition_ids(session.id, other);
+            }
+        }
+    }
+
+    /// Restore communication between two sessions
+    ///
+    /// Liveliness tokens of each side are reported as alive again to the other side.
+    pub fn heal(&self, a: &MemoryTransport, b: &MemoryTransport) {
+        let mut state = self.lock();
+        if !state.partitions.remove(&partition_key(a.id, b.id)) {
+            return;
+        }
+        state.schedule_liveliness_between(a.id, b.id, true);
+        state.schedule_liveliness_between(b.id, a.id, true);
+        self.wake(state);
+    }
+
+    /// Remove all partitions
+    pub fn heal_all(&self) {
+        let mut state = self.lock();
+        let partitions: Vec<_> = state.partitions.drain().collect();
+        for (a, b) in partitions {
+            state.schedule_liveliness_between(a, b, true);
+            state.schedule_liveliness_between(b, a, true);
+        }
+        self.wake(state);
+    }
+
+    fn partition_ids(&self, a: MemorySessionId, b: MemorySessionId) {
+        let mut state = self.lock();
+        if !state.partitions.insert(partition_key(a, b)) {
+            return;
+        }
+        state.schedule_liveliness_between(a, b, false);
+        state.schedule_liveliness_between(b, a, false);
+        self.wake(state);
+    }
+
+    /// Ids of all sessions owning at least one declared entity
+    fn session_ids(&self) -> HashSet<MemorySessionId> {
+        let state = self.lock();
+        let mut ids = HashSet::new();
+        ids.extend(state.subscribers.values().map(|e| e.session));
+        ids.extend(state.queryables.values().map(|e| e.session));
+        ids.extend(state.tokens.values().map(|e| e.session));
+        ids.extend(state.liveliness_subscribers.values().map(|e| e.session));
+        ids
+    }
+
+    fn lock(&self) -> MutexGuard<'_, NetworkState> {
+        self.shared
+            .state
+            .lock()
+            .expect("memory network state poisoned")
+    }
+
+    /// Make sure scheduled actions get delivered, then release the lock
+    fn wake(&self, mut state: MutexGuard<'_, NetworkState>) {
+        if state.queue.is_empty() {
+            return;
+        }
+        if state.pump_running {
+            drop(state);
+            self.shared.notify.notify_one();
+            return;
+        }
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                state.pump_running = true;
+                drop(state);
+                handle.spawn(Self::pump(Arc::downgrade(&self.shared)));
+            }
+            Err(_) => {
+                // No runtime (e.g. entities dropped during shutdown): deliver immediately
+                let mut actions = Vec::new();
+                while let Some(scheduled) = state.queue.pop() {
+                    actions.push(scheduled.action);
+                }
+                drop(state);
+                for action in actions {
+                    action();
+                }
+            }
+        }
+    }
+
+    /// Deliver scheduled actions in (time, sequence) order until the queue is empty
+    async fn pump(shared: Weak<NetworkShared>) {
+        loop {
+            let Some(shared) = shared.upgrade() else {
+                return;
+            };
+            let (due, next_at) = {
+                let mut state = shared.state.lock().expect("memory network state poisoned");
+                let now = Instant::now();
+                let mut due = Vec::new();
+                while state.queue.peek().is_some_and(|s| s.at <= now) {
+                    due.push(state.queue.pop().expect("peeked").action);
+                }
+                let next_at = state.queue.peek().map(|s| s.at);
+                if due.is_empty() && next_at.is_none() {
+                    state.pump_running = false;
+                    return;
+                }
+                (due, next_at)
+            };
+            for action in due {
+                action();
+            }
+            if let Some(at) = next_at {
+                tokio::select! {
+                    () = tokio::time::sleep_until(at) => {}
+                    () = shared.notify.notified() => {}
+                }
+            }
+        }
+    }
+
+    fn remove(&self, kind: EntityKind, id: u64) {
+        let mut state = self.lock();
+        match kind {
+            EntityKind::Subscriber => {
+                state.subscribers.remove(&id);
+            }
+            EntityKind::Queryable => {
+                state.queryables.remove(&id);
+            }
+            EntityKind::LivelinessSubscriber => {
+                state.liveliness_subscribers.remove(&id);
+            }
+            EntityKind::Token => {
+                if let Some(token) = state.tokens.remove(&id) {
+                    state.schedule_token_change(token.session, &token.key_expr, false);
+                }
+            }
+        }
+        self.wake(state);
+    }
+}
+
+/// Removes an entity from the network when dropped
+struct EntityGuard {
+    network: Weak<NetworkShared>,
+    kind: EntityKind,
+    id: u64,
+}
+
+impl Drop for EntityGuard {
+    fn drop(&mut self) {
+        if let Some(shared) = self.network.upgrade() {
+            MemoryNetwork { shared }.remove(self.kind, self.id);
+        }
+    }
+}
+
+/// Session attached to a [`MemoryNetwork`]
+#[derive(Clone)]
+pub struct MemoryTransport {
+    network: MemoryNetwork,
+    id: MemorySessionId,
+}
+
+impl std::fmt::Debug for MemoryTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MemoryTransport").field("id", &self.id).finish()
+    }
+}
+
+impl MemoryTransport {
+    /// Identifier of this session in the network
+    pub fn id(&self) -> MemorySessionId {
+        self.id
+    }
+
+    /// The network this session is attached to
+    pub fn network(&self) -> &MemoryNetwork {
+        &self.network
+    }
+
+    fn guard(&self, kind: EntityKind, id: u64) -> EntityGuard {
+        EntityGuard {
+            network: Arc::downgrade(&self.network.shared),
+            kind,
+            id,
+        }
+    }
+}
+
+struct MemoryPublisher {
+    transport: MemoryTransport,
+    key_expr: KeyExpr<'static>,
+}
+
+impl TransportPublisher for MemoryPublisher {
+    fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    fn put(&self, payload: ZBytes) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let from = self.transport.id;
+            let network = &self.transport.network;
+            let mut state = network.lock();
+            let targets: Vec<_> = state
+                .subscribers
+                .values()
+                .filter(|s| state.reachable(from, s.session) && s.key_expr.intersects(&self.key_expr))
+                .map(|s| (s.session, s.sender.clone()))
+                .collect();
+            for (to, sender) in targets {
+                if state.lost(from, to) {
+                    continue;
+                }
+                let sample = TransportSample::put(self.key_expr.clone(), payload.clone())
+                    .with_source(from.to_string());
+                let delay = state.delay(from, to);
+                state.schedule(delay, Box::new(move || {
+                    let _ = sender.send(sample);
+                }));
+            }
+            network.wake(state);
+            Ok(())
+        })
+    }
+}
+
+/// Responder delivering replies back to the querying session
+struct MemoryResponder {
+    network: MemoryNetwork,
+    replier: MemorySessionId,
+    querier: MemorySessionId,
+    /// Shared reply channel; the query is finalized when all holders are gone
+    reply_tx: Arc<Mutex<Option<flume::Sender<TransportReply>>>>,
+}
+
+impl MemoryResponder {
+    fn send(&self, reply: TransportReply) {
+        let Some(sender) = self
+            .reply_tx
+            .lock()
+            .expect("reply channel poisoned")
+            .clone()
+        else {
+            // Query already timed out
+            return;
+        };
+        let mut state = self.network.lock();
+        if !state.reachable(self.replier, self.querier) {
+            return;
+        }
+        let delay = state.delay(self.replier, self.querier);
+        state.schedule(delay, Box::new(move || {
+            let _ = sender.send(reply);
+        }));
+        self.network.wake(state);
+    }
+}
+
+impl QueryResponder for MemoryResponder {
+    fn reply(&self, key_expr: KeyExpr<'static>, payload: ZBytes) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.send(Ok(TransportSample::put(key_expr, payload)));
+            Ok(())
+        })
+    }
+
+    fn reply_err(&self, payload: ZBytes) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.send(Err(payload));
+            Ok(())
+        })
+    }
+}
+
+impl Transport for MemoryTransport {
+    fn declare_publisher(
+        &self,
+        key_expr: KeyExpr<'static>,
+        _qos: LinkQos,
+    ) -> BoxFuture<'_, Result<Box<dyn TransportPublisher>>> {
+        Box::pin(async move {
+            Ok(Box::new(MemoryPublisher {
+                transport: self.clone(),
+                key_expr,
+            }) as Box<dyn TransportPublisher>)
+        })
+    }
+
+    fn declare_subscriber(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportSample>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let mut state = self.network.lock();
+            let id = state.next_id();
+            state.subscribers.insert(
+                id,
+                Entry {
+                    session: self.id,
+                    key_expr: key_expr.clone(),
+                    sender: tx,
+                },
+            );
+            drop(state);
+            Ok(TransportReceiver::new(
+                key_expr,
+                rx,
+                Box::new(self.guard(EntityKind::Subscriber, id)),
+            ))
+        })
+    }
+
+    fn declare_queryable(
+        &self,
+        key_expr: KeyExpr<'static>,
+        _qos: LinkQos,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportQuery>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let mut state = self.network.lock();
+            let id = state.next_id();
+            state.queryables.insert(
+                id,
+                Entry {
+                    session: self.id,
+                    key_expr: key_expr.clone(),
+                    sender: tx,
+                },
+            );
+            drop(state);
+            Ok(TransportReceiver::new(
+                key_expr,
+                rx,
+                Box::new(self.guard(EntityKind::Queryable, id)),
+            ))
+        })
+    }
+
+    fn get(
+        &self,
+        key_expr: KeyExpr<'static>,
+        payload: Option<ZBytes>,
+        _qos: LinkQos,
+    ) -> BoxFuture<'_, Result<flume::Receiver<TransportReply>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let reply_tx = Arc::new(Mutex::new(Some(tx)));
+            let mut state = self.network.lock();
+            let targets: Vec<_> = state
+                .queryables
+                .values()
+                .filter(|q| state.reachable(self.id, q.session) && q.key_expr.intersects(&key_expr))
+                .map(|q| (q.session, q.sender.clone()))
+                .collect();
+            for (replier, sender) in targets {
+                let responder = MemoryResponder {
+                    network: self.network.clone(),
+                    replier,
+                    querier: self.id,
+                    reply_tx: reply_tx.clone(),
+                };
+                let query = TransportQuery::new(key_expr.clone(), payload.clone(), Arc::new(responder))
+                    .with_source(self.id.to_string());
+                let delay = state.delay(self.id, replier);
+                state.schedule(delay, Box::new(move || {
+                    let _ = sender.send(query);
+                }));
+            }
+            // Finalize the query after the timeout even if some replier never answers
+            let weak_reply_tx = Arc::downgrade(&reply_tx);
+            state.schedule(QUERY_TIMEOUT, Box::new(move || {
+                if let Some(reply_tx) = weak_reply_tx.upgrade() {
+                    reply_tx.lock().expect("reply channel poisoned").take();
+                }
+            }));
+            self.network.wake(state);
+            Ok(rx)
+        })
+    }
+
+    fn declare_liveliness_token(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<TransportToken>> {
+        Box::pin(async move {
+            let mut state = self.network.lock();
+            let id = state.next_id();
+            state.tokens.insert(
+                id,
+                Entry {
+                    session: self.id,
+                    key_expr: key_expr.clone(),
+                    sender: (),
+                },
+            );
+            state.schedule_token_change(self.id, &key_expr, true);
+            self.network.wake(state);
+            Ok(Box::new(self.guard(EntityKind::Token, id)) as TransportToken)
+        })
+    }
+
+    fn get_liveliness(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<flume::Receiver<TransportSample>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let mut state = self.network.lock();
+            let alive: Vec<_> = state
+                .tokens
+                .values()
+                .filter(|t| state.reachable(t.session, self.id) && t.key_expr.intersects(&key_expr))
+                .map(|t| (t.session, t.key_expr.clone()))
+                .collect();
+            for (session, token_key_expr) in alive {
+                let tx = tx.clone();
+                let delay = state.delay(session, self.id);
+                state.schedule(delay, Box::new(move || {
+                    let _ = tx.send(TransportSample::put(token_key_expr, ZBytes::new()));
+                }));
+            }
+            self.network.wake(state);
+            Ok(rx)
+        })
+    }
+
+    fn declare_liveliness_subscriber(
+        &self,
+        key_expr: KeyExpr<'static>,
+        history: bool,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportSample>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let mut state = self.network.lock();
+            if history {
+                let alive: Vec<_> = state
+                    .tokens
+                    .values()
+                    .filter(|t| state.reachable(t.session, self.id) && t.key_expr.intersects(&key_expr))
+                    .map(|t| (t.session, t.key_expr.clone()))
+                    .collect();
+                for (session, token_key_expr) in alive {
+                    let tx = tx.clone();
+                    let delay = state.delay(session, self.id);
+                    state.schedule(delay, Box::new(move || {
+                        let _ = tx.send(TransportSample::put(token_key_expr, ZBytes::new()));
+                    }));
+                }
+            }
+            let id = state.next_id();
+            state.liveliness_subscribers.insert(
+                id,
+                Entry {
+                    session: self.id,
+                    key_expr: key_expr.clone(),
+                    sender: tx,
+                },
+            );
+            self.network.wake(state);
+            Ok(TransportReceiver::new(
+                key_expr,
+                rx,
+                Box::new(self.guard(EntityKind::LivelinessSubscriber, id)),
+            ))
+        })
+    }
+
+    fn reports_sources(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ke(s: &str) -> KeyExpr<'static> {
+        KeyExpr::try_from(s.to_string()).unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pubsub_latency() {
+        let network = MemoryNetwork::new();
+        network.set_default_conditions(LinkConditions {
+            latency: Duration::from_millis(20),
+            ..Default::default()
+        });
+        let a = network.open();
+        let b = network.open();
+
+        let subscriber = b.declare_subscriber(ke("test/*")).await.unwrap();
+        let publisher = a.declare_publisher(ke("test/x"), LinkQos::default()).await.unwrap();
+
+        let start = Instant::now();
+        publisher.put(ZBytes::from("hello")).await.unwrap();
+        let sample = subscriber.recv_async().await.unwrap();
+        assert_eq!(sample.key_expr.as_str(), "test/x");
+        assert_eq!(sample.source, Some(a.id().to_string()));
+        assert_eq!(start.elapsed(), Duration::from_millis(20));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pubsub_preserves_order_without_jitter() {
+        let network = MemoryNetwork::new();
+        network.set_default_conditions(LinkConditions {
+            latency: Duration::from_millis(5),
+            ..Default::default()
+        });
+        let a = network.open();
+        let b = network.open();
+
+        let subscriber = b.declare_subscriber(ke("test/x")).await.unwrap();
+        let publisher = a.declare_publisher(ke("test/x"), LinkQos::default()).await.unwrap();
+        for i in 0..10u32 {
+            publisher.put(zenoh_ext::z_serialize(&i)).await.unwrap();
+        }
+        for i in 0..10u32 {
+            let sample = subscriber.recv_async().await.unwrap();
+            let value: u32 = zenoh_ext::z_deserialize(&sample.payload).unwrap();
+            assert_eq!(value, i);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_pubsub_full_loss() {
+        let network = MemoryNetwork::new();
+        let a = network.open();
+        let b = network.open();
+        network.set_link_conditions(&a, &b, LinkConditions {
+            loss: 1.0,
+            ..Default::default()
+        });
+
+        let subscriber = b.declare_subscriber(ke("test/x")).await.unwrap();
+        let publisher = a.declare_publisher(ke("test/x"), LinkQos::default()).await.unwrap();
+        publisher.put(ZBytes::from("lost")).await.unwrap();
+
+        let received = tokio::time::timeout(Duration::from_secs(1), subscriber.recv_async()).await;
+        assert!(received.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_query_reply_and_finalization() {
+        let network = MemoryNetwork::new();
+        let a = network.open();
+        let b = network.open();
+
+        let queryable = b.declare_queryable(ke("test/q"), LinkQos::request()).await.unwrap();
+        let replies = a.get(ke("test/*"), None, LinkQos::request()).await.unwrap();
+
+        let query = queryable.recv_async().await.unwrap();
+        assert_eq!(query.source(), Some(a.id().to_string().as_str()));
+        query.reply(ke("test/q"), ZBytes::from("answer")).await.unwrap();
+        drop(query);
+
+        let reply = replies.recv_async().await.unwrap();
+        assert!(reply.is_ok());
+        // Query is finalized once the only responder is dropped
+        assert!(replies.recv_async().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_query_blocked_by_partition() {
+        let network = MemoryNetwork::new();
+        let a = network.open();
+        let b = network.open();
+        let _queryable = b.declare_queryable(ke("test/q"), LinkQos::request()).await.unwrap();
+        network.partition(&a, &b);
+
+        let replies = a.get(ke("test/q"), None, LinkQos::request()).await.unwrap();
+        assert!(replies.recv_async().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_liveliness_partition_and_heal() {
+        let network = MemoryNetwork::new();
+        let a = network.open();
+        let b = network.open();
+
+        let _token = a.declare_liveliness_token(ke("test/node/a")).await.unwrap();
+        let subscriber = b
+            .declare_liveliness_subscriber(ke("test/node/*"), true)
+            .await
+            .unwrap();
+        assert_eq!(subscriber.recv_async().await.unwrap().kind, zenoh::sample::SampleKind::Put);
+
+        network.partition(&a, &b);
+        assert_eq!(subscriber.recv_async().await.unwrap().kind, zenoh::sample::SampleKind::Delete);
+
+        network.heal(&a, &b);
+        assert_eq!(subscriber.recv_async().await.unwrap().kind, zenoh::sample::SampleKind::Put);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_liveliness_token_drop() {
+        let network = MemoryNetwork::new();
+        let a = network.open();
+        let b = network.open();
+
+        let token = a.declare_liveliness_token(ke("test/node/a")).await.unwrap();
+        let alive = b.get_liveliness(ke("test/node/a")).await.unwrap();
+        assert!(alive.recv_async().await.is_ok());
+
+        let subscriber = b
+            .declare_liveliness_subscriber(ke("test/node/a"), false)
+            .await
+            .unwrap();
+        drop(token);
+        assert_eq!(subscriber.recv_async().await.unwrap().kind, zenoh::sample::SampleKind::Delete);
+
+        let alive = b.get_liveliness(ke("test/node/a")).await.unwrap();
+        assert!(alive.recv_async().await.is_err());
+    }
+}
@@ -1,18 +1,38 @@
 //! Network layer for zenoh-arena
 
+pub mod chat_message;
+pub mod handshake_info;
+pub mod host_notice;
 pub mod host_querier;
 pub mod host_queryable;
 pub mod keyexpr;
+pub mod match_ticket;
+pub mod memory_transport;
 pub mod node_liveliness;
 pub mod node_publisher;
 pub mod node_subscriber;
+#[cfg(feature = "shared-memory")]
+pub mod shm_pool;
+pub mod transport;
+pub mod zenoh_transport;
 
-pub use host_querier::HostQuerier;
+pub use chat_message::{ChatMessage, ChatRequest, MAX_CHAT_LEN};
+pub use handshake_info::{HandshakeInfo, PROTOCOL_VERSION};
+pub use host_notice::HostNotice;
+pub use host_querier::{DiscoveredHost, Discovery, HostConnection, HostQuerier};
 pub use host_queryable::HostQueryable;
 #[allow(unused_imports)]
 pub use keyexpr::{
     KeyexprLink, KeyexprNode, LinkType, NodeType,
 };
+pub use match_ticket::{MatchAssignment, MatchTicket};
+pub use memory_transport::{LinkConditions, MemoryNetwork, MemorySessionId, MemoryTransport};
 pub use node_liveliness::{NodeLivelinessToken, NodeLivelinessWatch};
 pub use node_publisher::NodePublisher;
-pub use node_subscriber::NodeSubscriber;
+pub use node_subscriber::{NodeMessage, NodeSubscriber};
+#[cfg(feature = "shared-memory")]
+pub use shm_pool::ShmPool;
+pub use transport::{
+    LinkQos, QueryResponder, Transport, TransportPublisher, TransportQuery, TransportReceiver,
+    TransportReply, TransportSample, TransportToken,
+};
@@ -2,23 +2,27 @@
 
 use crate::error::Result;
 use crate::network::keyexpr::{KeyexprNode, NodeType};
-use crate::node::types::NodeId;
+use crate::network::transport::{Transport, TransportReceiver, TransportSample, TransportToken};
+use crate::node::types::{NodeId, RoomId};
 use futures::future::select_all;
 use std::pin::Pin;
 use zenoh::key_expr::KeyExpr;
-use zenoh::liveliness::LivelinessToken;
 use zenoh::sample::SampleKind;
-use zenoh::pubsub::Subscriber;
-use zenoh::handlers::FifoChannelHandler;
-use zenoh::sample::Sample;
 
-/// Wrapper around Zenoh's LivelinessToken for a node
+/// Wrapper around a transport liveliness token for a node
 ///
 /// The token is automatically undeclared when dropped.
-#[derive(Debug)]
 pub struct NodeLivelinessToken {
-    _token: LivelinessToken,
-    _node_id: NodeId,
+    _token: TransportToken,
+    node_id: NodeId,
+}
+
+impl std::fmt::Debug for NodeLivelinessToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NodeLivelinessToken")
+            .field("node_id", &self.node_id)
+            .finish()
+    }
 }
 
 impl NodeLivelinessToken {
@@ -27,21 +31,21 @@ impl NodeLivelinessToken {
     /// Before creating the token, performs a liveliness.get() request to check if
     /// another token with the same keyexpr already exists in the network.
     /// If a conflict is detected, returns a LivelinessTokenConflict error.
+    ///
+    /// If `room` is Some, the token is scoped to that room.
     pub async fn declare(
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         prefix: impl Into<KeyExpr<'static>>,
         node_type: NodeType,
         node_id: NodeId,
+        room: Option<RoomId>,
     ) -> Result<Self> {
-        let keyexpr_node = KeyexprNode::new(prefix.into(), node_type, Some(node_id.clone()));
+        let keyexpr_node =
+            KeyexprNode::new(prefix.into(), node_type, Some(node_id.clone())).with_room(room);
         let keyexpr: KeyExpr = keyexpr_node.into();
 
         // Check if another token with the same keyexpr already exists
-        let replies = session
-            .liveliness()
-            .get(keyexpr.clone())
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        let replies = transport.get_liveliness(keyexpr.clone()).await?;
 
         // If we receive any liveliness tokens, it means another token already exists
         if (replies.recv_async().await).is_ok() {
@@ -52,13 +56,9 @@ impl NodeLivelinessToken {
         }
 
         // No existing token found, declare the new one
-        let token = session
-            .liveliness()
-            .declare_token(keyexpr)
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        let token = transport.declare_liveliness_token(keyexpr).await?;
 
-        Ok(Self { _token: token, _node_id: node_id })
+        Ok(Self { _token: token, node_id })
     }
 }
 
@@ -73,7 +73,8 @@ impl NodeLivelinessToken {
 /// - Hosts to detect when any client disconnects (wildcard pattern)
 #[derive(Debug)]
 pub struct NodeLivelinessWatch {
-    subscribers: Vec<Subscriber<FifoChannelHandler<Sample>>>,
+    /// Subscribers with the prefix their keyexpr is declared under
+    subscribers: Vec<(KeyExpr<'static>, TransportReceiver<TransportSample>)>,
 }
 
 impl NodeLivelinessWatch {
@@ -93,25 +94,24 @@ impl NodeLivelinessWatch {
     /// The keyexpr can be:
     /// - Specific: with node_id as Some(id) to track a single node
     /// - Wildcard: with node_id as None to track all nodes matching the pattern
+    ///
+    /// If `room` is Some, only tokens scoped to that room are tracked.
     pub async fn subscribe(
         &mut self,
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         prefix: impl Into<KeyExpr<'static>>,
         node_type: NodeType,
         node_id: Option<NodeId>,
+        room: Option<RoomId>,
     ) -> Result<()>
     {
-        let keyexpr_node = KeyexprNode::new(prefix.into(), node_type, node_id);
+        let prefix = prefix.into();
+        let keyexpr_node = KeyexprNode::new(prefix.clone(), node_type, node_id).with_room(room);
         let keyexpr: KeyExpr = keyexpr_node.into();
 
-        let subscriber = session
-            .liveliness()
-            .declare_subscriber(keyexpr)
-            .history(true)
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        let subscriber = transport.declare_liveliness_subscriber(keyexpr, true).await?;
 
-        self.subscribers.push(subscriber);
+        self.subscribers.push((prefix, subscriber));
         Ok(())
     }
 
@@ -136,18 +136,18 @@ impl NodeLivelinessWatch {
         let futures_vec: Vec<_> = self
             .subscribers
             .iter_mut()
-            .map(|subscriber| {
+            .map(|(prefix, subscriber)| {
                 Box::pin(async move {
                     loop {
                         match subscriber.recv_async().await {
                             Ok(sample) => {
                                 // Extract node_id from the sample's keyexpr by parsing it as KeyexprNode
-                                let keyexpr_node = match KeyexprNode::try_from(sample.key_expr().clone().into_owned()) {
+                                let keyexpr_node = match KeyexprNode::parse(&sample.key_expr, prefix) {
                                     Ok(k) => k,
                                     Err(e) => {
                                         tracing::warn!(
                                             "Failed to parse keyexpr '{}': {}",
-                                            sample.key_expr(),
+                                            sample.key_expr,
                                             e
                                         );
                                         continue;
@@ -159,13 +159,13 @@ impl NodeLivelinessWatch {
                                     None => {
                                         tracing::warn!(
                                             "Received sample with wildcard node in keyexpr '{}'",
-                                            sample.key_expr()
+                                            sample.key_expr
                                         );
                                         continue;
                                     }
                                 };
 
-                                match sample.kind() {
+                                match sample.kind {
                                     SampleKind::Delete => {
                                         // Node went offline, liveliness lost
                                         tracing::info!(
@@ -189,9 +189,8 @@ impl NodeLivelinessWatch {
                                     "Liveliness subscription error: {}",
                                     e
                                 );
-                                // Subscription error - we cannot extract node_id without a sample
-                                // Continue to next iteration
-                                continue;
+                                // Subscription channel closed - we cannot extract node_id without a sample
+                                return Err(e);
                             }
                         }
                     }
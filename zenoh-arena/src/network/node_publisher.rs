@@ -1,32 +1,71 @@
 //! Publisher for sending actions to a remote node
+//!
+//! Publishers of a connection's links prefix each payload with the session id assigned
+//! by the host (8 bytes, little endian), see [`NodePublisher::with_session`].
 
-use crate::error::Result;
+use crate::codec::Codec;
+use crate::error::{ArenaError, Result};
 use crate::network::keyexpr::{KeyexprLink, LinkType};
-use crate::node::types::NodeId;
+use crate::network::transport::{LinkQos, Transport, TransportPublisher};
+use crate::node::types::{NodeId, RoomId};
+use zenoh::bytes::ZBytes;
 use zenoh::key_expr::KeyExpr;
+#[cfg(feature = "shared-memory")]
+use std::sync::Arc;
+#[cfg(feature = "shared-memory")]
+use crate::network::shm_pool::ShmPool;
+
+/// Size of the session header of link payloads
+const SESSION_HEADER_LEN: usize = 8;
+
+/// Prefix a payload with a session id
+pub(crate) fn add_session_header(session: u64, payload: ZBytes) -> ZBytes {
+    let mut bytes = session.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&payload.to_bytes());
+    ZBytes::from(bytes)
+}
+
+/// Split the session id from a payload
+pub(crate) fn split_session_header(payload: &ZBytes) -> Result<(u64, ZBytes)> {
+    let bytes = payload.to_bytes();
+    if bytes.len() < SESSION_HEADER_LEN {
+        return Err(ArenaError::MalformedInput(
+            "Payload too short for session header".to_string(),
+        ));
+    }
+    let (header, body) = bytes.split_at(SESSION_HEADER_LEN);
+    let session = u64::from_le_bytes(header.try_into().expect("header length checked"));
+    Ok((session, ZBytes::from(body.to_vec())))
+}
 
 /// Publishes to a Zenoh key expression with automatic serialization
 ///
-/// This publisher automatically serializes data of type T before publishing.
+/// This publisher automatically serializes data of type T with codec C before publishing.
 /// Internally constructs a Link role keyexpr from the provided prefix and node IDs.
 /// Use `put()` to publish a serialized value.
-pub struct NodePublisher<T> {
-    publisher: zenoh::pubsub::Publisher<'static>,
-    _phantom: std::marker::PhantomData<T>,
+pub struct NodePublisher<T, C> {
+    publisher: Box<dyn TransportPublisher>,
+    /// Session id sent with every value, if any
+    session: Option<u64>,
+    /// Pool large payloads are published from, if any
+    #[cfg(feature = "shared-memory")]
+    shm_pool: Option<Arc<ShmPool>>,
+    _phantom: std::marker::PhantomData<(T, C)>,
 }
 
-impl<T> std::fmt::Debug for NodePublisher<T> {
+impl<T, C> std::fmt::Debug for NodePublisher<T, C> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NodePublisher")
             .field("type", &std::any::type_name::<T>())
+            .field("codec", &std::any::type_name::<C>())
             .field("key_expr", &self.publisher.key_expr())
             .finish()
     }
 }
 
-impl<T> NodePublisher<T>
+impl<T, C> NodePublisher<T, C>
 where
-    T: zenoh_ext::Serialize,
+    C: Codec<T>,
 {
     /// Create a new NodePublisher
     ///
@@ -35,12 +74,17 @@ where
     ///   to send messages to a specific remote node
     /// - If `receiver_id` is None: `<prefix>/<link_type>/<sender_id>/*`
     ///   to broadcast messages to all nodes (wildcard receiver)
+    ///
+    /// If `room` is Some, the keyexpr is scoped to that room. Samples are published with
+    /// the given quality of service.
     pub async fn new(
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         prefix: impl Into<KeyExpr<'static>>,
         link_type: LinkType,
         sender_id: &NodeId,
         receiver_id: Option<&NodeId>,
+        room: Option<RoomId>,
+        qos: LinkQos,
     ) -> Result<Self> {
         // Construct Link keyexpr with optional receiver (None = wildcard)
         let node_keyexpr = KeyexprLink::new(
@@ -48,32 +92,108 @@ where
             link_type,
             Some(sender_id.clone()),
             receiver_id.cloned(),
-        );
+        )
+        .with_room(room);
         let keyexpr: KeyExpr = node_keyexpr.into();
         
-        let publisher = session
-            .declare_publisher(keyexpr)
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        let publisher = transport.declare_publisher(keyexpr, qos).await?;
 
         Ok(Self {
             publisher,
+            session: None,
+            #[cfg(feature = "shared-memory")]
+            shm_pool: None,
             _phantom: std::marker::PhantomData,
         })
     }
 
+    /// Send the session id with every value
+    ///
+    /// The receiving [`NodeSubscriber`](crate::network::NodeSubscriber) must be created
+    /// with `with_sessions()`.
+    pub fn with_session(mut self, session: u64) -> Self {
+        self.session = Some(session);
+        self
+    }
+
+    /// Publish large payloads from a shared-memory pool
+    ///
+    /// Subscribers on the same host read them without a copy; zenoh serializes them as
+    /// usual for remote subscribers.
+    #[cfg(feature = "shared-memory")]
+    pub fn with_shared_memory(mut self, pool: Arc<ShmPool>) -> Self {
+        self.shm_pool = Some(pool);
+        self
+    }
+
     /// Publish a serialized value
     ///
-    /// Serializes the value into a ZBytes payload and publishes it.
+    /// Serializes the value into a ZBytes payload and publishes it. Large payloads
+    /// are written with their session header straight into a shared-memory buffer.
     /// Returns an error if serialization or publishing fails.
     pub async fn put(&self, value: &T) -> Result<()> {
-        let payload = zenoh_ext::z_serialize(value);
+        let payload = C::encode(value)?;
+        #[cfg(feature = "shared-memory")]
+        if let Some(pool) = &self.shm_pool {
+            let header = self.session.map(u64::to_le_bytes);
+            let header = header.as_ref().map_or(&[][..], |header| &header[..]);
+            if let Some(mut buffer) = pool.alloc(header.len() + payload.len()) {
+                let (buffer_header, buffer_body) = buffer.split_at_mut(header.len());
+                buffer_header.copy_from_slice(header);
+                buffer_body.copy_from_slice(&payload.to_bytes());
+                return self.publisher.put(ZBytes::from(buffer)).await;
+            }
+        }
+        let payload = match self.session {
+            Some(session) => add_session_header(session, payload),
+            None => payload,
+        };
+
+        self.publisher.put(payload).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        self.publisher
-            .put(payload)
+    #[test]
+    fn test_session_header_roundtrip() {
+        let payload = add_session_header(0x0102_0304_0506_0708, ZBytes::from(vec![9u8, 10]));
+        let (session, body) = split_session_header(&payload).unwrap();
+        assert_eq!(session, 0x0102_0304_0506_0708);
+        assert_eq!(body.to_bytes().as_ref(), &[9u8, 10]);
+
+        assert!(split_session_header(&ZBytes::from(vec![1u8, 2, 3])).is_err());
+    }
+
+    #[cfg(feature = "shared-memory")]
+    #[tokio::test]
+    async fn test_put_session_payload_from_shared_memory() {
+        use crate::codec::ZenohCodec;
+        use crate::network::memory_transport::MemoryNetwork;
+        use crate::node::types::SharedMemory;
+
+        let network = MemoryNetwork::new();
+        let transport = network.open();
+        let prefix = KeyExpr::try_from("arena").unwrap();
+        let sender = NodeId::from_name("host1".to_string()).unwrap();
+        let keyexpr: KeyExpr = KeyexprLink::new(prefix.clone(), LinkType::State, Some(sender.clone()), None).into();
+        let subscriber = transport.declare_subscriber(keyexpr).await.unwrap();
+        let pool = Arc::new(ShmPool::new(SharedMemory { pool_size: 64 * 1024, threshold: 1024 }).unwrap());
+        let publisher = NodePublisher::<String, ZenohCodec>::new(&transport, prefix, LinkType::State, &sender, None, None, LinkQos::default())
             .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+            .unwrap()
+            .with_session(7)
+            .with_shared_memory(pool);
+
+        let state = "x".repeat(4096);
+        publisher.put(&state).await.unwrap();
 
-        Ok(())
+        let sample = subscriber.recv_async().await.unwrap();
+        assert!(sample.payload.as_shm().is_some());
+        let (session, body) = split_session_header(&sample.payload).unwrap();
+        assert_eq!(session, 7);
+        assert_eq!(<ZenohCodec as Codec<String>>::decode(&body).unwrap(), state);
     }
 }
@@ -1,60 +1,94 @@
 //! Subscriber for node data with deserialization
 
-use crate::error::Result;
+use crate::codec::Codec;
+use crate::error::{ArenaError, Result};
 use crate::network::keyexpr::{KeyexprLink, LinkType};
-use crate::node::types::NodeId;
+use crate::network::node_publisher::split_session_header;
+use crate::network::transport::{Transport, TransportReceiver, TransportSample};
+use crate::node::types::{NodeId, RoomId};
+use zenoh::bytes::ZBytes;
 use zenoh::key_expr::KeyExpr;
 
+/// Value received by a [`NodeSubscriber`] with information about its sender
+#[derive(Debug, Clone)]
+pub struct NodeMessage<T> {
+    /// Sender node, taken from the key expression
+    pub sender_id: NodeId,
+    /// Session id carried by the payload (0 if the subscriber does not expect one)
+    pub session: u64,
+    /// Transport session that published the sample, if the transport reports it
+    pub source: Option<String>,
+    /// Size of the encoded value in bytes
+    pub size: usize,
+    /// Deserialized value
+    pub value: T,
+}
+
 /// Subscribes to a Zenoh key expression and deserializes received data
 ///
-/// This subscriber automatically deserializes received samples into type T.
+/// This subscriber automatically deserializes received samples into type T using codec C.
 /// Uses a glob subscription pattern: subscribes to `<prefix>/action/*/<receiver_id>`
 /// to receive messages from any sender to the specified receiver.
 /// The `recv()` method returns both the sender ID and the deserialized value.
-pub struct NodeSubscriber<T> {
-    subscriber: zenoh::pubsub::Subscriber<zenoh::handlers::FifoChannelHandler<zenoh::sample::Sample>>,
-    _phantom: std::marker::PhantomData<T>,
+pub struct NodeSubscriber<T, C> {
+    subscriber: TransportReceiver<TransportSample>,
+    /// Prefix the link keyexpr is declared under, used to parse sample keyexprs
+    prefix: KeyExpr<'static>,
+    /// Whether payloads carry a session id
+    sessions: bool,
+    _phantom: std::marker::PhantomData<(T, C)>,
 }
 
-impl<T> std::fmt::Debug for NodeSubscriber<T> {
+impl<T, C> std::fmt::Debug for NodeSubscriber<T, C> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NodeSubscriber")
             .field("type", &std::any::type_name::<T>())
+            .field("codec", &std::any::type_name::<C>())
             .field("key_expr", &self.subscriber.key_expr())
             .finish()
     }
 }
 
-impl<T> NodeSubscriber<T>
+impl<T, C> NodeSubscriber<T, C>
 where
-    T: zenoh_ext::Deserialize,
+    C: Codec<T>,
 {
     /// Create a new subscriber for a Link keyexpr with receiver_id
     ///
     /// Declares a Zenoh subscriber for the link keyexpr pattern:
     /// `<prefix>/<link_type>/*/<receiver_id>` (sender_id=wildcard, receiver_id=node_id)
     /// to receive all messages sent to the specified receiver from any sender.
+    ///
+    /// If `room` is Some, the keyexpr is scoped to that room.
     pub async fn new(
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         prefix: impl Into<KeyExpr<'static>>,
         link_type: LinkType,
         receiver_node_id: &NodeId,
+        room: Option<RoomId>,
     ) -> Result<Self> {
         // Construct Link keyexpr: <prefix>/<link_type>/*/<receiver_id> (sender_id=*, receiver_id)
-        let node_keyexpr = KeyexprLink::new(prefix, link_type, None, Some(receiver_node_id.clone()));
+        let prefix = prefix.into();
+        let node_keyexpr = KeyexprLink::new(prefix.clone(), link_type, None, Some(receiver_node_id.clone()))
+            .with_room(room);
         let keyexpr: KeyExpr = node_keyexpr.into();
 
-        let subscriber = session
-            .declare_subscriber(keyexpr)
-            .await
-            .map_err(crate::error::ArenaError::Zenoh)?;
+        let subscriber = transport.declare_subscriber(keyexpr).await?;
 
         Ok(Self {
             subscriber,
+            prefix,
+            sessions: false,
             _phantom: std::marker::PhantomData,
         })
     }
 
+    /// Expect payloads carrying a session id, sent by a publisher created with `with_session()`
+    pub fn with_sessions(mut self) -> Self {
+        self.sessions = true;
+        self
+    }
+
     /// Receive and deserialize the next value with sender information
     ///
     /// Waits for the next sample from the subscriber and:
@@ -63,25 +97,64 @@ where
     ///
     /// Returns a tuple of (sender_id, value).
     /// Returns an error if reception, keyexpr parsing, or deserialization fails.
-    pub async fn recv(&mut self) -> Result<(NodeId, T)> {
-        let sample = self
-            .subscriber
-            .recv_async()
-            .await
-            .map_err(|e| crate::error::ArenaError::Internal(format!("Failed to receive sample: {}", e)))?;
+    pub async fn recv(&self) -> Result<(NodeId, T)> {
+        let message = self.recv_message().await?;
+        Ok((message.sender_id, message.value))
+    }
+
+    /// Receive the next value with sender and session information
+    ///
+    /// The session is 0 unless the subscriber was created with `with_sessions()`.
+    /// The source is the transport session that published the sample, if reported.
+    /// Samples with a keyexpr or payload that cannot be parsed are returned as
+    /// [`ArenaError::MalformedInput`]; the subscriber can be read again afterwards.
+    pub async fn recv_message(&self) -> Result<NodeMessage<T>> {
+        Self::decode(self.recv_payload().await?)
+    }
+
+    /// Receive the next payload with sender and session information, without decoding it
+    ///
+    /// Lets the receiver check the payload, like its size, before paying for
+    /// [`decode`](Self::decode).
+    pub async fn recv_payload(&self) -> Result<NodeMessage<ZBytes>> {
+        let sample = self.subscriber.recv_async().await?;
 
         // Parse the keyexpr to extract sender_id (node_src)
-        let keyexpr_link = KeyexprLink::try_from(sample.key_expr().clone().into_owned())?;
+        let keyexpr_link = KeyexprLink::parse(&sample.key_expr, &self.prefix).map_err(|e| {
+            ArenaError::MalformedInput(format!("Invalid sample keyexpr '{}': {}", sample.key_expr, e))
+        })?;
         let sender_id = keyexpr_link.node_src()
             .clone()
-            .ok_or_else(|| crate::error::ArenaError::Internal(
-                format!("Received sample with wildcard sender_id in keyexpr '{}'", sample.key_expr())
+            .ok_or_else(|| ArenaError::MalformedInput(
+                format!("Received sample with wildcard sender_id in keyexpr '{}'", sample.key_expr)
             ))?;
 
         // Deserialize the payload
-        let value: T = zenoh_ext::z_deserialize(sample.payload())
-            .map_err(|e| crate::error::ArenaError::Serialization(format!("Failed to deserialize: {}", e)))?;
+        let (session, payload) = if self.sessions {
+            split_session_header(&sample.payload)?
+        } else {
+            (0, sample.payload)
+        };
+        Ok(NodeMessage {
+            sender_id,
+            session,
+            source: sample.source,
+            size: payload.len(),
+            value: payload,
+        })
+    }
 
-        Ok((sender_id, value))
+    /// Decode a payload received by [`recv_payload`](Self::recv_payload)
+    pub fn decode(message: NodeMessage<ZBytes>) -> Result<NodeMessage<T>> {
+        let value = C::decode(&message.value).map_err(|e| {
+            ArenaError::MalformedInput(format!("Failed to decode sample from '{}': {}", message.sender_id, e))
+        })?;
+        Ok(NodeMessage {
+            sender_id: message.sender_id,
+            session: message.session,
+            source: message.source,
+            size: message.size,
+            value,
+        })
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,71 @@
+//! Shared-memory pool for large payloads
+//!
+//! Payloads at least as large as the threshold are encoded into a buffer of a zenoh
+//! shared-memory provider before being published. Subscribers on the same host map the
+//! buffer instead of receiving a copy through the transport; zenoh falls back to regular
+//! serialization for remote subscribers and peers without shared memory.
+
+use zenoh::shm::{GarbageCollect, PosixShmProviderBackend, ShmProvider, ShmProviderBuilder, ZShmMut};
+use zenoh::Wait;
+
+use crate::error::{ArenaError, Result};
+use crate::node::types::SharedMemory;
+
+/// Shared-memory provider publishing large payloads
+#[derive(Debug)]
+pub struct ShmPool {
+    provider: ShmProvider<PosixShmProviderBackend>,
+    threshold: usize,
+}
+
+impl ShmPool {
+    /// Create the POSIX shared-memory segment of the pool
+    pub fn new(config: SharedMemory) -> Result<Self> {
+        let provider = ShmProviderBuilder::default_backend(config.pool_size)
+            .wait()
+            .map_err(|e| ArenaError::Internal(format!("Failed to create shared-memory pool: {}", e)))?;
+        Ok(Self {
+            provider,
+            threshold: config.threshold,
+        })
+    }
+
+    /// Allocate a buffer for a payload of `len` bytes if it is large enough
+    ///
+    /// Returns None for payloads below the threshold, or that do not fit in the pool,
+    /// which are published without shared memory.
+    pub fn alloc(&self, len: usize) -> Option<ZShmMut> {
+        if len == 0 || len < self.threshold {
+            return None;
+        }
+        match self.provider.alloc(len).with_policy::<GarbageCollect>().wait() {
+            Ok(buffer) => Some(buffer),
+            Err(e) => {
+                tracing::debug!("Shared-memory pool full, publishing {} bytes without it: {:?}", len, e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alloc_large_payloads() {
+        let pool = ShmPool::new(SharedMemory {
+            pool_size: 64 * 1024,
+            threshold: 1024,
+        })
+        .unwrap();
+
+        assert!(pool.alloc(16).is_none());
+
+        let large = pool.alloc(4096).unwrap();
+        assert_eq!(large.len(), 4096);
+
+        // Payloads larger than the pool fall back to regular bytes
+        assert!(pool.alloc(128 * 1024).is_none());
+    }
+}
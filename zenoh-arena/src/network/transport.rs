@@ -0,0 +1,304 @@
+//! Transport abstraction for the network layer
+//!
+//! The arena network layer needs only a handful of primitives from the underlying
+//! middleware: publishers, subscribers, queryables, queries and liveliness tokens.
+//! The [`Transport`] trait captures exactly these primitives so that the node state
+//! machine can run either on a real [`zenoh::Session`] (the default) or on an
+//! in-memory simulated network (see [`MemoryNetwork`](super::MemoryNetwork)).
+//!
+//! Declared entities are represented by handles which undeclare the entity when
+//! dropped, mirroring zenoh's own semantics.
+
+use std::any::Any;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use zenoh::bytes::ZBytes;
+use zenoh::key_expr::KeyExpr;
+use zenoh::qos::{CongestionControl, Priority, Reliability};
+use zenoh::sample::SampleKind;
+
+use crate::error::{ArenaError, Result};
+
+/// Quality of service of the traffic on a link
+///
+/// Applied to publishers and queries. Replies follow the priority and congestion
+/// control of their query, only `express` is taken from the queryable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LinkQos {
+    /// Priority of the traffic
+    pub priority: Priority,
+    /// Whether to drop messages or to wait when the network is congested
+    pub congestion_control: CongestionControl,
+    /// Send messages at once instead of batching them, for lower latency
+    pub express: bool,
+    /// Reliability of published samples (ignored by queries)
+    pub reliability: Reliability,
+}
+
+impl LinkQos {
+    /// QoS of latency-sensitive traffic: real-time priority, express, dropped when congested
+    pub fn realtime() -> Self {
+        Self {
+            priority: Priority::RealTime,
+            congestion_control: CongestionControl::Drop,
+            express: true,
+            reliability: Reliability::Reliable,
+        }
+    }
+
+    /// Default QoS of queries: waits when the network is congested
+    pub fn request() -> Self {
+        Self {
+            congestion_control: CongestionControl::Block,
+            ..Self::default()
+        }
+    }
+
+    /// QoS of traffic that must not be lost: reliable, waits when the network is congested
+    pub fn reliable() -> Self {
+        Self {
+            congestion_control: CongestionControl::Block,
+            reliability: Reliability::Reliable,
+            ..Self::default()
+        }
+    }
+}
+
+/// Sample delivered by a subscriber, a liveliness subscriber or a query reply
+#[derive(Debug, Clone)]
+pub struct TransportSample {
+    /// Key expression the sample was published on
+    pub key_expr: KeyExpr<'static>,
+    /// Sample payload (empty for liveliness samples)
+    pub payload: ZBytes,
+    /// Put or Delete
+    pub kind: SampleKind,
+    /// Id of the session that published the sample, if the transport reports it
+    pub source: Option<String>,
+}
+
+impl TransportSample {
+    /// Create a Put sample
+    pub fn put(key_expr: KeyExpr<'static>, payload: ZBytes) -> Self {
+        Self {
+            key_expr,
+            payload,
+            kind: SampleKind::Put,
+            source: None,
+        }
+    }
+
+    /// Create a Delete sample with an empty payload
+    pub fn delete(key_expr: KeyExpr<'static>) -> Self {
+        Self {
+            key_expr,
+            payload: ZBytes::new(),
+            kind: SampleKind::Delete,
+            source: None,
+        }
+    }
+
+    /// Set the id of the session that published the sample
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+}
+
+/// Reply to a query: a sample on success, an error payload otherwise
+pub type TransportReply = std::result::Result<TransportSample, ZBytes>;
+
+/// Opaque handle keeping a declared entity alive
+///
+/// The entity is undeclared when the handle is dropped.
+pub type TransportToken = Box<dyn Any + Send + Sync>;
+
+/// Publisher declared on a transport
+pub trait TransportPublisher: Send + Sync {
+    /// Key expression the publisher was declared on
+    fn key_expr(&self) -> &KeyExpr<'static>;
+
+    /// Publish a payload
+    fn put(&self, payload: ZBytes) -> BoxFuture<'_, Result<()>>;
+}
+
+/// Replies to a received query on behalf of the transport
+pub trait QueryResponder: Send + Sync {
+    /// Send a successful reply
+    fn reply(&self, key_expr: KeyExpr<'static>, payload: ZBytes) -> BoxFuture<'_, Result<()>>;
+
+    /// Send an error reply
+    fn reply_err(&self, payload: ZBytes) -> BoxFuture<'_, Result<()>>;
+}
+
+/// Query received by a queryable
+///
+/// The query is finalized (no more replies are possible) when it is dropped.
+#[derive(Clone)]
+pub struct TransportQuery {
+    key_expr: KeyExpr<'static>,
+    payload: Option<ZBytes>,
+    source: Option<String>,
+    responder: Arc<dyn QueryResponder>,
+}
+
+impl std::fmt::Debug for TransportQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransportQuery")
+            .field("key_expr", &self.key_expr)
+            .field("source", &self.source)
+            .finish()
+    }
+}
+
+impl TransportQuery {
+    /// Create a new query wrapping a transport-specific responder
+    pub fn new(
+        key_expr: KeyExpr<'static>,
+        payload: Option<ZBytes>,
+        responder: Arc<dyn QueryResponder>,
+    ) -> Self {
+        Self {
+            key_expr,
+            payload,
+            source: None,
+            responder,
+        }
+    }
+
+    /// Set the id of the session that sent the query
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Key expression of the query
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// Optional payload sent with the query
+    pub fn payload(&self) -> Option<&ZBytes> {
+        self.payload.as_ref()
+    }
+
+    /// Id of the session that sent the query, if the transport reports it
+    pub fn source(&self) -> Option<&str> {
+        self.source.as_deref()
+    }
+
+    /// Send a successful reply
+    pub async fn reply(&self, key_expr: KeyExpr<'static>, payload: ZBytes) -> Result<()> {
+        self.responder.reply(key_expr, payload).await
+    }
+
+    /// Send an error reply
+    pub async fn reply_err(&self, payload: ZBytes) -> Result<()> {
+        self.responder.reply_err(payload).await
+    }
+}
+
+/// Receiving side of a declared subscriber, liveliness subscriber or queryable
+///
+/// Items are received through a channel; the underlying entity stays declared
+/// as long as the receiver is alive.
+pub struct TransportReceiver<T> {
+    key_expr: KeyExpr<'static>,
+    receiver: flume::Receiver<T>,
+    _token: TransportToken,
+}
+
+impl<T> std::fmt::Debug for TransportReceiver<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransportReceiver")
+            .field("type", &std::any::type_name::<T>())
+            .field("key_expr", &self.key_expr)
+            .finish()
+    }
+}
+
+impl<T> TransportReceiver<T> {
+    /// Create a new receiver from a channel and the token keeping the entity alive
+    pub fn new(key_expr: KeyExpr<'static>, receiver: flume::Receiver<T>, token: TransportToken) -> Self {
+        Self {
+            key_expr,
+            receiver,
+            _token: token,
+        }
+    }
+
+    /// Key expression the entity was declared on
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// Wait for the next item
+    pub async fn recv_async(&self) -> Result<T> {
+        self.receiver.recv_async().await.map_err(|_| {
+            ArenaError::Internal(format!("Transport channel closed for '{}'", self.key_expr))
+        })
+    }
+}
+
+/// Network primitives required by the arena
+///
+/// Implemented for [`zenoh::Session`] and for
+/// [`MemoryTransport`](super::MemoryTransport).
+pub trait Transport: Send + Sync + 'static {
+    /// Declare a publisher on a key expression with the given quality of service
+    fn declare_publisher(
+        &self,
+        key_expr: KeyExpr<'static>,
+        qos: LinkQos,
+    ) -> BoxFuture<'_, Result<Box<dyn TransportPublisher>>>;
+
+    /// Declare a subscriber on a key expression
+    fn declare_subscriber(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportSample>>>;
+
+    /// Declare a queryable on a key expression, replying with the given quality of service
+    /// (see [`LinkQos`] for the part that applies to replies)
+    fn declare_queryable(
+        &self,
+        key_expr: KeyExpr<'static>,
+        qos: LinkQos,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportQuery>>>;
+
+    /// Send a query with the given quality of service; the returned channel is closed once
+    /// the query is finalized
+    fn get(
+        &self,
+        key_expr: KeyExpr<'static>,
+        payload: Option<ZBytes>,
+        qos: LinkQos,
+    ) -> BoxFuture<'_, Result<flume::Receiver<TransportReply>>>;
+
+    /// Declare a liveliness token
+    fn declare_liveliness_token(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<TransportToken>>;
+
+    /// Query currently alive liveliness tokens; the channel is closed when done
+    fn get_liveliness(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<flume::Receiver<TransportSample>>>;
+
+    /// Subscribe to liveliness changes, optionally receiving already alive tokens first
+    fn declare_liveliness_subscriber(
+        &self,
+        key_expr: KeyExpr<'static>,
+        history: bool,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportSample>>>;
+
+    /// Whether received samples and queries report their source session
+    ///
+    /// Hosts reject handshakes without a source when it does.
+    fn reports_sources(&self) -> bool {
+        false
+    }
+}
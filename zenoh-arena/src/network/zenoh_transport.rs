@@ -0,0 +1,258 @@
+//! [`Transport`] implementation backed by a real zenoh session
+//!
+//! All entities are declared with callbacks forwarding into flume channels so that
+//! the rest of the network layer can consume them uniformly.
+//!
+//! Publications and queries carry zenoh [`SourceInfo`] with the entity id of the sender,
+//! which is reported as the source of received samples and queries (the zenoh id of the
+//! sending session).
+//!
+//! The source info is chosen by the sender: it tells apart well-behaved sessions, but does
+//! not authenticate against a malicious zenoh peer, which can claim the id of another
+//! session. Restrict who can join the network (zenoh access control, TLS or user/password
+//! authentication) when peers are not trusted.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use zenoh::bytes::ZBytes;
+use zenoh::key_expr::KeyExpr;
+use zenoh::query::{Query, Reply};
+use zenoh::sample::{Sample, SourceInfo};
+
+use crate::error::Result;
+use crate::network::transport::{
+    LinkQos, QueryResponder, Transport, TransportPublisher, TransportQuery, TransportReceiver,
+    TransportReply, TransportSample, TransportToken,
+};
+
+impl From<&Sample> for TransportSample {
+    fn from(sample: &Sample) -> Self {
+        Self {
+            key_expr: sample.key_expr().clone(),
+            payload: sample.payload().clone(),
+            kind: sample.kind(),
+            source: sample.source_info().map(source_of),
+        }
+    }
+}
+
+/// Session id of the entity described by a source info
+fn source_of(source_info: &SourceInfo) -> String {
+    source_info.source_id().zid().to_string()
+}
+
+fn reply_to_transport(reply: Reply) -> TransportReply {
+    match reply.result() {
+        Ok(sample) => Ok(TransportSample::from(sample)),
+        Err(err) => Err(err.payload().clone()),
+    }
+}
+
+/// Publisher declared on a zenoh session
+struct ZenohPublisher {
+    key_expr: KeyExpr<'static>,
+    publisher: zenoh::pubsub::Publisher<'static>,
+    /// Sequence number of the next publication
+    next_sn: AtomicU32,
+}
+
+impl TransportPublisher for ZenohPublisher {
+    fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    fn put(&self, payload: ZBytes) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            let sn = self.next_sn.fetch_add(1, Ordering::Relaxed);
+            self.publisher
+                .put(payload)
+                .source_info(SourceInfo::new(self.publisher.id(), sn))
+                .await?;
+            Ok(())
+        })
+    }
+}
+
+/// Responder wrapping a zenoh query
+struct ZenohResponder {
+    query: Query,
+    /// Quality of service of the replies
+    qos: LinkQos,
+}
+
+impl QueryResponder for ZenohResponder {
+    fn reply(&self, key_expr: KeyExpr<'static>, payload: ZBytes) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            // Replies take the priority and congestion control of their query
+            self.query.reply(key_expr, payload).express(self.qos.express).await?;
+            Ok(())
+        })
+    }
+
+    fn reply_err(&self, payload: ZBytes) -> BoxFuture<'_, Result<()>> {
+        Box::pin(async move {
+            self.query.reply_err(payload).await?;
+            Ok(())
+        })
+    }
+}
+
+impl Transport for zenoh::Session {
+    fn declare_publisher(
+        &self,
+        key_expr: KeyExpr<'static>,
+        qos: LinkQos,
+    ) -> BoxFuture<'_, Result<Box<dyn TransportPublisher>>> {
+        Box::pin(async move {
+            let publisher = zenoh::Session::declare_publisher(self, key_expr.clone())
+                .priority(qos.priority)
+                .congestion_control(qos.congestion_control)
+                .express(qos.express)
+                .reliability(qos.reliability)
+                .await?;
+            Ok(Box::new(ZenohPublisher {
+                key_expr,
+                publisher,
+                next_sn: AtomicU32::new(0),
+            }) as Box<dyn TransportPublisher>)
+        })
+    }
+
+    fn declare_subscriber(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportSample>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let subscriber = zenoh::Session::declare_subscriber(self, key_expr.clone())
+                .callback(move |sample| {
+                    let _ = tx.send(TransportSample::from(&sample));
+                })
+                .await?;
+            Ok(TransportReceiver::new(key_expr, rx, Box::new(subscriber)))
+        })
+    }
+
+    fn declare_queryable(
+        &self,
+        key_expr: KeyExpr<'static>,
+        qos: LinkQos,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportQuery>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let queryable = zenoh::Session::declare_queryable(self, key_expr.clone())
+                .callback(move |query| {
+                    let source = query.source_info().map(source_of);
+                    let mut transport_query = TransportQuery::new(
+                        query.key_expr().clone(),
+                        query.payload().cloned(),
+                        Arc::new(ZenohResponder { query, qos }),
+                    );
+                    if let Some(source) = source {
+                        transport_query = transport_query.with_source(source);
+                    }
+                    let _ = tx.send(transport_query);
+                })
+                .await?;
+            Ok(TransportReceiver::new(key_expr, rx, Box::new(queryable)))
+        })
+    }
+
+    fn get(
+        &self,
+        key_expr: KeyExpr<'static>,
+        payload: Option<ZBytes>,
+        qos: LinkQos,
+    ) -> BoxFuture<'_, Result<flume::Receiver<TransportReply>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            // Queries are not sequenced, the source info only identifies the session
+            let mut builder = zenoh::Session::get(self, key_expr)
+                .priority(qos.priority)
+                .congestion_control(qos.congestion_control)
+                .express(qos.express)
+                .source_info(SourceInfo::new(self.id(), 0))
+                .callback(move |reply| {
+                    let _ = tx.send(reply_to_transport(reply));
+                });
+            if let Some(payload) = payload {
+                builder = builder.payload(payload);
+            }
+            builder.await?;
+            Ok(rx)
+        })
+    }
+
+    fn declare_liveliness_token(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<TransportToken>> {
+        Box::pin(async move {
+            let token = self.liveliness().declare_token(key_expr).await?;
+            Ok(Box::new(token) as TransportToken)
+        })
+    }
+
+    fn get_liveliness(
+        &self,
+        key_expr: KeyExpr<'static>,
+    ) -> BoxFuture<'_, Result<flume::Receiver<TransportSample>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            self.liveliness()
+                .get(key_expr)
+                .callback(move |reply| {
+                    if let Ok(sample) = reply_to_transport(reply) {
+                        let _ = tx.send(sample);
+                    }
+                })
+                .await?;
+            Ok(rx)
+        })
+    }
+
+    fn declare_liveliness_subscriber(
+        &self,
+        key_expr: KeyExpr<'static>,
+        history: bool,
+    ) -> BoxFuture<'_, Result<TransportReceiver<TransportSample>>> {
+        Box::pin(async move {
+            let (tx, rx) = flume::unbounded();
+            let subscriber = self
+                .liveliness()
+                .declare_subscriber(key_expr.clone())
+                .history(history)
+                .callback(move |sample| {
+                    let _ = tx.send(TransportSample::from(&sample));
+                })
+                .await?;
+            Ok(TransportReceiver::new(key_expr, rx, Box::new(subscriber)))
+        })
+    }
+
+    fn reports_sources(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zenoh::qos::{CongestionControl, Priority};
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_publisher_qos() {
+        let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+        let key_expr = KeyExpr::try_from("test/qos").unwrap();
+        let subscriber = zenoh::Session::declare_subscriber(&session, key_expr.clone()).await.unwrap();
+
+        let publisher = Transport::declare_publisher(&session, key_expr, LinkQos::realtime()).await.unwrap();
+        publisher.put(ZBytes::from("action")).await.unwrap();
+        let sample = subscriber.recv_async().await.unwrap();
+        assert_eq!(sample.priority(), Priority::RealTime);
+        assert_eq!(sample.congestion_control(), CongestionControl::Drop);
+        assert!(sample.express());
+    }
+}
@@ -0,0 +1,109 @@
+/// Buffer for actions issued while searching for a host
+use std::collections::VecDeque;
+
+use crate::node::types::ActionBuffering;
+
+/// Actions issued while the node has no host, waiting to be replayed
+///
+/// Kept by the node across searches. Bounded by the configured capacity, dropping the
+/// oldest action first; actions older than the configured maximum age are dropped
+/// instead of being replayed.
+pub(crate) struct ActionBuffer<A> {
+    policy: ActionBuffering,
+    actions: VecDeque<(tokio::time::Instant, A)>,
+}
+
+impl<A> ActionBuffer<A> {
+    pub(crate) fn new(policy: ActionBuffering) -> Self {
+        Self {
+            policy,
+            actions: VecDeque::new(),
+        }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Buffer an action
+    ///
+    /// Returns false if buffering is disabled and the action was dropped.
+    pub(crate) fn push(&mut self, action: A) -> bool {
+        if self.policy.capacity == 0 {
+            return false;
+        }
+        self.drop_expired();
+        if self.actions.len() >= self.policy.capacity {
+            self.actions.pop_front();
+            tracing::debug!("Action buffer full, dropped the oldest action");
+        }
+        self.actions.push_back((tokio::time::Instant::now(), action));
+        true
+    }
+
+    /// Take the oldest action that has not expired
+    pub(crate) fn pop(&mut self) -> Option<A> {
+        self.drop_expired();
+        self.actions.pop_front().map(|(_, action)| action)
+    }
+
+    /// Look at the oldest action that has not expired, leaving it in the buffer
+    pub(crate) fn peek(&mut self) -> Option<&A> {
+        self.drop_expired();
+        self.actions.front().map(|(_, action)| action)
+    }
+
+    /// Remove the oldest action, once replayed after a `peek`
+    pub(crate) fn discard(&mut self) {
+        self.actions.pop_front();
+    }
+
+    fn drop_expired(&mut self) {
+        let Some(max_age) = self.policy.max_age else {
+            return;
+        };
+        let before = self.actions.len();
+        self.actions.retain(|(issued_at, _)| issued_at.elapsed() <= max_age);
+        let expired = before - self.actions.len();
+        if expired > 0 {
+            tracing::debug!("Dropped {} expired buffered action(s)", expired);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_action_buffer_capacity_and_expiry() {
+        let mut buffer = ActionBuffer::new(ActionBuffering {
+            capacity: 2,
+            max_age: Some(Duration::from_secs(1)),
+        });
+        assert!(buffer.push(1));
+        tokio::time::sleep(Duration::from_millis(600)).await;
+        assert!(buffer.push(2));
+        assert!(buffer.push(3));
+
+        // The oldest action was dropped to make room
+        assert_eq!(buffer.pop(), Some(2));
+
+        // Peeking leaves the action in the buffer until discarded
+        assert_eq!(buffer.peek(), Some(&3));
+        assert_eq!(buffer.peek(), Some(&3));
+
+        // The remaining action expires
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        assert_eq!(buffer.peek(), None);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_action_buffer_disabled() {
+        let mut buffer = ActionBuffer::new(ActionBuffering::disabled());
+        assert!(!buffer.push(1));
+        assert!(buffer.is_empty());
+    }
+}
@@ -1,12 +1,13 @@
 /// Node management module
 use std::sync::Arc;
 
+use super::action_buffer::ActionBuffer;
 use super::config::NodeConfig;
 use super::game_engine::{EngineFactory, GameEngine};
 use crate::error::{ArenaError, Result};
-use crate::network::NodeLivelinessToken;
+use crate::network::{NodeLivelinessToken, Transport};
 use crate::network::keyexpr::NodeType;
-use super::types::{NodeId, NodeState, NodeStateInternal, StepResult};
+use super::types::{ActionViolations, NodeId, NodeState, NodeStateInternal, QueueStats, StepResult};
 
 /// Commands that can be sent to the node
 #[derive(Debug, Clone)]
@@ -15,6 +16,31 @@ pub enum NodeCommand<A> {
     GameAction(A),
     /// Stop the node's run loop
     Stop,
+    /// Leave the current game and search for another host without becoming host
+    ///
+    /// A client disconnects, a host closes its game. The node keeps searching until it
+    /// connects to a host, as in client-only mode; a client skips the host it left in its
+    /// first search. Ignored in force_host mode.
+    Leave,
+    /// Become host immediately, keeping the current game state
+    ///
+    /// Ignored if the node already hosts.
+    BecomeHost,
+    /// Leave the current game (if any) and restart the search for hosts
+    ///
+    /// The search may end with the node hosting, unless it is in client-only mode.
+    /// Ignored in force_host mode.
+    SearchAgain,
+    /// Send a chat message to the members of the session, or to a single member
+    ///
+    /// Messages are relayed by the host and returned by `step` as `StepResult::Chat`.
+    /// Dropped while searching for a host.
+    Chat {
+        /// Member to send the message to (None for everybody)
+        recipient: Option<NodeId>,
+        /// Text of the message, at most [`MAX_CHAT_LEN`](crate::MAX_CHAT_LEN) bytes
+        text: String,
+    },
 }
 
 /// Main Node interface - manages host/client behavior and game sessions
@@ -31,8 +57,8 @@ pub struct Node<E: GameEngine, F: EngineFactory<E>> {
     /// Current node state
     state: NodeStateInternal<E>,
 
-    /// Zenoh session
-    session: zenoh::Session,
+    /// Network transport (zenoh session or simulated network)
+    transport: Arc<dyn Transport>,
 
     /// Engine factory - called when transitioning to host mode
     get_engine: Arc<F>,
@@ -49,18 +75,36 @@ pub struct Node<E: GameEngine, F: EngineFactory<E>> {
 
     /// Current game state (maintained across state transitions)
     game_state: Option<E::State>,
+
+    /// Result of a step whose replay of buffered actions was cancelled, returned by the next step
+    pending_result: Option<StepResult<E::State, E::Event>>,
+
+    /// Actions issued while searching, replayed to the next host
+    action_buffer: ActionBuffer<E::Action>,
+
+    /// Samples ignored in previous states (see `ignored_samples()`)
+    ignored_samples: u64,
+
+    /// Spoofed samples dropped in previous states (see `spoofed_samples()`)
+    spoofed_samples: u64,
+
+    /// Malformed samples dropped in previous states (see `malformed_samples()`)
+    malformed_samples: u64,
+
+    /// Action limit violations in previous states (see `action_violations()`)
+    action_violations: ActionViolations,
 }
 
 impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
     /// Create a new Node instance (internal use only - use builder pattern via SessionExt)
     pub(crate) async fn new_internal(
         config: NodeConfig,
-        session: zenoh::Session,
+        transport: Arc<dyn Transport>,
         get_engine: F,
     ) -> Result<Self> {
         let id = config.node_id.clone();
 
-        tracing::info!("Node '{}' initialized with Zenoh session", id);
+        tracing::info!("Node '{}' initialized", id);
 
         // Wrap the engine factory in Arc for shared ownership
         let get_engine = Arc::new(get_engine);
@@ -68,10 +112,11 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
         // Create liveliness token for this node's identity (NodeType::Node)
         // This protects the node name from conflicts with other nodes
         let node_liveliness_token = NodeLivelinessToken::declare(
-            &session,
+            &*transport,
             config.keyexpr_prefix.clone(),
             NodeType::Node,
             id.clone(),
+            None,
         )
         .await?;
 
@@ -85,8 +130,8 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
             // Use the constructor function to create host state with no initial state
             NodeStateInternal::host(
                 &*get_engine,
-                &session,
-                config.keyexpr_prefix.clone(),
+                &*transport,
+                &config,
                 &id,
                 None, // No initial state when force starting as host
             )
@@ -96,16 +141,23 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
             NodeStateInternal::searching()
         };
 
+        let action_buffer = ActionBuffer::new(config.action_buffering);
         let node = Self {
             id,
             config,
             state,
-            session,
+            transport,
             get_engine,
             command_rx,
             command_tx,
             _node_liveliness_token: node_liveliness_token,
             game_state: None,
+            pending_result: None,
+            action_buffer,
+            ignored_samples: 0,
+            spoofed_samples: 0,
+            malformed_samples: 0,
+            action_violations: ActionViolations::default(),
         };
 
         Ok(node)
@@ -116,9 +168,9 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
         &self.id
     }
 
-    /// Get reference to Zenoh session
-    pub fn session(&self) -> &zenoh::Session {
-        &self.session
+    /// Get reference to the network transport
+    pub fn transport(&self) -> &Arc<dyn Transport> {
+        &self.transport
     }
 
     /// Get a sender for sending commands to this node
@@ -133,7 +185,12 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
     /// - A new game state is produced by the engine (returns GameState)
     /// - The step timeout (configured in NodeConfig) elapses (returns Timeout)
     /// - A Stop command is received (returns Stop)
-    pub async fn step(&mut self) -> Result<StepResult<E::State>> {
+    ///
+    /// Cancel-safe: the future can be dropped (e.g. losing a `tokio::select!` race against
+    /// UI events) without losing the node's role, connections or queued commands. An
+    /// operation in flight, like a connection attempt, is started again by the next call.
+    /// A step dropped while replaying buffered actions returns its result from the next call.
+    pub async fn step(&mut self) -> Result<StepResult<E::State, E::Event>> {
         // If force_host is enabled, only Host state is allowed
         if self.config.force_host && !matches!(self.state, NodeStateInternal::Host { .. }) {
             return Err(ArenaError::Internal(
@@ -141,16 +198,43 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
             ));
         }
 
-        // Dispatch based on current state using state-specific run methods
-        let node_state = std::mem::replace(&mut self.state, NodeStateInternal::Stop);
-        let (next_node_state, step_result) = match node_state {
+        if self.pending_result.is_none() {
+            let step_result = self.step_state().await?;
+            // Update stored game state if a new one was produced
+            if let StepResult::GameState(new_state) = &step_result {
+                self.game_state = Some(new_state.clone());
+            }
+            self.pending_result = Some(step_result);
+        }
+        // Replay actions issued while searching to the new host
+        if !self.action_buffer.is_empty() {
+            match &self.state {
+                NodeStateInternal::Client(client_state) => {
+                    client_state.replay_actions(&self.id, &mut self.action_buffer).await;
+                }
+                NodeStateInternal::Host(host_state) => {
+                    host_state.replay_actions(&self.id, &mut self.action_buffer).await;
+                }
+                NodeStateInternal::SearchingHost(_) | NodeStateInternal::Stop => {}
+            }
+        }
+        Ok(self.pending_result.take().expect("step result computed"))
+    }
+
+    /// Run one step of the current state, applying the transition it returns
+    async fn step_state(&mut self) -> Result<StepResult<E::State, E::Event>> {
+        // Dispatch based on current state using state-specific run methods.
+        // The state stays in place while awaiting, so dropping this future keeps the node
+        // in its current role; transitions are applied only once the step completes.
+        let (next_node_state, step_result) = match &mut self.state {
             NodeStateInternal::SearchingHost(searching_state) => {
                 searching_state
                     .step(
-                        &self.session,
+                        &*self.transport,
                         &self.config,
                         &self.id,
                         &self.command_rx,
+                        &mut self.action_buffer,
                         &*self.get_engine,
                         self.game_state.clone(),
                     )
@@ -158,30 +242,87 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
             }
             NodeStateInternal::Client(client_state) => {
                 client_state
-                    .step(&self.config, &self.id, &self.command_rx, self.game_state.clone())
+                    .step(
+                        &self.config,
+                        &self.id,
+                        &*self.transport,
+                        &self.command_rx,
+                        &*self.get_engine,
+                        self.game_state.clone(),
+                    )
                     .await?
             }
             NodeStateInternal::Host(host_state) => {
                 host_state
-                    .step(&self.config, &self.id, &self.session, &self.command_rx)
+                    .step(
+                        &self.config,
+                        &self.id,
+                        &*self.transport,
+                        &self.command_rx,
+                        &*self.get_engine,
+                    )
                     .await?
             }
             NodeStateInternal::Stop => {
                 // If already stopped, remain stopped
-                (
-                    NodeStateInternal::Stop,
-                    StepResult::Stop,
-                )
+                (None, StepResult::Stop)
             }
         };
-        self.state = next_node_state;
-        // Update stored game state if a new one was produced
-        if let StepResult::GameState(new_state) = &step_result {
-            self.game_state = Some(new_state.clone());
+        if let Some(next_node_state) = next_node_state {
+            self.ignored_samples += self.state.ignored_samples();
+            self.spoofed_samples += self.state.spoofed_samples();
+            self.malformed_samples += self.state.malformed_samples();
+            self.action_violations += self.state.action_violations();
+            self.state = next_node_state;
         }
         Ok(step_result)
     }
 
+    /// Number of actions and states ignored since the node was created
+    ///
+    /// Counts traffic from hosts other than the current one, from earlier hosting sessions
+    /// and from nodes not admitted by this host.
+    pub fn ignored_samples(&self) -> u64 {
+        self.ignored_samples + self.state.ignored_samples()
+    }
+
+    /// Number of actions dropped as spoofed since the node was created
+    ///
+    /// Counts actions received while hosting whose sender id does not match the transport
+    /// session the sender connected from, i.e. actions forged on behalf of another client.
+    /// Sources reported by a zenoh session are set by the sender, so a malicious zenoh peer
+    /// can still forge them.
+    pub fn spoofed_samples(&self) -> u64 {
+        self.spoofed_samples + self.state.spoofed_samples()
+    }
+
+    /// Number of malformed samples dropped since the node was created
+    ///
+    /// Counts handshake queries received while hosting, and actions or states received
+    /// from other nodes, whose key expression or payload could not be parsed.
+    pub fn malformed_samples(&self) -> u64 {
+        self.malformed_samples + self.state.malformed_samples()
+    }
+
+    /// Counters of client actions that broke the action limits since the node was created
+    ///
+    /// Counts actions received while hosting that exceeded the rate or size limits or
+    /// failed the engine's validation, and the clients kicked for them
+    /// (see [`NodeBuilder::action_limits`](crate::NodeBuilder::action_limits)).
+    pub fn action_violations(&self) -> ActionViolations {
+        let mut action_violations = self.action_violations;
+        action_violations += self.state.action_violations();
+        action_violations
+    }
+
+    /// Depth of the queues between the host and the engine of each room, and items they dropped
+    ///
+    /// Empty when the node is not hosting (see
+    /// [`NodeBuilder::engine_queues`](crate::NodeBuilder::engine_queues)).
+    pub fn queue_stats(&self) -> Vec<QueueStats> {
+        self.state.queue_stats()
+    }
+
     /// Get the current node state without advancing the state machine
     pub fn state(&self) -> NodeState {
         self.state.to_node_state()
@@ -202,8 +343,10 @@ impl<E: GameEngine, F: EngineFactory<E>> Node<E, F> {
 mod tests {
     use crate::node::session_ext::SessionExt;
     use super::GameEngine;
+    use crate::codec::ZenohCodec;
 
     use super::*;
+    use crate::node::types::PlayerSlot;
 
     // Simple test engine for testing purposes
     #[derive(Debug)]
@@ -213,10 +356,10 @@ mod tests {
     }
 
     impl TestEngine {
-        fn new(_host_id: NodeId, input_rx: flume::Receiver<(NodeId, u32)>, output_tx: flume::Sender<String>) -> Self {
+        fn new(_host_id: NodeId, input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>, output_tx: flume::Sender<String>) -> Self {
             // Spawn a task to process actions
             std::thread::spawn(move || {
-                while let Ok((_node_id, _action)) = input_rx.recv() {
+                while let Ok((_node_id, _slot, _action)) = input_rx.recv() {
                     // Process the action
                     let _ = output_tx.send("processed".to_string());
                 }
@@ -231,6 +374,8 @@ mod tests {
     impl GameEngine for TestEngine {
         type Action = u32;
         type State = String;
+        type Event = ();
+        type Codec = ZenohCodec;
 
         fn max_clients(&self) -> Option<usize> {
             self.max_clients
@@ -429,4 +574,1643 @@ mod tests {
             .await
             .unwrap();
     }
+
+    /// Drive a node on its own task, reporting role changes
+    fn spawn_stepping<E, F>(mut node: Node<E, F>) -> (tokio::task::JoinHandle<()>, flume::Receiver<crate::NodeRole>)
+    where
+        E: GameEngine + 'static,
+        E::Action: Sync,
+        E::State: Sync,
+        F: EngineFactory<E> + 'static,
+    {
+        let (role_tx, role_rx) = flume::unbounded();
+        let handle = tokio::spawn(async move {
+            loop {
+                match node.step().await {
+                    Ok(StepResult::RoleChanged(role)) => {
+                        let _ = role_tx.send(role);
+                    }
+                    Ok(StepResult::Stop) | Err(_) => break,
+                    Ok(_) => {}
+                }
+            }
+        });
+        (handle, role_rx)
+    }
+
+    async fn simulated_node(
+        transport: &crate::MemoryTransport,
+        name: &str,
+    ) -> Node<TestEngine, impl EngineFactory<TestEngine> + 'static> {
+        transport
+            .declare_arena_node(|host_id, input_rx, output_tx, _initial_state| TestEngine::new(host_id, input_rx, output_tx))
+            .name(name.to_string())
+            .unwrap()
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+            .await
+            .unwrap()
+    }
+
+    async fn next_role(roles: &flume::Receiver<crate::NodeRole>) -> crate::NodeRole {
+        tokio::time::timeout(std::time::Duration::from_secs(30), roles.recv_async())
+            .await
+            .expect("role change expected")
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_host_failover() {
+        let network = crate::MemoryNetwork::new();
+        network.set_default_conditions(crate::LinkConditions {
+            latency: std::time::Duration::from_millis(5),
+            ..Default::default()
+        });
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let (alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+
+        let (_bravo, bravo_roles) = spawn_stepping(simulated_node(&bravo_transport, "bravo").await);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+
+        // Host goes away: the client falls back to search and takes over
+        alpha.abort();
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_partition_promotes_client() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let (_alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        let (_bravo, bravo_roles) = spawn_stepping(simulated_node(&bravo_transport, "bravo").await);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+
+        network.partition(&alpha_transport, &bravo_transport);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+        assert!(alpha_roles.is_empty());
+    }
+
+    /// Race a step against a short timer so that the pending step is dropped
+    async fn cancel_step<E: GameEngine, F: EngineFactory<E>>(node: &mut Node<E, F>) {
+        tokio::select! {
+            _ = node.step() => panic!("step expected to be pending"),
+            () = tokio::time::sleep(std::time::Duration::from_millis(10)) => {}
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_step_cancel_keeps_role() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (step_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(step_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // Dropping pending steps leaves both nodes in their roles
+        cancel_step(&mut alpha).await;
+        cancel_step(&mut bravo).await;
+        assert!(matches!(alpha.state(), NodeState::Host { connected_clients, .. } if connected_clients.len() == 1));
+        assert!(matches!(bravo.state(), NodeState::Client { ref host_id, .. } if host_id.as_str() == "alpha"));
+
+        // The connection still works: an action of the client reaches the host's engine
+        bravo.sender().send(NodeCommand::GameAction(1)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(bravo_result, StepResult::GameState(ref s) if s == "processed"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_unconfirmed_client_dropped() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let charlie_transport = network.open();
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut charlie = simulated_node(&charlie_transport, "charlie").await;
+        let (charlie_result, _) = tokio::join!(charlie.step(), cancel_step(&mut alpha));
+        assert!(matches!(charlie_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // Bravo's step is dropped right after the host accepted it, before it declared its token
+        let bravo_id = NodeId::from_name("bravo".to_string()).unwrap();
+        let handshake_info = TestEngine::handshake_info();
+        let (connection, _) = tokio::join!(
+            crate::network::HostQuerier::connect_to(
+                &bravo_transport,
+                prefix,
+                bravo_id,
+                alpha.id().clone(),
+                &handshake_info,
+                crate::LinkQos::request(),
+            ),
+            cancel_step(&mut alpha)
+        );
+        assert!(connection.is_some());
+        assert!(matches!(alpha.state(), NodeState::Host { ref connected_clients, .. } if connected_clients.len() == 2));
+
+        // The host drops it once the search timeout elapsed, keeping the real client
+        for _ in 0..10 {
+            assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        }
+        assert!(matches!(
+            alpha.state(),
+            NodeState::Host { ref connected_clients, .. } if connected_clients == &vec![charlie.id().clone()]
+        ));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_reconnect_not_duplicated() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // Bravo connects again before the host saw it leave: same slot, counted once
+        let handshake_info = TestEngine::handshake_info();
+        let (connection, _) = tokio::join!(
+            crate::network::HostQuerier::connect_to(
+                &bravo_transport,
+                prefix,
+                bravo.id().clone(),
+                alpha.id().clone(),
+                &handshake_info,
+                crate::LinkQos::request(),
+            ),
+            cancel_step(&mut alpha)
+        );
+        let NodeState::Client { slot, .. } = bravo.state() else {
+            panic!("bravo is not a client");
+        };
+        assert_eq!(connection.map(|connection| connection.slot), Some(slot));
+        for _ in 0..10 {
+            assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        }
+        let NodeState::Host { connected_clients, players, .. } = alpha.state() else {
+            panic!("alpha is not hosting");
+        };
+        assert_eq!(connected_clients, vec![bravo.id().clone()]);
+        assert_eq!(players.len(), 2);
+
+        // Once bravo is gone, no phantom entry is left behind
+        drop(bravo);
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert!(matches!(alpha.state(), NodeState::Host { ref connected_clients, .. } if connected_clients.is_empty()));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_step_cancel_resumes_search() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        // A host that is not stepping never answers, so the search can only time out
+        let _alpha = room_node(&alpha_transport, "alpha").force_host(true).await.unwrap();
+        let mut bravo = room_node(&bravo_transport, "bravo").search_jitter_ms(200).await.unwrap();
+
+        // Steps dropped after 10ms still add up to the jitter and the search timeout
+        let start = tokio::time::Instant::now();
+        let mut cancelled = 0;
+        let result = loop {
+            match tokio::time::timeout(std::time::Duration::from_millis(10), bravo.step()).await {
+                Ok(result) => break result.unwrap(),
+                Err(_) => cancelled += 1,
+            }
+            assert!(cancelled < 100, "search restarted by cancelled steps");
+        };
+        assert!(matches!(result, StepResult::RoleChanged(crate::NodeRole::Host)));
+        assert!(start.elapsed() >= std::time::Duration::from_millis(500));
+        assert!(cancelled >= 40);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_role_commands() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let alpha = simulated_node(&alpha_transport, "alpha").await;
+        let alpha_sender = alpha.sender();
+        let (_alpha, alpha_roles) = spawn_stepping(alpha);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        let bravo = simulated_node(&bravo_transport, "bravo").await;
+        let bravo_sender = bravo.sender();
+        let (_bravo, bravo_roles) = spawn_stepping(bravo);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+
+        // The client takes over hosting, the old host leaves and joins it
+        bravo_sender.send(NodeCommand::BecomeHost).unwrap();
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+        alpha_sender.send(NodeCommand::Leave).unwrap();
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Client);
+
+        // A host leaving without hosting again hands the game over to its client
+        bravo_sender.send(NodeCommand::Leave).unwrap();
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+
+        // Searching again may end in hosting
+        alpha_sender.send(NodeCommand::SearchAgain).unwrap();
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        let alpha_role = next_role(&alpha_roles).await;
+        let bravo_role = next_role(&bravo_roles).await;
+        assert_ne!(alpha_role, bravo_role);
+    }
+
+    /// Step a node for a while, dropping the step pending at the end
+    async fn step_for<E: GameEngine, F: EngineFactory<E>>(node: &mut Node<E, F>, duration: std::time::Duration) {
+        let _ = tokio::time::timeout(duration, async {
+            loop {
+                node.step().await.unwrap();
+            }
+        })
+        .await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_client_leaves_only_host() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // The first search after leaving skips the host just left
+        bravo.sender().send(NodeCommand::Leave).unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::SearchingHost)));
+        let step_time = std::time::Duration::from_millis(100);
+        let (bravo_result, _) = tokio::join!(bravo.step(), step_for(&mut alpha, 5 * step_time));
+        assert!(matches!(bravo_result.unwrap(), StepResult::Timeout));
+        assert!(matches!(alpha.state(), NodeState::Host { ref connected_clients, .. } if connected_clients.is_empty()));
+
+        // Later searches may join it again, as a single client
+        let (bravo_result, _) = tokio::join!(bravo.step(), step_for(&mut alpha, 10 * step_time));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        step_for(&mut alpha, 10 * step_time).await;
+        let NodeState::Host { connected_clients, players, .. } = alpha.state() else {
+            panic!("alpha is not hosting");
+        };
+        assert_eq!(connected_clients, vec![bravo.id().clone()]);
+        assert_eq!(players.len(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_client_only() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let bravo = client_only_node(&bravo_transport).await;
+        let bravo_sender = bravo.sender();
+        let (_bravo, bravo_roles) = spawn_stepping(bravo);
+
+        // Without a host the node keeps searching
+        tokio::time::sleep(std::time::Duration::from_secs(20)).await;
+        assert!(bravo_roles.is_empty());
+
+        let (_alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+
+        // An explicit command still makes it host
+        bravo_sender.send(NodeCommand::BecomeHost).unwrap();
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+    }
+
+    async fn client_only_node(
+        transport: &crate::MemoryTransport,
+    ) -> Node<TestEngine, impl EngineFactory<TestEngine> + 'static> {
+        transport
+            .declare_arena_node(|host_id, input_rx, output_tx, _initial_state| TestEngine::new(host_id, input_rx, output_tx))
+            .name("bravo".to_string())
+            .unwrap()
+            .client_only(true)
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_split_brain_merge() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        // Both nodes become host while partitioned
+        network.partition(&alpha_transport, &bravo_transport);
+        let (_alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        let (_bravo, bravo_roles) = spawn_stepping(simulated_node(&bravo_transport, "bravo").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+
+        // Once healed, the lower ranked empty host yields and joins the other one
+        network.heal(&alpha_transport, &bravo_transport);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        assert!(alpha_roles.is_empty());
+        assert!(bravo_roles.is_empty());
+    }
+
+    // Test engine producing a new state every millisecond
+    struct BusyEngine {
+        _input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>,
+    }
+
+    impl GameEngine for BusyEngine {
+        type Action = u32;
+        type State = String;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            None
+        }
+    }
+
+    impl BusyEngine {
+        fn new(input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>, output_tx: flume::Sender<String>) -> Self {
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(std::time::Duration::from_millis(1));
+                let mut tick = 0u64;
+                loop {
+                    interval.tick().await;
+                    tick += 1;
+                    if output_tx.send(format!("tick {}", tick)).is_err() {
+                        break;
+                    }
+                }
+            });
+            Self { _input_rx: input_rx }
+        }
+    }
+
+    async fn busy_node(
+        transport: &crate::MemoryTransport,
+        name: &str,
+    ) -> Node<BusyEngine, impl EngineFactory<BusyEngine> + 'static> {
+        transport
+            .declare_arena_node(|_host_id, input_rx, output_tx, _initial_state| BusyEngine::new(input_rx, output_tx))
+            .name(name.to_string())
+            .unwrap()
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_busy_hosts_merge() {
+        let network = crate::MemoryNetwork::new();
+        network.set_default_conditions(crate::LinkConditions {
+            latency: std::time::Duration::from_millis(5),
+            ..Default::default()
+        });
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        // The look for other hosts outlasts the steps returning every new state
+        network.partition(&alpha_transport, &bravo_transport);
+        let (_alpha, alpha_roles) = spawn_stepping(busy_node(&alpha_transport, "alpha").await);
+        let (_bravo, bravo_roles) = spawn_stepping(busy_node(&bravo_transport, "bravo").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+
+        network.heal(&alpha_transport, &bravo_transport);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+        assert!(alpha_roles.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_merge_hands_off_clients() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let charlie_transport = network.open();
+        let delta_transport = network.open();
+        let merge_node = |transport, name| room_node(transport, name).host_merge(crate::MergePolicy::HandOff);
+
+        // Two games of two players form on both sides of a partition
+        network.partition(&alpha_transport, &bravo_transport);
+        network.partition(&alpha_transport, &delta_transport);
+        network.partition(&charlie_transport, &bravo_transport);
+        network.partition(&charlie_transport, &delta_transport);
+        let (_alpha, alpha_roles) = spawn_stepping(merge_node(&alpha_transport, "alpha").await.unwrap());
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        let (_bravo, bravo_roles) = spawn_stepping(merge_node(&bravo_transport, "bravo").await.unwrap());
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+        let (_charlie, charlie_roles) = spawn_stepping(merge_node(&charlie_transport, "charlie").await.unwrap());
+        assert_eq!(next_role(&charlie_roles).await, crate::NodeRole::Client);
+        let (_delta, delta_roles) = spawn_stepping(merge_node(&delta_transport, "delta").await.unwrap());
+        assert_eq!(next_role(&delta_roles).await, crate::NodeRole::Client);
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+        let delta_id = NodeId::from_name("delta".to_string()).unwrap();
+        let delta_notices = crate::network::NodeSubscriber::<crate::network::HostNotice, ZenohCodec>::new(
+            &delta_transport,
+            prefix,
+            crate::network::keyexpr::LinkType::Notice,
+            &delta_id,
+            None,
+        )
+        .await
+        .unwrap()
+        .with_sessions();
+
+        // Alpha ranks above bravo by id; bravo yields, handing its client off to alpha
+        network.heal_all();
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::SearchingHost);
+        let (sender, notice) = delta_notices.recv().await.unwrap();
+        assert_eq!(sender.as_str(), "bravo");
+        assert_eq!(
+            notice,
+            crate::network::HostNotice::HandOff {
+                host: NodeId::from_name("alpha".to_string()).unwrap()
+            }
+        );
+        assert_eq!(next_role(&delta_roles).await, crate::NodeRole::SearchingHost);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+        assert_eq!(next_role(&delta_roles).await, crate::NodeRole::Client);
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        assert!(alpha_roles.is_empty());
+        assert!(charlie_roles.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_merge_never() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        network.partition(&alpha_transport, &bravo_transport);
+        let (_alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        let bravo = room_node(&bravo_transport, "bravo")
+            .host_merge(crate::MergePolicy::Never)
+            .await
+            .unwrap();
+        let (_bravo, bravo_roles) = spawn_stepping(bravo);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+
+        network.heal(&alpha_transport, &bravo_transport);
+        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        assert!(alpha_roles.is_empty());
+        assert!(bravo_roles.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_actions_replayed_after_search() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        // Actions issued while searching reach the local engine once hosting
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        alpha.sender().send(NodeCommand::GameAction(1)).unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::GameState(ref s) if s == "processed"));
+
+        // ... or the host the node connects to
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        bravo.sender().send(NodeCommand::GameAction(2)).unwrap();
+        let (bravo_result, alpha_result) = tokio::join!(bravo.step(), step_until_state(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_search_under_input() {
+        let network = crate::MemoryNetwork::new();
+        network.set_default_conditions(crate::LinkConditions {
+            latency: std::time::Duration::from_millis(5),
+            ..Default::default()
+        });
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let (_alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+
+        // Actions keep coming faster than a handshake round trip while bravo searches
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let sender = bravo.sender();
+        let _input = tokio::spawn(async move {
+            while sender.send(NodeCommand::GameAction(1)).is_ok() {
+                tokio::time::sleep(std::time::Duration::from_millis(1)).await;
+            }
+        });
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_buffered_actions_expire() {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+
+        // Client-only keeps the node searching, so the action stays buffered
+        let mut alpha = room_node(&transport, "alpha")
+            .client_only(true)
+            .action_buffering(crate::ActionBuffering {
+                capacity: 8,
+                max_age: Some(std::time::Duration::from_millis(100)),
+            })
+            .await
+            .unwrap();
+        alpha.sender().send(NodeCommand::GameAction(1)).unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+        alpha.sender().send(NodeCommand::BecomeHost).unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        for _ in 0..5 {
+            assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_foreign_traffic_ignored() {
+        use crate::network::{keyexpr::LinkType, NodePublisher};
+
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let rogue_transport = network.open();
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // States broadcast by another host are ignored by the client
+        let rogue_id = NodeId::from_name("rogue".to_string()).unwrap();
+        let rogue_states = NodePublisher::<String, ZenohCodec>::new(&rogue_transport, prefix.clone(), LinkType::State, &rogue_id, None, None, crate::LinkQos::default())
+            .await
+            .unwrap()
+            .with_session(7);
+        rogue_states.put(&"forged".to_string()).await.unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+        assert_eq!(bravo.ignored_samples(), 1);
+
+        // Actions of unadmitted nodes and of other sessions are ignored by the host
+        let alpha_id = alpha.id().clone();
+        let rogue_actions = NodePublisher::<u32, ZenohCodec>::new(&rogue_transport, prefix.clone(), LinkType::Action, &rogue_id, Some(&alpha_id), None, crate::LinkQos::default())
+            .await
+            .unwrap()
+            .with_session(7);
+        rogue_actions.put(&1).await.unwrap();
+        let bravo_id = bravo.id().clone();
+        let stale_actions = NodePublisher::<u32, ZenohCodec>::new(&rogue_transport, prefix, LinkType::Action, &bravo_id, Some(&alpha_id), None, crate::LinkQos::default())
+            .await
+            .unwrap()
+            .with_session(7);
+        stale_actions.put(&1).await.unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert_eq!(alpha.ignored_samples(), 2);
+
+        // Traffic of the connection still flows
+        bravo.sender().send(NodeCommand::GameAction(1)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(bravo_result, StepResult::GameState(ref s) if s == "processed"));
+        assert_eq!(bravo.ignored_samples(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_spoofed_actions_dropped() {
+        use crate::network::{keyexpr::LinkType, NodePublisher};
+
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let rogue_transport = network.open();
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // Forged action on behalf of bravo, with the right session but from another transport session
+        let NodeStateInternal::Host(host_state) = &alpha.state else {
+            panic!("alpha is not hosting");
+        };
+        let session = host_state.session;
+        let alpha_id = alpha.id().clone();
+        let bravo_id = bravo.id().clone();
+        let forged_actions = NodePublisher::<u32, ZenohCodec>::new(&rogue_transport, prefix, LinkType::Action, &bravo_id, Some(&alpha_id), None, crate::LinkQos::default())
+            .await
+            .unwrap()
+            .with_session(session);
+        forged_actions.put(&1).await.unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert_eq!(alpha.spoofed_samples(), 1);
+        assert_eq!(alpha.ignored_samples(), 0);
+
+        // Actions really sent by bravo are still accepted
+        bravo.sender().send(NodeCommand::GameAction(1)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(bravo_result, StepResult::GameState(ref s) if s == "processed"));
+        assert_eq!(alpha.spoofed_samples(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_malformed_input_dropped() {
+        use crate::network::Transport;
+
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let rogue_transport = network.open();
+
+        let mut alpha = simulated_node(&alpha_transport, "alpha").await;
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let alpha_id = alpha.id().clone();
+
+        // Handshake queries with a glob client id or a garbled payload
+        let ke = |s: String| zenoh::key_expr::KeyExpr::try_from(s).unwrap();
+        let _glob = rogue_transport
+            .get(ke(format!("zenoh/arena/handshake/*/{}", alpha_id)), None, crate::LinkQos::request())
+            .await
+            .unwrap();
+        let garbled = rogue_transport
+            .get(ke(format!("zenoh/arena/handshake/rogue/{}", alpha_id)), Some(zenoh::bytes::ZBytes::from(vec![0xffu8; 12])), crate::LinkQos::request())
+            .await
+            .unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert!(matches!(garbled.recv_async().await, Ok(Err(_))));
+        assert_eq!(alpha.malformed_samples(), 2);
+
+        // A client still connects, and a garbled action does not disturb the game
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        let garbled_action = rogue_transport
+            .declare_publisher(ke(format!("zenoh/arena/action/{}/{}", bravo.id(), alpha_id)), crate::LinkQos::default())
+            .await
+            .unwrap();
+        garbled_action.put(zenoh::bytes::ZBytes::from(vec![1u8])).await.unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert_eq!(alpha.malformed_samples(), 3);
+
+        bravo.sender().send(NodeCommand::GameAction(1)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(bravo_result, StepResult::GameState(ref s) if s == "processed"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_actions_rate_limited() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let limits = crate::ActionLimits {
+            rate: Some(1),
+            ..Default::default()
+        };
+        let mut alpha = room_node(&alpha_transport, "alpha").action_limits(limits).await.unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // A burst of actions above the rate: only the first one reaches the engine
+        for action in 0..3 {
+            bravo.sender().send(NodeCommand::GameAction(action)).unwrap();
+        }
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        assert_eq!(alpha.action_violations().rate_limited, 2);
+        assert!(matches!(step_until_state(&mut bravo).await, StepResult::GameState(ref s) if s == "processed"));
+
+        // The client is not kicked by default and gets actions through once the rate allows
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        bravo.sender().send(NodeCommand::GameAction(3)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(bravo_result, StepResult::GameState(ref s) if s == "processed"));
+        assert_eq!(alpha.action_violations().kicked, 0);
+    }
+
+    // Test engine rejecting the action 0
+    struct PickyTestEngine(TestEngine);
+
+    impl GameEngine for PickyTestEngine {
+        type Action = u32;
+        type State = String;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            self.0.max_clients
+        }
+
+        fn validate_action(&self, _player: &NodeId, _slot: PlayerSlot, action: &u32) -> std::result::Result<(), String> {
+            if *action == 0 {
+                Err("no zero".to_string())
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_invalid_action_warned() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let mut alpha = alpha_transport
+            .declare_arena_node(|host_id, input_rx, output_tx, _initial_state| {
+                PickyTestEngine(TestEngine::new(host_id, input_rx, output_tx))
+            })
+            .name("alpha".to_string())
+            .unwrap()
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+            .action_limits(crate::ActionLimits {
+                on_violation: crate::ViolationPolicy::Warn,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // The invalid action is counted but still forwarded
+        bravo.sender().send(NodeCommand::GameAction(0)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::GameState(ref s) if s == "processed"));
+        assert!(matches!(bravo_result, StepResult::GameState(ref s) if s == "processed"));
+        assert_eq!(alpha.action_violations().invalid, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_client_kicked() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let limits = crate::ActionLimits {
+            max_size: Some(0),
+            on_violation: crate::ViolationPolicy::Kick { after: 2 },
+            ..Default::default()
+        };
+        let mut alpha = room_node(&alpha_transport, "alpha").action_limits(limits).await.unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // Every action is oversized, the second violation kicks the client
+        for action in 0..2 {
+            bravo.sender().send(NodeCommand::GameAction(action)).unwrap();
+        }
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        let violations = alpha.action_violations();
+        assert_eq!(violations.oversized, 2);
+        assert_eq!(violations.kicked, 1);
+        assert!(matches!(alpha.state(), NodeState::Host { ref connected_clients, .. } if connected_clients.is_empty()));
+
+        // The kicked client is told and searches again, skipping the host that kicked it
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::SearchingHost)));
+        let (_alpha, alpha_roles) = spawn_stepping(alpha);
+        let (_bravo, bravo_roles) = spawn_stepping(bravo);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+        assert!(alpha_roles.is_empty());
+    }
+
+    // Test engine publishing a burst of states at start and never taking actions
+    struct BurstEngine {
+        _input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>,
+        _output_tx: flume::Sender<String>,
+    }
+
+    impl GameEngine for BurstEngine {
+        type Action = u32;
+        type State = String;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            None
+        }
+    }
+
+    impl BurstEngine {
+        fn new(input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>, output_tx: flume::Sender<String>) -> Self {
+            for i in 0..5 {
+                output_tx.send(format!("state {}", i)).unwrap();
+            }
+            Self {
+                _input_rx: input_rx,
+                _output_tx: output_tx,
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_queues_bounded() {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+        let queues = crate::EngineQueues {
+            actions: crate::EngineQueue::bounded(2, crate::Backpressure::DropOldest),
+            states: crate::EngineQueue::latest(),
+        };
+        let mut alpha = transport
+            .declare_arena_node(|_host_id, input_rx, output_tx, _initial_state| BurstEngine::new(input_rx, output_tx))
+            .force_host(true)
+            .step_timeout_break_ms(100)
+            .engine_queues(queues)
+            .await
+            .unwrap();
+
+        // Only the latest state of the burst is kept
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::GameState(ref s) if s == "state 4"));
+
+        // Actions the engine does not take replace the oldest queued ones
+        for action in 0..5 {
+            alpha.sender().send(NodeCommand::GameAction(action)).unwrap();
+        }
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+        let stats = alpha.queue_stats();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats[0].queued_actions, 2);
+        assert_eq!(stats[0].dropped_actions, 3);
+        assert_eq!(stats[0].queued_states, 0);
+        assert_eq!(stats[0].dropped_states, 4);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_step_cancel_during_host_replay() {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+        let queues = crate::EngineQueues {
+            actions: crate::EngineQueue::bounded(1, crate::Backpressure::Block),
+            states: crate::EngineQueue::latest(),
+        };
+        let (inputs_tx, inputs_rx) = flume::unbounded();
+        let mut alpha = transport
+            .declare_arena_node(move |_host_id, input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>, output_tx, _initial_state| {
+                inputs_tx.send(input_rx.clone()).unwrap();
+                BurstEngine::new(input_rx, output_tx)
+            })
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+            .engine_queues(queues)
+            .await
+            .unwrap();
+
+        // Actions issued while searching, of which the engine queue holds a single one
+        for action in 0..3 {
+            assert!(alpha.action_buffer.push(action));
+        }
+        assert!(tokio::time::timeout(std::time::Duration::from_secs(1), alpha.step()).await.is_err());
+
+        // The next step finishes the replay and returns the result of the cancelled step
+        let input_rx = inputs_rx.recv_async().await.unwrap();
+        let consumer = tokio::spawn(async move {
+            let mut actions = Vec::new();
+            while actions.len() < 3 {
+                let (_node_id, _slot, action) = input_rx.recv_async().await.unwrap();
+                actions.push(action);
+            }
+            actions
+        });
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let actions = tokio::time::timeout(std::time::Duration::from_secs(1), consumer).await;
+        assert_eq!(actions.expect("all actions replayed").unwrap(), vec![0, 1, 2]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_state_rates_coalesce() {
+        use crate::network::{keyexpr::LinkType, NodeSubscriber};
+
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let observer_transport = network.open();
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+        let observer_id = NodeId::from_name("observer".to_string()).unwrap();
+        let observer = NodeSubscriber::<String, ZenohCodec>::new(&observer_transport, prefix, LinkType::State, &observer_id, None)
+            .await
+            .unwrap()
+            .with_sessions();
+
+        let rates = crate::StateRates {
+            publish: Some(5),
+            deliver: Some(10),
+        };
+        let mut alpha = alpha_transport
+            .declare_arena_node(|_host_id, input_rx, output_tx, _initial_state| BurstEngine::new(input_rx, output_tx))
+            .force_host(true)
+            .step_timeout_break_ms(1000)
+            .state_rates(rates)
+            .unwrap()
+            .await
+            .unwrap();
+
+        // The first state is delivered and published at once, the others are coalesced
+        let start = tokio::time::Instant::now();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::GameState(ref s) if s == "state 0"));
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::GameState(ref s) if s == "state 4"));
+        assert_eq!(start.elapsed(), std::time::Duration::from_millis(100));
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::Timeout));
+
+        assert_eq!(observer.recv().await.unwrap().1, "state 0");
+        assert_eq!(observer.recv().await.unwrap().1, "state 4");
+        assert_eq!(start.elapsed(), std::time::Duration::from_millis(1100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_state_rates_reject_zero() {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+        let rates = crate::StateRates {
+            publish: Some(0),
+            deliver: None,
+        };
+        let result = transport
+            .declare_arena_node(|host_id, input_rx, output_tx, _initial_state| TestEngine::new(host_id, input_rx, output_tx))
+            .state_rates(rates);
+        assert!(matches!(result, Err(crate::ArenaError::InvalidConfig(_))));
+    }
+
+    // Same engine with a bumped game version, incompatible with TestEngine
+    struct NewerTestEngine(#[allow(dead_code)] TestEngine);
+
+    impl GameEngine for NewerTestEngine {
+        type Action = u32;
+        type State = String;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        const GAME_VERSION: u32 = 1;
+
+        fn max_clients(&self) -> Option<usize> {
+            self.0.max_clients
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_incompatible_version_not_joined() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let (_alpha, alpha_roles) = spawn_stepping(simulated_node(&alpha_transport, "alpha").await);
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+
+        let bravo = bravo_transport
+            .declare_arena_node(|host_id, input_rx, output_tx, _initial_state| {
+                NewerTestEngine(TestEngine::new(host_id, input_rx, output_tx))
+            })
+            .name("bravo".to_string())
+            .unwrap()
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+            .await
+            .unwrap();
+        let (_bravo, bravo_roles) = spawn_stepping(bravo);
+
+        // The only host runs an incompatible game version, so bravo hosts its own game
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Host);
+        assert!(alpha_roles.is_empty());
+    }
+
+    fn room_node<'a>(
+        transport: &'a crate::MemoryTransport,
+        name: &str,
+    ) -> crate::NodeBuilder<'a, TestEngine, impl EngineFactory<TestEngine> + 'static> {
+        transport
+            .declare_arena_node(|host_id, input_rx, output_tx, _initial_state| TestEngine::new(host_id, input_rx, output_tx))
+            .name(name.to_string())
+            .unwrap()
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_rooms() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let charlie_transport = network.open();
+        let delta_transport = network.open();
+        let r1 = crate::RoomId::new("r1").unwrap();
+        let r2 = crate::RoomId::new("r2").unwrap();
+
+        let mut alpha = room_node(&alpha_transport, "alpha")
+            .force_host(true)
+            .rooms(vec![r1.clone(), r2.clone()])
+            .await
+            .unwrap();
+        let (results_tx, results_rx) = flume::unbounded();
+        let _alpha = tokio::spawn(async move {
+            while let Ok(result) = alpha.step().await {
+                match result {
+                    StepResult::Timeout => {}
+                    StepResult::Stop => break,
+                    other => {
+                        let _ = results_tx.send((other, alpha.state()));
+                    }
+                }
+            }
+        });
+
+        // Bravo asks for the second room
+        let mut bravo = room_node(&bravo_transport, "bravo").room(r2.clone()).await.unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert!(matches!(bravo.state(), NodeState::Client { room: Some(ref room), .. } if room == &r2));
+
+        // Charlie lets the host choose and lands in the first room
+        let mut charlie = room_node(&charlie_transport, "charlie").await.unwrap();
+        assert!(matches!(charlie.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert!(matches!(charlie.state(), NodeState::Client { room: Some(ref room), .. } if room == &r1));
+
+        // Actions of bravo are processed by the engine of the second room only
+        bravo.sender().send(NodeCommand::GameAction(7)).unwrap();
+        let mut bravo_state = None;
+        while bravo_state.is_none() {
+            if let StepResult::GameState(state) = bravo.step().await.unwrap() {
+                bravo_state = Some(state);
+            }
+        }
+        assert_eq!(bravo_state.as_deref(), Some("processed"));
+        let (result, host_state) = results_rx.recv_async().await.unwrap();
+        assert!(matches!(result, StepResult::RoomState(ref room, _) if room == &r2));
+        match host_state {
+            NodeState::Host { rooms, connected_clients, .. } => {
+                assert_eq!(connected_clients.len(), 2);
+                assert_eq!(rooms.len(), 2);
+                assert_eq!(rooms[0].connected_clients, vec![charlie.id().clone()]);
+                assert_eq!(rooms[1].connected_clients, vec![bravo.id().clone()]);
+            }
+            other => panic!("Expected host state, got {:?}", other),
+        }
+        assert!(matches!(charlie.step().await.unwrap(), StepResult::Timeout));
+
+        // Unknown rooms are rejected, so delta ends up hosting its own game
+        let mut delta = room_node(&delta_transport, "delta")
+            .room(crate::RoomId::new("r9").unwrap())
+            .await
+            .unwrap();
+        assert!(matches!(delta.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_matchmaking() {
+        let network = crate::MemoryNetwork::new();
+        network.set_default_conditions(crate::LinkConditions {
+            latency: std::time::Duration::from_millis(5),
+            ..Default::default()
+        });
+        let matchmaker_transport = network.open();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let charlie_transport = network.open();
+
+        let mut matchmaker = matchmaker_transport
+            .declare_matchmaker(crate::StandardMatchRules::new(2).max_rating_gap(100))
+            .name("matchmaker".to_string())
+            .unwrap()
+            .step_timeout_break_ms(100)
+            .await
+            .unwrap();
+        let (matches_tx, matches_rx) = flume::unbounded();
+        let _matchmaker = tokio::spawn(async move {
+            while let Ok(assignments) = matchmaker.step().await {
+                for assignment in assignments {
+                    let _ = matches_tx.send(assignment);
+                }
+            }
+        });
+
+        let ticket = |rating, region| crate::MatchTicket::new(rating, region);
+        let alpha = room_node(&alpha_transport, "alpha").matchmaking(ticket(1000, "eu")).await.unwrap();
+        let (_alpha, alpha_roles) = spawn_stepping(alpha);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        // Charlie plays in another region and stays in the queue
+        let charlie = room_node(&charlie_transport, "charlie").matchmaking(ticket(1000, "us")).await.unwrap();
+        let (_charlie, charlie_roles) = spawn_stepping(charlie);
+        let bravo = room_node(&bravo_transport, "bravo").matchmaking(ticket(1050, "eu")).await.unwrap();
+        let (_bravo, bravo_roles) = spawn_stepping(bravo);
+
+        let assignment = matches_rx.recv_async().await.unwrap();
+        assert_eq!(assignment.host.as_str(), "alpha");
+        assert_eq!(assignment.members.len(), 2);
+
+        // The longest waiting player hosts, the other one joins with the regular handshake
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+        assert_eq!(next_role(&bravo_roles).await, crate::NodeRole::Client);
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+        assert!(charlie_roles.is_empty());
+        assert!(matches_rx.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_step_cancel_keeps_assignment() {
+        let network = crate::MemoryNetwork::new();
+        network.set_default_conditions(crate::LinkConditions {
+            latency: std::time::Duration::from_millis(5),
+            ..Default::default()
+        });
+        let matchmaker_transport = network.open();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let mut matchmaker = matchmaker_transport
+            .declare_matchmaker(crate::StandardMatchRules::new(2))
+            .name("matchmaker".to_string())
+            .unwrap()
+            .step_timeout_break_ms(100)
+            .await
+            .unwrap();
+        let (matches_tx, matches_rx) = flume::unbounded();
+        let _matchmaker = tokio::spawn(async move {
+            while let Ok(assignments) = matchmaker.step().await {
+                for assignment in assignments {
+                    let _ = matches_tx.send(assignment);
+                }
+            }
+        });
+
+        let ticket = crate::MatchTicket::new(1000, "eu");
+        let alpha = room_node(&alpha_transport, "alpha").matchmaking(ticket.clone()).await.unwrap();
+        let (_alpha, alpha_roles) = spawn_stepping(alpha);
+        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        let mut bravo = room_node(&bravo_transport, "bravo").matchmaking(ticket).await.unwrap();
+
+        // Steps dropped while waiting for the assignment and while connecting to the host
+        let mut result = None;
+        for _ in 0..30 {
+            if let Ok(step_result) = tokio::time::timeout(std::time::Duration::from_millis(10), bravo.step()).await {
+                result = Some(step_result.unwrap());
+                break;
+            }
+        }
+        assert_eq!(next_role(&alpha_roles).await, crate::NodeRole::Host);
+
+        // The assignment survives them: bravo joins alpha without queueing again
+        let result = match result {
+            Some(result) => result,
+            None => bravo.step().await.unwrap(),
+        };
+        assert!(matches!(result, StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert!(matches!(bravo.state(), NodeState::Client { ref host_id, .. } if host_id.as_str() == "alpha"));
+        assert_eq!(matches_rx.len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_commands_while_joining_assigned_host() {
+        use crate::network::{keyexpr::LinkType, NodePublisher};
+
+        let network = crate::MemoryNetwork::new();
+        let matchmaker_transport = network.open();
+        let bravo_transport = network.open();
+        let prefix = zenoh::key_expr::KeyExpr::try_from("zenoh/arena").unwrap();
+        let matchmaker_id = NodeId::from_name("matchmaker".to_string()).unwrap();
+        let ghost_id = NodeId::from_name("ghost".to_string()).unwrap();
+
+        let mut bravo = room_node(&bravo_transport, "bravo")
+            .matchmaking(crate::MatchTicket::new(1000, "eu"))
+            .await
+            .unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+
+        // The matchmaker assigns a host that never shows up
+        let assignments = NodePublisher::<crate::MatchAssignment, ZenohCodec>::new(&matchmaker_transport, prefix, LinkType::Match, &matchmaker_id, Some(bravo.id()), None, crate::LinkQos::default())
+            .await
+            .unwrap();
+        let assignment = crate::MatchAssignment {
+            host: ghost_id.clone(),
+            members: vec![ghost_id, bravo.id().clone()],
+        };
+        assignments.put(&assignment).await.unwrap();
+
+        // Connection attempts end with the step, and give way to commands at once
+        let start = tokio::time::Instant::now();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+        assert!(start.elapsed() <= std::time::Duration::from_millis(150));
+        let sender = bravo.sender();
+        let start = tokio::time::Instant::now();
+        let (result, _) = tokio::join!(bravo.step(), async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            sender.send(NodeCommand::Stop).unwrap();
+        });
+        assert!(matches!(result.unwrap(), StepResult::Stop));
+        assert!(start.elapsed() < std::time::Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_commands_while_joining_handed_off_host() {
+        let network = crate::MemoryNetwork::new();
+        let bravo_transport = network.open();
+        let ghost_id = NodeId::from_name("ghost".to_string()).unwrap();
+
+        // Handed off to a host that never shows up
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(10);
+        bravo.state = NodeStateInternal::handed_off(ghost_id, deadline);
+
+        // Connection attempts end with the step, and give way to commands at once
+        let start = tokio::time::Instant::now();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+        assert!(start.elapsed() <= std::time::Duration::from_millis(150));
+        let sender = bravo.sender();
+        let start = tokio::time::Instant::now();
+        let (result, _) = tokio::join!(bravo.step(), async {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            sender.send(NodeCommand::BecomeHost).unwrap();
+        });
+        assert!(matches!(result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        assert!(start.elapsed() < std::time::Duration::from_millis(100));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_player_slots() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let charlie_transport = network.open();
+
+        let mut alpha = room_node(&alpha_transport, "alpha").force_host(true).await.unwrap();
+        let (states_tx, states_rx) = flume::unbounded();
+        let _alpha = tokio::spawn(async move {
+            while let Ok(result) = alpha.step().await {
+                if matches!(result, StepResult::Stop) {
+                    break;
+                }
+                let _ = states_tx.send(alpha.state());
+            }
+        });
+
+        let slot_of = |node: &Node<TestEngine, _>| match node.state() {
+            NodeState::Client { slot, .. } => slot,
+            other => panic!("Expected client state, got {:?}", other),
+        };
+
+        // The host holds slot 0, clients get the next free slots
+        let mut bravo = room_node(&bravo_transport, "bravo").await.unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert_eq!(slot_of(&bravo), PlayerSlot::new(1, 1));
+        let mut charlie = room_node(&charlie_transport, "charlie").await.unwrap();
+        assert!(matches!(charlie.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert_eq!(slot_of(&charlie), PlayerSlot::new(2, 1));
+
+        // Bravo reconnects and gets its slot back
+        drop(bravo);
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        let mut bravo = room_node(&bravo_transport, "bravo").await.unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        assert_eq!(slot_of(&bravo), PlayerSlot::new(1, 1));
+
+        // The host reports the players of its game ordered by slot
+        let players = loop {
+            if let NodeState::Host { players, .. } = states_rx.recv_async().await.unwrap() {
+                if players.len() == 3 {
+                    break players;
+                }
+            }
+        };
+        let names: Vec<_> = players.iter().map(|(id, slot)| (id.as_str(), slot.index)).collect();
+        assert_eq!(names, vec![("alpha", 0), ("bravo", 1), ("charlie", 2)]);
+    }
+
+    /// Counts actions, panics on action 0
+    struct CrashingEngine {
+        thread: crate::EngineThread,
+    }
+
+    impl CrashingEngine {
+        fn new(input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>, output_tx: flume::Sender<String>, initial_state: Option<String>) -> Self {
+            let mut count: u32 = initial_state.map(|state| state.parse().unwrap()).unwrap_or_default();
+            let thread = crate::EngineThread::spawn(move || {
+                while let Ok((_node_id, _slot, action)) = input_rx.recv() {
+                    assert_ne!(action, 0, "crash requested");
+                    count += 1;
+                    let _ = output_tx.send(count.to_string());
+                }
+            });
+            Self { thread }
+        }
+    }
+
+    impl GameEngine for CrashingEngine {
+        type Action = u32;
+        type State = String;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            None
+        }
+
+        async fn exited(&mut self) -> crate::EngineExit {
+            self.thread.exit().await
+        }
+    }
+
+    async fn crashing_host(transport: &crate::MemoryTransport, policy: crate::RestartPolicy) -> Node<CrashingEngine, impl EngineFactory<CrashingEngine> + 'static> {
+        transport
+            .declare_arena_node(|_host_id, input_rx, output_tx, initial_state| CrashingEngine::new(input_rx, output_tx, initial_state))
+            .name("alpha".to_string())
+            .unwrap()
+            .force_host(true)
+            .step_timeout_break_ms(100)
+            .engine_restart(policy)
+            .await
+            .unwrap()
+    }
+
+    async fn step_until_state<E: GameEngine, F: EngineFactory<E>>(node: &mut Node<E, F>) -> StepResult<E::State, E::Event> {
+        loop {
+            match node.step().await.unwrap() {
+                StepResult::Timeout => {}
+                other => return other,
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_crash_restarts_from_last_state() {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+        let mut alpha = crashing_host(&transport, crate::RestartPolicy::Restart { max_restarts: 1 }).await;
+        let sender = alpha.sender();
+
+        sender.send(NodeCommand::GameAction(1)).unwrap();
+        sender.send(NodeCommand::GameAction(1)).unwrap();
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::GameState(ref s) if s == "1"));
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::GameState(ref s) if s == "2"));
+
+        // The crash is reported and the engine continues from the last published state
+        sender.send(NodeCommand::GameAction(0)).unwrap();
+        let result = step_until_state(&mut alpha).await;
+        assert!(matches!(result, StepResult::EngineCrashed(None, ref reason) if reason.contains("crash requested")));
+        assert!(matches!(alpha.state(), NodeState::Host { .. }));
+        sender.send(NodeCommand::GameAction(1)).unwrap();
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::GameState(ref s) if s == "3"));
+
+        // Once the restarts are exhausted the crash ends the game
+        sender.send(NodeCommand::GameAction(0)).unwrap();
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::EngineCrashed(None, _)));
+        assert!(matches!(alpha.state(), NodeState::SearchingHost));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_engine_crash_without_restart() {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+        let mut alpha = crashing_host(&transport, crate::RestartPolicy::Never).await;
+
+        alpha.sender().send(NodeCommand::GameAction(0)).unwrap();
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::EngineCrashed(None, _)));
+        assert!(matches!(alpha.state(), NodeState::SearchingHost));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_chat() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let chat = |recipient: Option<&NodeId>, text: &str| NodeCommand::Chat {
+            recipient: recipient.cloned(),
+            text: text.to_string(),
+        };
+
+        let mut alpha = room_node(&alpha_transport, "alpha").chat_history(1).await.unwrap();
+        let alpha_id = alpha.id().clone();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+
+        // Messages to everybody come back to their sender, the last one is kept for later members
+        alpha.sender().send(chat(None, "first")).unwrap();
+        alpha.sender().send(chat(None, "welcome")).unwrap();
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::Chat(ref m) if m.text == "first"));
+        assert!(matches!(step_until_state(&mut alpha).await, StepResult::Chat(ref m) if m.text == "welcome"));
+
+        // A joining client receives the history
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let bravo_id = bravo.id().clone();
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        let (bravo_result, _) = tokio::join!(step_until_state(&mut bravo), cancel_step(&mut alpha));
+        assert!(matches!(
+            bravo_result,
+            StepResult::Chat(ref m) if m.text == "welcome" && m.sender == alpha_id && m.recipient.is_none()
+        ));
+
+        // Messages to a single member only reach it, stamped with their sender
+        bravo.sender().send(chat(Some(&alpha_id), "psst")).unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Timeout));
+        assert!(matches!(
+            step_until_state(&mut alpha).await,
+            StepResult::Chat(ref m) if m.text == "psst" && m.sender == bravo_id && m.recipient.as_ref() == Some(&alpha_id)
+        ));
+        alpha.sender().send(chat(Some(&bravo_id), "hey")).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(alpha.step(), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result.unwrap(), StepResult::Timeout));
+        assert!(matches!(bravo_result, StepResult::Chat(ref m) if m.text == "hey" && m.sender == alpha_id));
+
+        // Messages of a client to everybody are relayed back to it
+        bravo.sender().send(chat(None, "gg")).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::Chat(ref m) if m.text == "gg" && m.sender == bravo_id));
+        assert!(matches!(bravo_result, StepResult::Chat(ref m) if m.text == "gg" && m.sender == bravo_id));
+    }
+
+    // Test engine emitting an event for each action: to everybody for 0, to its sender otherwise
+    struct EventEngine {
+        events: Option<flume::Receiver<crate::EngineEvent<String>>>,
+        _output_tx: flume::Sender<String>,
+    }
+
+    impl EventEngine {
+        fn new(input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>, output_tx: flume::Sender<String>) -> Self {
+            let (event_tx, event_rx) = flume::unbounded();
+            std::thread::spawn(move || {
+                while let Ok((node_id, _slot, action)) = input_rx.recv() {
+                    let event = match action {
+                        0 => crate::EngineEvent::broadcast("boom".to_string()),
+                        n => crate::EngineEvent::to(vec![node_id], format!("ack {}", n)),
+                    };
+                    let _ = event_tx.send(event);
+                }
+            });
+            Self {
+                events: Some(event_rx),
+                _output_tx: output_tx,
+            }
+        }
+    }
+
+    impl GameEngine for EventEngine {
+        type Action = u32;
+        type State = String;
+        type Event = String;
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            None
+        }
+
+        fn take_events(&mut self) -> Option<flume::Receiver<crate::EngineEvent<String>>> {
+            self.events.take()
+        }
+    }
+
+    fn event_node<'a>(
+        transport: &'a crate::MemoryTransport,
+        name: &str,
+    ) -> crate::NodeBuilder<'a, EventEngine, impl EngineFactory<EventEngine> + 'static> {
+        transport
+            .declare_arena_node(|_host_id, input_rx, output_tx, _initial_state| EventEngine::new(input_rx, output_tx))
+            .name(name.to_string())
+            .unwrap()
+            .search_jitter_ms(0)
+            .search_timeout_ms(500)
+            .step_timeout_break_ms(100)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_engine_events() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+
+        let mut alpha = event_node(&alpha_transport, "alpha").await.unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+        let mut bravo = event_node(&bravo_transport, "bravo").await.unwrap();
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+
+        // Events to everybody reach the host and its clients
+        bravo.sender().send(NodeCommand::GameAction(0)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result, StepResult::Event(None, ref e) if e == "boom"));
+        assert!(matches!(bravo_result, StepResult::Event(None, ref e) if e == "boom"));
+
+        // Events to a single player only reach it
+        bravo.sender().send(NodeCommand::GameAction(7)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(alpha.step(), step_until_state(&mut bravo));
+        assert!(matches!(alpha_result.unwrap(), StepResult::Timeout));
+        assert!(matches!(bravo_result, StepResult::Event(None, ref e) if e == "ack 7"));
+
+        alpha.sender().send(NodeCommand::GameAction(1)).unwrap();
+        let (alpha_result, bravo_result) = tokio::join!(step_until_state(&mut alpha), bravo.step());
+        assert!(matches!(alpha_result, StepResult::Event(None, ref e) if e == "ack 1"));
+        assert!(matches!(bravo_result.unwrap(), StepResult::Timeout));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_simulated_arena_observer() {
+        let network = crate::MemoryNetwork::new();
+        let alpha_transport = network.open();
+        let bravo_transport = network.open();
+        let observer_transport = network.open();
+
+        let mut alpha = room_node(&alpha_transport, "alpha")
+            .rooms(vec![crate::RoomId::new("lobby").unwrap()])
+            .await
+            .unwrap();
+        assert!(matches!(alpha.step().await.unwrap(), StepResult::RoleChanged(crate::NodeRole::Host)));
+
+        // The roster is complete at once
+        let mut observer = observer_transport.declare_arena_observer().count_traffic(true).await.unwrap();
+        let roster = observer.roster();
+        assert_eq!(roster.nodes.len(), 1);
+        assert_eq!(roster.hosts().next().unwrap().id, *alpha.id());
+
+        // A joining client is reported with its host and room
+        let mut bravo = simulated_node(&bravo_transport, "bravo").await;
+        let bravo_id = bravo.id().clone();
+        let (bravo_result, _) = tokio::join!(bravo.step(), cancel_step(&mut alpha));
+        assert!(matches!(bravo_result.unwrap(), StepResult::RoleChanged(crate::NodeRole::Client)));
+        let client = loop {
+            match observer.next_change().await.unwrap() {
+                crate::RosterChange::Updated(node) if node.role == crate::ObservedRole::Client => break node,
+                crate::RosterChange::Joined(node) | crate::RosterChange::Updated(node) => assert_eq!(node.id, bravo_id),
+                change => panic!("unexpected change {:?}", change),
+            }
+        };
+        assert_eq!(client.host.as_ref(), Some(alpha.id()));
+        assert_eq!(client.room.as_ref().map(crate::RoomId::as_str), Some("lobby"));
+        let roster = observer.roster();
+        let host = roster.hosts().next().unwrap();
+        assert_eq!(roster.clients_of(host).map(|node| node.id.clone()).collect::<Vec<_>>(), vec![bravo_id.clone()]);
+
+        // Hosts accepting clients answer probes with their number of clients
+        let (probed, _) = tokio::join!(observer.probe_hosts("", 0), cancel_step(&mut alpha));
+        let probed = probed.unwrap();
+        assert_eq!(probed.len(), 1);
+        assert_eq!((&probed[0].id, probed[0].players), (alpha.id(), 1));
+        let (probed, _) = tokio::join!(observer.probe_hosts("other", 0), cancel_step(&mut alpha));
+        assert!(probed.unwrap().is_empty());
+
+        // Messages are counted per type of link while changes are awaited
+        bravo.sender().send(NodeCommand::GameAction(1)).unwrap();
+        let (_, bravo_result) = tokio::join!(step_until_state(&mut alpha), step_until_state(&mut bravo));
+        assert!(matches!(bravo_result, StepResult::GameState(_)));
+        let pending = tokio::time::timeout(std::time::Duration::from_millis(10), observer.next_change()).await;
+        assert!(pending.is_err());
+        let traffic = observer.traffic();
+        let messages = |link| traffic.iter().find(|t| t.link == link).map(|t| t.messages);
+        assert_eq!(messages(crate::LinkType::Action), Some(1));
+        assert_eq!(messages(crate::LinkType::State), Some(1));
+
+        // A stopped node leaves the roster
+        bravo.sender().send(NodeCommand::Stop).unwrap();
+        assert!(matches!(bravo.step().await.unwrap(), StepResult::Stop));
+        drop(bravo);
+        let left = loop {
+            if let crate::RosterChange::Left(node) = observer.next_change().await.unwrap() {
+                break node;
+            }
+        };
+        assert_eq!(left.id, bravo_id);
+        assert_eq!(observer.roster().nodes.len(), 1);
+    }
 }
@@ -0,0 +1,255 @@
+/// Async engines spawned on the tokio runtime by the node
+use std::future::Future;
+
+use crate::codec::Codec;
+use crate::node::game_engine::{task_exit, EngineEvent, EngineExit, EngineFactory, GameEngine};
+use crate::node::types::{NodeId, PlayerSlot};
+
+/// Game engine processing actions in an async future
+///
+/// An alternative to implementing [`GameEngine`] with a blocking thread: the future
+/// returned by [`run`](AsyncGameEngine::run) is spawned by the node on its tokio runtime,
+/// so it can use async I/O (e.g. to talk to a local rules service). The future is
+/// cancelled when the host drops the engine, i.e. when the room is closed or the node
+/// leaves the host state. Use [`async_engine`] to turn a constructor into an
+/// [`EngineFactory`]. [`TickEngine`](crate::TickEngine)s are run as async engines too.
+pub trait AsyncGameEngine: Send + 'static {
+    /// Action type from user/client
+    type Action: Send + 'static;
+
+    /// State type sent to clients
+    type State: Send + Clone + 'static;
+
+    /// Event type sent to clients apart from the state (see [`take_events`](AsyncGameEngine::take_events))
+    ///
+    /// Use `()` for engines without events.
+    type Event: Send + Sync + 'static;
+
+    /// Codec used to encode actions, states and events on the wire
+    type Codec: Codec<Self::Action> + Codec<Self::State> + Codec<Self::Event>;
+
+    /// Game identifier exchanged during the handshake
+    const GAME_ID: &'static str = "";
+
+    /// Game version exchanged during the handshake
+    const GAME_VERSION: u32 = 0;
+
+    /// Maximum number of clients allowed (None = unlimited)
+    fn max_clients(&self) -> Option<usize>;
+
+    /// Number of teams players are distributed over
+    fn team_count(&self) -> u32 {
+        1
+    }
+
+    /// Check an action received from a client before it is queued for the engine
+    ///
+    /// Like [`GameEngine::validate_action`], but without access to the engine, which runs
+    /// on its own task. Accepts every action by default.
+    fn validate_action(_player: &NodeId, _slot: PlayerSlot, _action: &Self::Action) -> std::result::Result<(), String>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Hand over the receiving end of the engine's event channel
+    ///
+    /// Called once before [`run`](AsyncGameEngine::run), see [`GameEngine::take_events`].
+    /// Returns None by default.
+    fn take_events(&mut self) -> Option<flume::Receiver<EngineEvent<Self::Event>>> {
+        None
+    }
+
+    /// Process actions from `input_rx` and send states to `output_tx` until the game is over
+    ///
+    /// Returning ends the game, which the host handles like any other engine exit.
+    /// A panic of the future is reported as a crash.
+    fn run(
+        self,
+        input_rx: flume::Receiver<(NodeId, PlayerSlot, Self::Action)>,
+        output_tx: flume::Sender<Self::State>,
+    ) -> impl Future<Output = ()> + Send;
+}
+
+/// [`GameEngine`] running an [`AsyncGameEngine`] future on a tokio task
+///
+/// The task is aborted when this value is dropped: the future is dropped at its next
+/// await point and never polled again.
+pub struct AsyncDriven<T: AsyncGameEngine> {
+    max_clients: Option<usize>,
+    team_count: u32,
+    events: Option<flume::Receiver<EngineEvent<T::Event>>>,
+    task: tokio::task::JoinHandle<()>,
+    _phantom: std::marker::PhantomData<fn() -> T>,
+}
+
+impl<T: AsyncGameEngine> AsyncDriven<T> {
+    /// Spawn the future of `engine` with the given channels
+    ///
+    /// Must be called within a tokio runtime.
+    pub fn spawn(
+        mut engine: T,
+        input_rx: flume::Receiver<(NodeId, PlayerSlot, T::Action)>,
+        output_tx: flume::Sender<T::State>,
+    ) -> Self {
+        let max_clients = engine.max_clients();
+        let team_count = engine.team_count();
+        let events = engine.take_events();
+        let task = tokio::spawn(engine.run(input_rx, output_tx));
+        Self {
+            max_clients,
+            team_count,
+            events,
+            task,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<T: AsyncGameEngine> Drop for AsyncDriven<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl<T: AsyncGameEngine> GameEngine for AsyncDriven<T> {
+    type Action = T::Action;
+    type State = T::State;
+    type Event = T::Event;
+    type Codec = T::Codec;
+
+    const GAME_ID: &'static str = T::GAME_ID;
+    const GAME_VERSION: u32 = T::GAME_VERSION;
+
+    fn max_clients(&self) -> Option<usize> {
+        self.max_clients
+    }
+
+    fn team_count(&self) -> u32 {
+        self.team_count
+    }
+
+    fn validate_action(&self, player: &NodeId, slot: PlayerSlot, action: &T::Action) -> std::result::Result<(), String> {
+        T::validate_action(player, slot, action)
+    }
+
+    fn take_events(&mut self) -> Option<flume::Receiver<EngineEvent<T::Event>>> {
+        self.events.take()
+    }
+
+    fn exited(&mut self) -> impl std::future::Future<Output = EngineExit> + Send {
+        task_exit(&mut self.task)
+    }
+}
+
+/// Create an engine factory spawning async engines
+///
+/// `new_engine` receives the host's NodeId and the optional initial state, like the
+/// other arguments of an [`EngineFactory`].
+///
+/// # Example
+/// ```no_run
+/// use zenoh_arena::{async_engine, AsyncGameEngine, NodeId, PlayerSlot, SessionExt, ZenohCodec};
+///
+/// struct Echo;
+///
+/// impl AsyncGameEngine for Echo {
+///     type Action = String;
+///     type State = String;
+///     type Event = ();
+///     type Codec = ZenohCodec;
+///
+///     fn max_clients(&self) -> Option<usize> { None }
+///
+///     async fn run(
+///         self,
+///         input_rx: flume::Receiver<(NodeId, PlayerSlot, String)>,
+///         output_tx: flume::Sender<String>,
+///     ) {
+///         while let Ok((from, _slot, action)) = input_rx.recv_async().await {
+///             let _ = output_tx.send(format!("{}: {}", from, action));
+///         }
+///     }
+/// }
+///
+/// # async fn example() {
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let node = session
+///     .declare_arena_node(async_engine(|_host_id, _state| Echo))
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub fn async_engine<T, F>(new_engine: F) -> impl EngineFactory<AsyncDriven<T>>
+where
+    T: AsyncGameEngine,
+    F: Fn(NodeId, Option<T::State>) -> T + Send + Sync,
+{
+    move |host_id, input_rx, output_tx, initial_state| {
+        AsyncDriven::spawn(new_engine(host_id, initial_state), input_rx, output_tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::ZenohCodec;
+
+    /// Answers each action after an async delay; reports being dropped
+    struct Delayed {
+        dropped_tx: flume::Sender<()>,
+    }
+
+    struct DropGuard(flume::Sender<()>);
+
+    impl Drop for DropGuard {
+        fn drop(&mut self) {
+            let _ = self.0.send(());
+        }
+    }
+
+    impl AsyncGameEngine for Delayed {
+        type Action = u32;
+        type State = u32;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            Some(2)
+        }
+
+        async fn run(
+            self,
+            input_rx: flume::Receiver<(NodeId, PlayerSlot, u32)>,
+            output_tx: flume::Sender<u32>,
+        ) {
+            let _guard = DropGuard(self.dropped_tx);
+            while let Ok((_from, slot, action)) = input_rx.recv_async().await {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                let _ = output_tx.send(action + slot.index);
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_async_engine_cancelled_on_drop() {
+        let (dropped_tx, dropped_rx) = flume::unbounded();
+        let factory = async_engine(move |_host_id, _state| Delayed {
+            dropped_tx: dropped_tx.clone(),
+        });
+        let (input_tx, input_rx) = flume::unbounded();
+        let (output_tx, output_rx) = flume::unbounded();
+        let engine = factory(NodeId::generate(), input_rx, output_tx, None);
+        assert_eq!(engine.max_clients(), Some(2));
+
+        input_tx.send((NodeId::generate(), PlayerSlot::new(1, 1), 41)).unwrap();
+        assert_eq!(output_rx.recv_async().await.unwrap(), 42);
+
+        // Dropping the engine cancels the future even though its input is still open
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), 1)).unwrap();
+        drop(engine);
+        dropped_rx.recv_async().await.unwrap();
+        assert!(output_rx.recv_async().await.is_err());
+    }
+}
@@ -0,0 +1,154 @@
+/// Chat relay run by a host
+use std::collections::{HashMap, VecDeque};
+use std::time::SystemTime;
+
+use zenoh::key_expr::KeyExpr;
+
+use crate::codec::ZenohCodec;
+use crate::error::Result;
+use crate::network::keyexpr::LinkType;
+use crate::network::{ChatMessage, ChatRequest, LinkQos, NodeMessage, NodePublisher, NodeSubscriber, Transport};
+use crate::node::config::NodeConfig;
+use crate::node::types::NodeId;
+
+/// Chat of a hosting session
+///
+/// Receives the chat requests of the clients, and relays messages to everybody or to a
+/// single member. The last messages sent to everybody are kept for members joining later.
+pub(crate) struct HostChat {
+    /// Key expression prefix of the chat links
+    prefix: KeyExpr<'static>,
+    /// Session id of the host, tagging the relayed messages
+    session: u64,
+    /// Quality of service of the relayed messages
+    qos: LinkQos,
+    /// Subscriber to receive chat requests from clients
+    subscriber: NodeSubscriber<ChatRequest, ZenohCodec>,
+    /// Publisher relaying messages to everybody
+    broadcast: NodePublisher<ChatMessage, ZenohCodec>,
+    /// Publishers relaying messages to single members, declared on first use
+    direct: HashMap<NodeId, NodePublisher<ChatMessage, ZenohCodec>>,
+    /// Last messages sent to everybody, oldest first
+    history: VecDeque<ChatMessage>,
+    /// Maximum number of messages kept in the history
+    history_capacity: usize,
+}
+
+impl HostChat {
+    /// Declare the chat links of a host
+    pub(crate) async fn open(
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        session: u64,
+    ) -> Result<Self> {
+        let prefix = config.keyexpr_prefix.clone();
+        let subscriber = NodeSubscriber::new(transport, prefix.clone(), LinkType::Chat, node_id, None)
+            .await?
+            .with_sessions();
+        let broadcast = NodePublisher::new(
+            transport,
+            prefix.clone(),
+            LinkType::Chat,
+            node_id,
+            None, // Broadcast to all members
+            None,
+            config.chat_qos,
+        )
+        .await?
+        .with_session(session);
+
+        Ok(Self {
+            prefix,
+            session,
+            qos: config.chat_qos,
+            subscriber,
+            broadcast,
+            direct: HashMap::new(),
+            history: VecDeque::new(),
+            history_capacity: config.chat_history,
+        })
+    }
+
+    /// Wait for the next chat request of a client
+    pub(crate) async fn recv(&self) -> Result<NodeMessage<ChatRequest>> {
+        self.subscriber.recv_message().await
+    }
+
+    /// Stamp a message with its sender and the current time
+    pub(crate) fn stamp(sender: NodeId, recipient: Option<NodeId>, text: String) -> ChatMessage {
+        ChatMessage {
+            sender,
+            recipient,
+            timestamp: SystemTime::now(),
+            text,
+        }
+    }
+
+    /// Relay a message to everybody, or to its recipient
+    ///
+    /// Messages to everybody are added to the history.
+    pub(crate) async fn relay(
+        &mut self,
+        transport: &dyn Transport,
+        node_id: &NodeId,
+        message: &ChatMessage,
+    ) -> Result<()> {
+        match &message.recipient {
+            Some(recipient) => self.send_to(transport, node_id, recipient, message).await,
+            None => {
+                if self.history_capacity > 0 {
+                    if self.history.len() == self.history_capacity {
+                        self.history.pop_front();
+                    }
+                    self.history.push_back(message.clone());
+                }
+                self.broadcast.put(message).await
+            }
+        }
+    }
+
+    /// Send the history to a member, oldest message first
+    pub(crate) async fn send_history(
+        &mut self,
+        transport: &dyn Transport,
+        node_id: &NodeId,
+        recipient: &NodeId,
+    ) -> Result<()> {
+        let history: Vec<ChatMessage> = self.history.iter().cloned().collect();
+        for message in &history {
+            self.send_to(transport, node_id, recipient, message).await?;
+        }
+        Ok(())
+    }
+
+    /// Forget the link to a member that left
+    pub(crate) fn forget(&mut self, member: &NodeId) {
+        self.direct.remove(member);
+    }
+
+    /// Send a message on the link to a single member
+    async fn send_to(
+        &mut self,
+        transport: &dyn Transport,
+        node_id: &NodeId,
+        recipient: &NodeId,
+        message: &ChatMessage,
+    ) -> Result<()> {
+        if !self.direct.contains_key(recipient) {
+            let publisher = NodePublisher::new(
+                transport,
+                self.prefix.clone(),
+                LinkType::Chat,
+                node_id,
+                Some(recipient),
+                None,
+                self.qos,
+            )
+            .await?
+            .with_session(self.session);
+            self.direct.insert(recipient.clone(), publisher);
+        }
+        self.direct[recipient].put(message).await
+    }
+}
@@ -1,11 +1,16 @@
 use crate::{NodeRole, StepResult};
 /// Client state implementation
 use crate::node::config::NodeConfig;
-use crate::error::Result;
-use crate::network::{NodeLivelinessToken, NodeLivelinessWatch, NodePublisher, NodeSubscriber};
-use crate::node::game_engine::GameEngine;
+use crate::error::{ArenaError, Result};
+use crate::codec::ZenohCodec;
+use futures::FutureExt;
+
+use crate::network::{ChatMessage, ChatRequest, HostNotice, NodeLivelinessToken, NodeMessage, NodeLivelinessWatch, NodePublisher, NodeSubscriber};
+use crate::network::{Transport, TransportToken};
+use crate::node::game_engine::{EngineFactory, GameEngine};
+use crate::node::action_buffer::ActionBuffer;
 use crate::node::arena_node::NodeCommand;
-use crate::node::types::{NodeId, NodeStateInternal};
+use crate::node::types::{NodeId, NodeStateInternal, PlayerSlot, RoomId};
 
 /// State while connected as a client to a host
 pub(crate) struct ClientState<E>
@@ -14,37 +19,116 @@ where
 {
     /// ID of the host we're connected to
     pub(crate) host_id: NodeId,
+    /// Room assigned by the host (None if the host does not serve rooms)
+    pub(crate) room: Option<RoomId>,
+    /// Player slot assigned by the host
+    pub(crate) slot: PlayerSlot,
+    /// Session id of the host, tagging actions and states of the connection
+    pub(crate) session: u64,
+    /// Number of states, events and chat messages ignored for coming from another host or session
+    pub(crate) ignored_samples: u64,
+    /// Number of states and events dropped for being malformed
+    pub(crate) malformed_samples: u64,
     /// Watches for host liveliness to detect disconnection
     pub(crate) liveliness_watch: NodeLivelinessWatch,
     /// Client's liveliness token (type: Client) for the host to track disconnection
     pub(crate) _liveliness_token: NodeLivelinessToken,
+    /// Liveliness token advertising the connection to the host to observers
+    pub(crate) _connection_token: TransportToken,
     /// Publisher for sending actions to the host
-    pub(crate) action_publisher: NodePublisher<E::Action>,
+    pub(crate) action_publisher: NodePublisher<E::Action, E::Codec>,
     /// Subscriber for receiving game state from the host
-    pub(crate) state_subscriber: NodeSubscriber<E::State>,
+    pub(crate) state_subscriber: NodeSubscriber<E::State, E::Codec>,
+    /// Subscriber for receiving engine events from the host
+    pub(crate) event_subscriber: NodeSubscriber<E::Event, E::Codec>,
+    /// Publisher for sending chat requests to the host
+    pub(crate) chat_publisher: NodePublisher<ChatRequest, ZenohCodec>,
+    /// Subscriber for receiving chat messages relayed by the host
+    pub(crate) chat_subscriber: NodeSubscriber<ChatMessage, ZenohCodec>,
+    /// Subscriber for receiving notices from the host
+    pub(crate) notice_subscriber: NodeSubscriber<HostNotice, ZenohCodec>,
 }
 
 impl<E> ClientState<E>
 where
     E: GameEngine,
 {
+    /// Send the actions buffered while searching to the host, oldest first
+    ///
+    /// Each action leaves the buffer once sent, so that a dropped step replays it again.
+    pub(crate) async fn replay_actions(&self, node_id: &NodeId, action_buffer: &mut ActionBuffer<E::Action>) {
+        while let Some(action) = action_buffer.peek() {
+            if let Err(e) = self.action_publisher.put(action).await {
+                tracing::error!(
+                    "Node '{}' failed to replay action to host '{}': {}",
+                    node_id,
+                    self.host_id,
+                    e
+                );
+            }
+            action_buffer.discard();
+        }
+    }
+
+    /// Follow a notice received from the host
+    ///
+    /// Returns the next state, or None if the notice comes from another host or session.
+    fn on_notice(
+        &mut self,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        message: NodeMessage<HostNotice>,
+    ) -> Option<NodeStateInternal<E>> {
+        if message.sender_id != self.host_id || message.session != self.session {
+            tracing::debug!(
+                "Node '{}' ignoring notice from '{}' outside its session",
+                node_id,
+                message.sender_id
+            );
+            self.ignored_samples += 1;
+            return None;
+        }
+        match message.value {
+            HostNotice::HandOff { host } => {
+                tracing::info!("Node '{}' handed off by host '{}' to host '{}'", node_id, self.host_id, host);
+                let deadline =
+                    tokio::time::Instant::now() + tokio::time::Duration::from_millis(config.search_timeout_ms);
+                Some(NodeStateInternal::handed_off(host, deadline))
+            }
+            HostNotice::Kicked => {
+                tracing::warn!("Node '{}' kicked by host '{}'", node_id, self.host_id);
+                Some(NodeStateInternal::kicked(self.host_id.clone()))
+            }
+        }
+    }
+
     /// Process the Client state - handle commands while connected to a host
     ///
-    /// Consumes self and returns the next state.
+    /// Returns the next state if the node leaves the Client state.
     /// Handles commands from the command channel while connected to a host.
     /// Monitors liveliness of the connected host and returns to SearchingHost if disconnected.
     /// Returns when either:
     /// - Host liveliness is lost (transitions back to SearchingHost)
+    /// - The host hands the client off to another host (transitions to SearchingHost to join it)
+    /// - The host kicks the client (transitions back to SearchingHost)
     /// - The step timeout elapses
     /// - A Stop command is received (returns Stop)
     /// - A new game state is received from host
-    pub(crate) async fn step(
-        mut self,
+    /// - An engine event is received from host
+    /// - A chat message is relayed by the host
+    /// - A role command is received (Leave, BecomeHost or SearchAgain)
+    pub(crate) async fn step<F>(
+        &mut self,
         config: &NodeConfig,
         node_id: &NodeId,
+        transport: &dyn Transport,
         command_rx: &flume::Receiver<NodeCommand<E::Action>>,
-        _game_state: Option<E::State>,
-    ) -> Result<(NodeStateInternal<E>, StepResult<E::State>)> {
+        get_engine: &F,
+        game_state: Option<E::State>,
+    ) -> Result<(Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)>
+    where
+        F: EngineFactory<E>,
+    {
         let timeout = tokio::time::Duration::from_millis(config.step_timeout_break_ms);
         let sleep = tokio::time::sleep(timeout);
         tokio::pin!(sleep);
@@ -55,7 +139,7 @@ where
                 // Timeout elapsed
                 () = &mut sleep => {
                     return Ok((
-                        NodeStateInternal::Client(self),
+                        None,
                         StepResult::Timeout,
                     ));
                 }
@@ -63,10 +147,16 @@ where
                 disconnect_result = self.liveliness_watch.disconnected() => {
                     match disconnect_result {
                         Ok(disconnected_id) => {
+                            // A host handing off its clients leaves right after telling them
+                            if let Some(Ok(message)) = self.notice_subscriber.recv_message().now_or_never() {
+                                if let Some(next_state) = self.on_notice(config, node_id, message) {
+                                    return Ok((Some(next_state), StepResult::RoleChanged(NodeRole::SearchingHost)));
+                                }
+                            }
                             tracing::info!("Node '{}' detected host '{}' disconnection, returning to search with preserved state", node_id, disconnected_id);
                             // Transition back to SearchingHost, preserving the game state
                             return Ok((
-                                NodeStateInternal::searching(),
+                                Some(NodeStateInternal::searching()),
                                 StepResult::RoleChanged(NodeRole::SearchingHost)
                             ));
                         }
@@ -74,16 +164,25 @@ where
                             tracing::warn!("Node '{}' liveliness error: {}", node_id, e);
                             // Treat error as disconnect
                             return Ok((
-                                NodeStateInternal::searching(),
+                                Some(NodeStateInternal::searching()),
                                 StepResult::RoleChanged(NodeRole::SearchingHost)
                             ));
                         }
                     }
                 }
                 // Game state received from host
-                state_result = self.state_subscriber.recv() => {
+                state_result = self.state_subscriber.recv_message() => {
                     match state_result {
-                        Ok((_sender_id, new_game_state)) => {
+                        Ok(message) if message.sender_id != self.host_id || message.session != self.session => {
+                            tracing::debug!(
+                                "Node '{}' ignoring game state from '{}' outside its session",
+                                node_id,
+                                message.sender_id
+                            );
+                            self.ignored_samples += 1;
+                            continue;
+                        }
+                        Ok(message) => {
                             tracing::debug!(
                                 "Node '{}' received game state from host '{}'",
                                 node_id,
@@ -91,10 +190,15 @@ where
                             );
                             // Return immediately with the received game state
                             return Ok((
-                                NodeStateInternal::Client(self),
-                                StepResult::GameState(new_game_state),
+                                None,
+                                StepResult::GameState(message.value),
                             ));
                         }
+                        Err(ArenaError::MalformedInput(reason)) => {
+                            tracing::debug!("Node '{}' dropping malformed game state: {}", node_id, reason);
+                            self.malformed_samples += 1;
+                            continue;
+                        }
                         Err(e) => {
                             tracing::warn!(
                                 "Node '{}' failed to receive game state: {}",
@@ -106,19 +210,89 @@ where
                         }
                     }
                 }
+                // Engine event received from host
+                event_result = self.event_subscriber.recv_message() => {
+                    match event_result {
+                        Ok(message) if message.sender_id != self.host_id || message.session != self.session => {
+                            tracing::debug!(
+                                "Node '{}' ignoring engine event from '{}' outside its session",
+                                node_id,
+                                message.sender_id
+                            );
+                            self.ignored_samples += 1;
+                            continue;
+                        }
+                        Ok(message) => {
+                            return Ok((None, StepResult::Event(self.room.clone(), message.value)));
+                        }
+                        Err(ArenaError::MalformedInput(reason)) => {
+                            tracing::debug!("Node '{}' dropping malformed engine event: {}", node_id, reason);
+                            self.malformed_samples += 1;
+                            continue;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Node '{}' failed to receive engine event: {}", node_id, e);
+                            continue;
+                        }
+                    }
+                }
+                // Notice received from the host
+                notice_result = self.notice_subscriber.recv_message() => {
+                    match notice_result {
+                        Ok(message) => {
+                            if let Some(next_state) = self.on_notice(config, node_id, message) {
+                                return Ok((Some(next_state), StepResult::RoleChanged(NodeRole::SearchingHost)));
+                            }
+                        }
+                        Err(ArenaError::MalformedInput(reason)) => {
+                            tracing::debug!("Node '{}' dropping malformed notice: {}", node_id, reason);
+                            self.malformed_samples += 1;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Node '{}' failed to receive notice: {}", node_id, e);
+                        }
+                    }
+                    continue;
+                }
+                // Chat message relayed by the host
+                chat_result = self.chat_subscriber.recv_message() => {
+                    match chat_result {
+                        Ok(message) if message.sender_id != self.host_id || message.session != self.session => {
+                            tracing::debug!(
+                                "Node '{}' ignoring chat message from '{}' outside its session",
+                                node_id,
+                                message.sender_id
+                            );
+                            self.ignored_samples += 1;
+                            continue;
+                        }
+                        Ok(message) => {
+                            return Ok((None, StepResult::Chat(message.value)));
+                        }
+                        Err(ArenaError::MalformedInput(reason)) => {
+                            tracing::debug!("Node '{}' dropping malformed chat message: {}", node_id, reason);
+                            self.malformed_samples += 1;
+                            continue;
+                        }
+                        Err(e) => {
+                            tracing::warn!("Node '{}' failed to receive chat message: {}", node_id, e);
+                            continue;
+                        }
+                    }
+                }
                 // Command received
                 result = command_rx.recv_async() => match result {
                     Err(_) => {
                         tracing::info!("Node '{}' command channel closed", node_id);
                         return Ok((
-                            NodeStateInternal::Stop,
+                            Some(NodeStateInternal::Stop),
                             StepResult::Stop,
                         ));
                     }
                     Ok(NodeCommand::Stop) => {
                         tracing::info!("Node '{}' received Stop command, exiting", node_id);
                         return Ok((
-                            NodeStateInternal::Stop,
+                            Some(NodeStateInternal::Stop),
                             StepResult::Stop,
                         ));
                     }
@@ -140,6 +314,44 @@ where
                         // Continue the loop
                         continue;
                     }
+                    Ok(NodeCommand::Chat { recipient, text }) => {
+                        // Send the message to the host, which relays it
+                        if let Err(e) = self.chat_publisher.put(&ChatRequest::Message { recipient, text }).await {
+                            tracing::error!(
+                                "Node '{}' failed to send chat message to host '{}': {}",
+                                node_id,
+                                self.host_id,
+                                e
+                            );
+                        }
+                        continue;
+                    }
+                    Ok(NodeCommand::Leave) => {
+                        tracing::info!("Node '{}' leaving host '{}'", node_id, self.host_id);
+                        return Ok((
+                            Some(NodeStateInternal::leaving(Some(self.host_id.clone()))),
+                            StepResult::RoleChanged(NodeRole::SearchingHost),
+                        ));
+                    }
+                    Ok(NodeCommand::SearchAgain) => {
+                        tracing::info!("Node '{}' leaving host '{}' to search again", node_id, self.host_id);
+                        return Ok((
+                            Some(NodeStateInternal::searching()),
+                            StepResult::RoleChanged(NodeRole::SearchingHost),
+                        ));
+                    }
+                    Ok(NodeCommand::BecomeHost) => {
+                        tracing::info!("Node '{}' leaving host '{}' to become host", node_id, self.host_id);
+                        let next_state = NodeStateInternal::host(
+                            get_engine,
+                            transport,
+                            config,
+                            node_id,
+                            game_state,
+                        )
+                        .await?;
+                        return Ok((Some(next_state), StepResult::RoleChanged(NodeRole::Host)));
+                    }
                 }
             }
         }
@@ -0,0 +1,90 @@
+/// Rate limiter coalescing values offered too early into the most recent one
+use tokio::time::{Duration, Instant};
+
+/// Hands on at most `rate` values per second, keeping the most recent value offered
+/// in between until its interval has elapsed
+pub(crate) struct Coalescer<T> {
+    /// Minimum time between two values (None hands on every value)
+    interval: Option<Duration>,
+    /// Time from which the next value may be handed on
+    next_at: Instant,
+    /// Most recent value offered too early
+    pending: Option<T>,
+}
+
+impl<T> Coalescer<T> {
+    pub(crate) fn new(rate: Option<u32>) -> Self {
+        Self {
+            interval: rate.map(|rate| Duration::from_secs(1) / rate.max(1)),
+            next_at: Instant::now(),
+            pending: None,
+        }
+    }
+
+    /// Offer a value, returning it if it may be handed on now
+    ///
+    /// Otherwise the value replaces the pending one until the deadline.
+    pub(crate) fn offer(&mut self, value: T) -> Option<T> {
+        let Some(interval) = self.interval else {
+            return Some(value);
+        };
+        let now = Instant::now();
+        if now >= self.next_at {
+            self.next_at = now + interval;
+            self.pending = None;
+            Some(value)
+        } else {
+            self.pending = Some(value);
+            None
+        }
+    }
+
+    /// Time at which the pending value may be handed on (None without pending value)
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.pending.as_ref().map(|_| self.next_at)
+    }
+
+    /// Take the pending value if its deadline has passed
+    pub(crate) fn take_due(&mut self) -> Option<T> {
+        let now = Instant::now();
+        if self.pending.is_none() || now < self.next_at {
+            return None;
+        }
+        if let Some(interval) = self.interval {
+            self.next_at = now + interval;
+        }
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_coalescer_keeps_latest() {
+        let mut coalescer = Coalescer::new(Some(10));
+        assert_eq!(coalescer.offer(1), Some(1));
+        assert_eq!(coalescer.offer(2), None);
+        assert_eq!(coalescer.offer(3), None);
+        assert_eq!(coalescer.take_due(), None);
+
+        let deadline = coalescer.deadline().unwrap();
+        tokio::time::sleep_until(deadline).await;
+        assert_eq!(coalescer.take_due(), Some(3));
+        assert_eq!(coalescer.deadline(), None);
+
+        // The interval restarts from the value handed on last
+        assert_eq!(coalescer.offer(4), None);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(coalescer.offer(5), Some(5));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_coalescer_unlimited() {
+        let mut coalescer = Coalescer::new(None);
+        assert_eq!(coalescer.offer(1), Some(1));
+        assert_eq!(coalescer.offer(2), Some(2));
+        assert_eq!(coalescer.deadline(), None);
+    }
+}
@@ -2,7 +2,13 @@
 
 use zenoh::key_expr::KeyExpr;
 
-use crate::node::types::NodeId;
+#[cfg(feature = "shared-memory")]
+use std::sync::Arc;
+
+use crate::network::{LinkQos, MatchTicket};
+#[cfg(feature = "shared-memory")]
+use crate::network::ShmPool;
+use crate::node::types::{ActionBuffering, ActionLimits, EngineQueues, MergePolicy, NodeId, RestartPolicy, RoomId, StateRates};
 
 // Main configuration for a Node
 #[derive(Debug, Clone)]
@@ -13,6 +19,10 @@ pub(crate) struct NodeConfig {
     /// Whether to force host mode (blocks Searching and Client states)
     pub force_host: bool,
 
+    /// Whether the node never becomes host on its own
+    /// A search that finds no host is retried with backoff instead.
+    pub client_only: bool,
+
     /// Timeout for step() method in milliseconds
     /// step() returns when either new game state is available or this timeout elapses
     pub step_timeout_break_ms: u64,
@@ -31,6 +41,64 @@ pub(crate) struct NodeConfig {
 
     /// Key expression prefix for all arena operations
     pub keyexpr_prefix: KeyExpr<'static>,
+
+    /// Rooms served when acting as host
+    /// Each room runs its own engine instance. Empty means a single game without rooms.
+    pub rooms: Vec<RoomId>,
+
+    /// Room requested when connecting to a host as client
+    /// None lets the host assign a room.
+    pub room: Option<RoomId>,
+
+    /// Ticket submitted to a matchmaker while searching
+    /// None searches for hosts directly.
+    pub matchmaking: Option<MatchTicket>,
+
+    /// What to do when an engine panics while hosting
+    pub engine_restart: RestartPolicy,
+
+    /// Whether a host yields to other hosts it discovers
+    /// Hosts look for other hosts every search timeout. Hosts with configured rooms or
+    /// in force_host mode never yield.
+    pub host_merge: MergePolicy,
+
+    /// How actions issued while searching are kept for the next host
+    pub action_buffering: ActionBuffering,
+
+    /// Limits applied to the actions of each client when acting as host
+    pub action_limits: ActionLimits,
+
+    /// Capacities and backpressure policies of the channels between a host and its engines
+    pub engine_queues: EngineQueues,
+
+    /// Maximum rates at which a host publishes and delivers the states of its engines
+    pub state_rates: StateRates,
+
+    /// Quality of service of actions sent by clients
+    pub action_qos: LinkQos,
+
+    /// Quality of service of states published by hosts
+    pub state_qos: LinkQos,
+
+    /// Quality of service of discovery and connection queries and their replies, and of
+    /// matchmaking tickets and assignments
+    pub handshake_qos: LinkQos,
+
+    /// Quality of service of engine events published by hosts
+    pub event_qos: LinkQos,
+
+    /// Quality of service of chat messages between hosts and their clients
+    pub chat_qos: LinkQos,
+
+    /// Quality of service of the notices sent by hosts to their clients (hand-offs, kicks)
+    pub notice_qos: LinkQos,
+
+    /// Number of chat messages to everybody a host keeps for members joining later
+    pub chat_history: usize,
+
+    /// Shared-memory pool large states are published from when acting as host
+    #[cfg(feature = "shared-memory")]
+    pub shared_memory: Option<Arc<ShmPool>>,
 }
 
 impl Default for NodeConfig {
@@ -38,10 +106,29 @@ impl Default for NodeConfig {
         Self {
             node_id: NodeId::generate(),
             force_host: false,
+            client_only: false,
             step_timeout_break_ms: 5000,
             search_timeout_ms: 3000, // 3 seconds to search for hosts
             search_jitter_ms: 1000, // 0-1 second random delay before searching
             keyexpr_prefix: KeyExpr::try_from("zenoh/arena").unwrap().into_owned(),
+            rooms: Vec::new(),
+            room: None,
+            matchmaking: None,
+            engine_restart: RestartPolicy::default(),
+            host_merge: MergePolicy::default(),
+            action_buffering: ActionBuffering::default(),
+            action_limits: ActionLimits::default(),
+            engine_queues: EngineQueues::default(),
+            state_rates: StateRates::default(),
+            action_qos: LinkQos::default(),
+            state_qos: LinkQos::default(),
+            handshake_qos: LinkQos::request(),
+            event_qos: LinkQos::reliable(),
+            chat_qos: LinkQos::reliable(),
+            notice_qos: LinkQos::reliable(),
+            chat_history: 0,
+            #[cfg(feature = "shared-memory")]
+            shared_memory: None,
         }
     }
 }
@@ -0,0 +1,200 @@
+/// Queues between a host and its engines, applying their backpressure policy
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use crate::node::types::{Backpressure, EngineQueue};
+
+/// Delay between checks for room in a full blocking queue
+const READY_POLL_MS: u64 = 1;
+
+/// Create the channel of a queue, bounded to its capacity
+fn channel<T>(queue: &EngineQueue) -> (flume::Sender<T>, flume::Receiver<T>) {
+    match (queue.backpressure, queue.capacity) {
+        (Backpressure::Latest, _) => flume::bounded(1),
+        (_, Some(capacity)) => flume::bounded(capacity.max(1)),
+        (_, None) => flume::unbounded(),
+    }
+}
+
+/// Sending end of a queue, waiting for room or dropping the oldest item when it is full
+pub(crate) struct QueueSender<T> {
+    tx: flume::Sender<T>,
+    /// Receiver kept to drop the oldest item of a full queue
+    rx: flume::Receiver<T>,
+    backpressure: Backpressure,
+    /// Number of items dropped, kept across engine restarts
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T> QueueSender<T> {
+    /// Create a queue, returning its sending end and the receiver for the consumer
+    pub(crate) fn new(queue: &EngineQueue, dropped: Arc<AtomicU64>) -> (Self, flume::Receiver<T>) {
+        let (tx, rx) = channel(queue);
+        let sender = Self {
+            tx,
+            rx: rx.clone(),
+            backpressure: queue.backpressure,
+            dropped,
+        };
+        (sender, rx)
+    }
+
+    /// Queue an item according to the backpressure policy
+    ///
+    /// Returns the item back if the consumer is gone.
+    pub(crate) async fn send(&self, item: T) -> Result<(), flume::SendError<T>> {
+        // The receiver kept by this sender does not count as a consumer
+        if self.tx.receiver_count() <= 1 {
+            return Err(flume::SendError(item));
+        }
+        match self.backpressure {
+            Backpressure::Block => self.tx.send_async(item).await,
+            Backpressure::DropOldest | Backpressure::Latest => {
+                let mut item = item;
+                loop {
+                    match self.tx.try_send(item) {
+                        Ok(()) => return Ok(()),
+                        Err(flume::TrySendError::Full(back)) => {
+                            if self.rx.try_recv().is_ok() {
+                                self.dropped.fetch_add(1, Ordering::Relaxed);
+                            }
+                            item = back;
+                        }
+                        Err(flume::TrySendError::Disconnected(back)) => return Err(flume::SendError(back)),
+                    }
+                }
+            }
+        }
+    }
+
+    /// Wait until [`send`](Self::send) can queue an item without waiting
+    ///
+    /// Only a full blocking queue makes this wait. Flume does not signal free room, so the
+    /// queue is checked again every millisecond. Cancel safe, unlike `send`, which drops
+    /// its item when cancelled.
+    pub(crate) async fn ready(&self) {
+        if !matches!(self.backpressure, Backpressure::Block) {
+            return;
+        }
+        while self.tx.is_full() && self.tx.receiver_count() > 1 {
+            tokio::time::sleep(std::time::Duration::from_millis(READY_POLL_MS)).await;
+        }
+    }
+
+    /// Number of queued items
+    pub(crate) fn len(&self) -> usize {
+        self.tx.len()
+    }
+
+    /// Number of items dropped from the full queue
+    pub(crate) fn dropped(&self) -> &Arc<AtomicU64> {
+        &self.dropped
+    }
+}
+
+/// Receiving end of a state queue
+pub(crate) struct QueueReceiver<T> {
+    rx: flume::Receiver<T>,
+    /// Number of items dropped, kept across engine restarts
+    dropped: Arc<AtomicU64>,
+}
+
+impl<T: Send + 'static> QueueReceiver<T> {
+    /// Create a queue, returning the sender for the producer and its receiving end
+    ///
+    /// With a dropping policy, items go through a task forwarding them into the bounded
+    /// queue, so that the producer never waits for the consumer.
+    pub(crate) fn new(queue: &EngineQueue, dropped: Arc<AtomicU64>) -> (flume::Sender<T>, Self) {
+        match queue.backpressure {
+            Backpressure::Block => {
+                let (tx, rx) = channel(queue);
+                (tx, Self { rx, dropped })
+            }
+            Backpressure::DropOldest | Backpressure::Latest => {
+                let (tx, forward_rx) = flume::unbounded();
+                let (sender, rx) = QueueSender::new(queue, dropped.clone());
+                tokio::spawn(async move {
+                    while let Ok(item) = forward_rx.recv_async().await {
+                        if sender.send(item).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+                (tx, Self { rx, dropped })
+            }
+        }
+    }
+
+    /// Wait for the next item, failing once the producer is gone and the queue is empty
+    pub(crate) async fn recv_async(&self) -> Result<T, flume::RecvError> {
+        self.rx.recv_async().await
+    }
+
+    /// Number of queued items
+    pub(crate) fn len(&self) -> usize {
+        self.rx.len()
+    }
+
+    /// Number of items dropped from the full queue
+    pub(crate) fn dropped(&self) -> &Arc<AtomicU64> {
+        &self.dropped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_queue_drop_oldest() {
+        let dropped = Arc::default();
+        let (sender, rx) = QueueSender::new(&EngineQueue::bounded(2, Backpressure::DropOldest), Arc::clone(&dropped));
+        for item in 0..5 {
+            sender.send(item).await.unwrap();
+        }
+        assert_eq!(sender.len(), 2);
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+        assert_eq!(rx.drain().collect::<Vec<_>>(), vec![3, 4]);
+
+        drop(rx);
+        assert!(sender.send(5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_queue_block_waits_for_room() {
+        let (sender, rx) = QueueSender::new(&EngineQueue::bounded(1, Backpressure::Block), Arc::default());
+        sender.send(0).await.unwrap();
+        let blocked = tokio::time::timeout(std::time::Duration::from_millis(10), sender.send(1)).await;
+        assert!(blocked.is_err());
+        assert_eq!(rx.recv_async().await.unwrap(), 0);
+        sender.send(1).await.unwrap();
+        assert_eq!(rx.recv_async().await.unwrap(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_queue_ready_waits_for_room() {
+        let (sender, rx) = QueueSender::new(&EngineQueue::bounded(1, Backpressure::Block), Arc::default());
+        sender.send(0).await.unwrap();
+        let blocked = tokio::time::timeout(std::time::Duration::from_millis(10), sender.ready()).await;
+        assert!(blocked.is_err());
+        assert_eq!(rx.recv_async().await.unwrap(), 0);
+        sender.ready().await;
+        assert_eq!(sender.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_state_queue_keeps_latest() {
+        let (tx, queue) = QueueReceiver::new(&EngineQueue::latest(), Arc::default());
+        for item in 0..10 {
+            tx.send(item).unwrap();
+        }
+        drop(tx);
+        // The forwarding task drops every state but the last one
+        while !queue.rx.is_disconnected() {
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(queue.recv_async().await.unwrap(), 9);
+        assert_eq!(queue.dropped().load(Ordering::Relaxed), 9);
+        assert!(queue.recv_async().await.is_err());
+    }
+}
@@ -1,24 +1,206 @@
-use crate::node::types::NodeId;
+use std::future::Future;
+
+use crate::codec::Codec;
+use crate::network::HandshakeInfo;
+use crate::node::types::{NodeId, PlayerSlot};
 
 /// Trait for game engine integration
 ///
-/// The engine runs only on the host node and processes actions from clients via channels
+/// The engine runs only on the host node and processes actions from clients via channels.
+/// Each action comes with the sender's [`PlayerSlot`], assigned by the host when the
+/// player joined, so engines can index players without their own bookkeeping.
 pub trait GameEngine: Send + Sync {
     /// Action type from user/client
-    type Action: zenoh_ext::Serialize + zenoh_ext::Deserialize + Send;
+    type Action: Send;
 
     /// State type sent to clients
-    type State: zenoh_ext::Serialize + zenoh_ext::Deserialize + Send + Clone;
+    type State: Send + Clone + 'static;
+
+    /// Event type sent to clients apart from the state (see [`take_events`](GameEngine::take_events))
+    ///
+    /// Use `()` for engines without events.
+    type Event: Send + Sync + 'static;
+
+    /// Codec used to encode actions, states and events on the wire
+    ///
+    /// Use [`ZenohCodec`](crate::ZenohCodec) for types implementing `zenoh_ext` serialization,
+    /// or one of the serde-based codecs enabled by the `json`, `bincode` or `postcard` features.
+    type Codec: Codec<Self::Action> + Codec<Self::State> + Codec<Self::Event>;
+
+    /// Game identifier exchanged during the handshake
+    ///
+    /// Nodes only connect to hosts declaring the same identifier.
+    const GAME_ID: &'static str = "";
+
+    /// Game version exchanged during the handshake
+    ///
+    /// Increment it whenever the `Action` or `State` wire layout changes incompatibly.
+    const GAME_VERSION: u32 = 0;
 
     /// Maximum number of clients allowed (None = unlimited)
     fn max_clients(&self) -> Option<usize>;
+
+    /// Number of teams players are distributed over
+    ///
+    /// The player in slot `n` joins team `n % team_count`.
+    fn team_count(&self) -> u32 {
+        1
+    }
+
+    /// Check an action received from a client before it is queued for the engine
+    ///
+    /// Runs on the host, after the client's [`ActionLimits`](crate::ActionLimits) are
+    /// checked. Return an error with the reason to reject the action; rejected actions
+    /// are handled like any other violation of the limits. Accepts every action by default.
+    fn validate_action(
+        &self,
+        _player: &NodeId,
+        _slot: PlayerSlot,
+        _action: &Self::Action,
+    ) -> std::result::Result<(), String> {
+        Ok(())
+    }
+
+    /// Hand over the receiving end of the engine's event channel
+    ///
+    /// Called by the host once after creating the engine. Engines emitting transient events
+    /// (sound cues, "line cleared", "you were hit") create a channel, keep its sender and
+    /// return its receiver; the host delivers every [`EngineEvent`] reliably to its
+    /// recipients, apart from the state. Returns None by default.
+    fn take_events(&mut self) -> Option<flume::Receiver<EngineEvent<Self::Event>>> {
+        None
+    }
+
+    /// Report how the engine ended once its state channel has closed
+    ///
+    /// The host uses it to tell a crash from the end of the game. The default reports a
+    /// clean end; engines running their own thread can keep it in an [`EngineThread`] and
+    /// delegate to [`EngineThread::exit`] to report panics.
+    fn exited(&mut self) -> impl Future<Output = EngineExit> + Send {
+        async { EngineExit::Finished }
+    }
+
+    /// Handshake information advertised by nodes running this engine
+    fn handshake_info() -> HandshakeInfo
+    where
+        Self: Sized,
+    {
+        HandshakeInfo::new(Self::GAME_ID, Self::GAME_VERSION)
+    }
+}
+
+/// Event emitted by an engine for the players of its room
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineEvent<T> {
+    /// Players receiving the event (None for everybody in the room)
+    pub recipients: Option<Vec<NodeId>>,
+    /// The event
+    pub event: T,
+}
+
+impl<T> EngineEvent<T> {
+    /// Event for everybody in the room
+    pub fn broadcast(event: T) -> Self {
+        Self {
+            recipients: None,
+            event,
+        }
+    }
+
+    /// Event for the given players only
+    pub fn to(recipients: Vec<NodeId>, event: T) -> Self {
+        Self {
+            recipients: Some(recipients),
+            event,
+        }
+    }
+
+    /// Whether a player receives the event
+    pub fn is_for(&self, player: &NodeId) -> bool {
+        self.recipients
+            .as_ref()
+            .is_none_or(|recipients| recipients.contains(player))
+    }
+}
+
+/// How an engine ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EngineExit {
+    /// The game ended normally
+    Finished,
+    /// The engine panicked, with the panic message
+    Crashed(String),
+}
+
+/// Thread running an engine, reporting whether it panicked
+///
+/// Engines spawning their own thread can use it instead of `std::thread::spawn` and
+/// forward [`GameEngine::exited`] to [`exit`](EngineThread::exit).
+#[derive(Debug)]
+pub struct EngineThread {
+    handle: Option<std::thread::JoinHandle<()>>,
+    /// Pending join of the finished thread, kept so that `exit` can be resumed if cancelled
+    join: Option<tokio::task::JoinHandle<std::thread::Result<()>>>,
+}
+
+impl EngineThread {
+    /// Spawn the engine thread
+    pub fn spawn<F>(f: F) -> Self
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        Self {
+            handle: Some(std::thread::spawn(f)),
+            join: None,
+        }
+    }
+
+    /// Wait for the thread to end and report how it ended
+    ///
+    /// Meant to be called once the engine's state channel has closed. Cancel-safe: a
+    /// dropped call is resumed by the next one.
+    pub async fn exit(&mut self) -> EngineExit {
+        if let Some(handle) = self.handle.take() {
+            self.join = Some(tokio::task::spawn_blocking(move || handle.join()));
+        }
+        let Some(join) = &mut self.join else {
+            return EngineExit::Finished;
+        };
+        let result = join.await;
+        self.join = None;
+        match result {
+            Ok(Ok(())) => EngineExit::Finished,
+            Ok(Err(payload)) => EngineExit::Crashed(panic_message(&*payload)),
+            Err(e) => EngineExit::Crashed(e.to_string()),
+        }
+    }
+}
+
+/// Extract the message of a panic payload
+pub(crate) fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "engine panicked".to_string()
+    }
+}
+
+/// Exit of an engine task spawned by the library
+pub(crate) async fn task_exit(task: &mut tokio::task::JoinHandle<()>) -> EngineExit {
+    match task.await {
+        Ok(()) => EngineExit::Finished,
+        Err(e) if e.is_panic() => EngineExit::Crashed(panic_message(&*e.into_panic())),
+        Err(e) => EngineExit::Crashed(e.to_string()),
+    }
 }
 
 /// Type alias for engine factory function
 ///
 /// This function creates a game engine instance given:
 /// - The host's NodeId (to identify the host player)
-/// - A receiver for actions from clients (with their NodeId)
+/// - A receiver for actions from players (with their NodeId and PlayerSlot)
 /// - A sender for broadcasting state updates to clients
 /// - An optional initial state to restore the engine to a previous state
 ///
@@ -33,7 +215,7 @@ pub trait GameEngine: Send + Sync {
 /// ```ignore
 /// fn create_engine(
 ///     host_id: NodeId,
-///     input_rx: flume::Receiver<(NodeId, Action)>,
+///     input_rx: flume::Receiver<(NodeId, PlayerSlot, Action)>,
 ///     output_tx: flume::Sender<State>,
 ///     initial_state: Option<State>
 /// ) -> MyEngine {
@@ -42,7 +224,7 @@ pub trait GameEngine: Send + Sync {
 /// ```
 pub trait EngineFactory<E: GameEngine>: Fn(
     NodeId,
-    flume::Receiver<(NodeId, E::Action)>,
+    flume::Receiver<(NodeId, PlayerSlot, E::Action)>,
     flume::Sender<E::State>,
     Option<E::State>
 ) -> E + Send + Sync {}
@@ -51,5 +233,5 @@ pub trait EngineFactory<E: GameEngine>: Fn(
 impl<E, F> EngineFactory<E> for F
 where
     E: GameEngine,
-    F: Fn(NodeId, flume::Receiver<(NodeId, E::Action)>, flume::Sender<E::State>, Option<E::State>) -> E + Send + Sync,
+    F: Fn(NodeId, flume::Receiver<(NodeId, PlayerSlot, E::Action)>, flume::Sender<E::State>, Option<E::State>) -> E + Send + Sync,
 {}
@@ -1,57 +1,532 @@
 /// Host state implementation
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
 
+use futures::future::select_all;
+use zenoh::bytes::ZBytes;
+use zenoh::key_expr::KeyExpr;
+
 use crate::StepResult;
-use crate::error::Result;
-use crate::network::keyexpr::NodeType;
+use crate::error::{ArenaError, Result};
+use crate::codec::{Codec, ZenohCodec};
+use crate::network::keyexpr::{KeyexprNode, LinkType, NodeType};
+use crate::network::{LinkQos, Transport};
 use crate::{
-    network::{host_queryable::HostRequest, NodePublisher, NodeSubscriber},
+    network::{host_queryable::HostRequest, ChatMessage, ChatRequest, DiscoveredHost, Discovery, HostNotice, HostQuerier, NodeMessage, NodePublisher, NodeSubscriber, MAX_CHAT_LEN},
     node::{
         config::NodeConfig,
-        game_engine::GameEngine,
+        game_engine::{EngineEvent, EngineExit, EngineFactory, GameEngine},
+        action_buffer::ActionBuffer,
         arena_node::NodeCommand,
-        types::{NodeId, NodeStateInternal},
+        chat::HostChat,
+        coalescer::Coalescer,
+        engine_queue::{QueueReceiver, QueueSender},
+        rate_limiter::RateLimiter,
+        types::{
+            ActionLimits, ActionViolations, EngineQueues, MergePolicy, NodeId, NodeStateInternal, PlayerSlot,
+            QueueStats, RestartPolicy, RoomId, RoomInfo, ViolationPolicy,
+        },
     },
 };
 
-/// State while acting as a host
-pub(crate) struct HostState<E>
+/// A game instance run by a host
+///
+/// Each room has its own engine, connected clients, player slots, action subscriber, state
+/// publisher and event publishers.
+/// A host without configured rooms runs a single room with no id, using keyexprs without
+/// a room segment.
+pub(crate) struct HostRoom<E>
 where
     E: GameEngine,
 {
+    /// Room identifier (None for a host without rooms)
+    pub(crate) id: Option<RoomId>,
     /// List of connected client IDs
     pub(crate) connected_clients: Vec<NodeId>,
-    /// Game engine (only present in Host mode)
+    /// Transport sessions clients were admitted from, checked against every action
+    pub(crate) client_sources: HashMap<NodeId, String>,
+    /// Rate limiters and violation counts of clients, created on their first action
+    client_limits: HashMap<NodeId, ClientLimits>,
+    /// Host playing in this room (it holds slot 0)
+    pub(crate) host_player: Option<NodeId>,
+    /// Slots assigned so far, kept for disconnected players so that they get them back
+    pub(crate) slots: Vec<(NodeId, PlayerSlot)>,
+    /// Game engine instance of this room
     pub(crate) engine: E,
+    /// Capacities and backpressure policies of the engine channels
+    queues: EngineQueues,
     /// Input channel sender (for HostState to send actions to engine)
-    pub(crate) input_tx: flume::Sender<(NodeId, E::Action)>,
+    pub(crate) input_tx: QueueSender<(NodeId, PlayerSlot, E::Action)>,
     /// Output channel receiver (for HostState to receive states from engine)
-    pub(crate) output_rx: flume::Receiver<E::State>,
+    pub(crate) output_rx: QueueReceiver<E::State>,
+    /// Last state published by the engine, used to restart it after a crash
+    pub(crate) last_state: Option<E::State>,
+    /// Number of times the engine was restarted after a crash
+    pub(crate) restarts: u32,
+    /// States of the engine waiting to be published to clients
+    publish_rate: Coalescer<E::State>,
+    /// States of the engine waiting to be returned by `step`
+    deliver_rate: Coalescer<E::State>,
+    /// Subscriber to receive actions from clients
+    pub(crate) action_subscriber: NodeSubscriber<E::Action, E::Codec>,
+    /// Publisher to send game state to all clients
+    pub(crate) state_publisher: NodePublisher<E::State, E::Codec>,
+    /// Events emitted by the engine (None if the engine emits none)
+    events: Option<flume::Receiver<EngineEvent<E::Event>>>,
+    /// Publisher to send engine events to all clients
+    event_publisher: NodePublisher<E::Event, E::Codec>,
+    /// Publishers to send engine events to single clients, declared on first use
+    event_targets: HashMap<NodeId, NodePublisher<E::Event, E::Codec>>,
+    /// Key expression prefix of the event links
+    prefix: KeyExpr<'static>,
+    /// Session id of the host, tagging the events
+    session: u64,
+    /// Quality of service of the event links
+    event_qos: LinkQos,
+}
+
+impl<E> HostRoom<E>
+where
+    E: GameEngine,
+{
+    /// Open a room: create its engine and declare its action subscriber and state publisher
+    ///
+    /// If `host_plays` is set, the host takes slot 0 of the room. Actions and states are
+    /// tagged with the host's `session`. The engine channels are bounded and the states
+    /// rate limited as configured.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) async fn open<F>(
+        get_engine: &F,
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        id: Option<RoomId>,
+        host_plays: bool,
+        session: u64,
+        initial_state: Option<E::State>,
+    ) -> Result<Self>
+    where
+        F: EngineFactory<E>,
+    {
+        let prefix = config.keyexpr_prefix.clone();
+        let queues = config.engine_queues;
+
+        // Create channels for engine communication
+        let (input_tx, input_rx) = QueueSender::new(&queues.actions, Arc::default());
+        let (output_tx, output_rx) = QueueReceiver::new(&queues.states, Arc::default());
+
+        // Create engine with the channels and optional initial state
+        let mut engine = get_engine(node_id.clone(), input_rx, output_tx, initial_state);
+        let events = engine.take_events();
+
+        // Create action subscriber to receive actions from clients
+        let action_subscriber =
+            NodeSubscriber::new(transport, prefix.clone(), LinkType::Action, node_id, id.clone())
+                .await?
+                .with_sessions();
+
+        // Create state publisher to send game state to all clients (using wildcard for receiver)
+        let state_publisher = NodePublisher::new(
+            transport,
+            prefix.clone(),
+            LinkType::State,
+            node_id,
+            None, // Broadcast to all clients
+            id.clone(),
+            config.state_qos,
+        )
+        .await?
+        .with_session(session);
+        #[cfg(feature = "shared-memory")]
+        let state_publisher = match &config.shared_memory {
+            Some(pool) => state_publisher.with_shared_memory(pool.clone()),
+            None => state_publisher,
+        };
+
+        // Create event publisher to send engine events to all clients
+        let event_publisher = NodePublisher::new(
+            transport,
+            prefix.clone(),
+            LinkType::Event,
+            node_id,
+            None, // Broadcast to all clients
+            id.clone(),
+            config.event_qos,
+        )
+        .await?
+        .with_session(session);
+
+        let host_player = host_plays.then(|| node_id.clone());
+        let slots = host_player
+            .iter()
+            .map(|host_id| (host_id.clone(), PlayerSlot::new(0, engine.team_count())))
+            .collect();
+
+        Ok(Self {
+            id,
+            connected_clients: Vec::new(),
+            client_sources: HashMap::new(),
+            client_limits: HashMap::new(),
+            host_player,
+            slots,
+            engine,
+            last_state: None,
+            restarts: 0,
+            publish_rate: Coalescer::new(config.state_rates.publish),
+            deliver_rate: Coalescer::new(config.state_rates.deliver),
+            queues,
+            input_tx,
+            output_rx,
+            action_subscriber,
+            state_publisher,
+            events,
+            event_publisher,
+            event_targets: HashMap::new(),
+            prefix,
+            session,
+            event_qos: config.event_qos,
+        })
+    }
+
+    /// Replace the engine of the room with a new instance, keeping its clients and slots
+    fn restart_engine<F>(&mut self, get_engine: &F, node_id: &NodeId, initial_state: Option<E::State>)
+    where
+        F: EngineFactory<E>,
+    {
+        let (input_tx, input_rx) = QueueSender::new(&self.queues.actions, self.input_tx.dropped().clone());
+        let (output_tx, output_rx) = QueueReceiver::new(&self.queues.states, self.output_rx.dropped().clone());
+        self.engine = get_engine(node_id.clone(), input_rx, output_tx, initial_state);
+        self.events = self.engine.take_events();
+        self.input_tx = input_tx;
+        self.output_rx = output_rx;
+    }
+
+    /// Check the encoded size of an action of a client, before decoding it
+    fn check_size(limits: &ActionLimits, size: usize) -> std::result::Result<(), ActionViolation> {
+        match limits.max_size {
+            Some(max_size) if size > max_size => Err(ActionViolation::Oversized(size)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Check a decoded action of a client against the rate limit and the engine's validation
+    fn check_action(
+        &mut self,
+        limits: &ActionLimits,
+        sender_id: &NodeId,
+        slot: PlayerSlot,
+        action: &E::Action,
+    ) -> std::result::Result<(), ActionViolation> {
+        if let Some(rate) = limits.rate {
+            let client_limits = self.client_limits.entry(sender_id.clone()).or_default();
+            let limiter = client_limits
+                .limiter
+                .get_or_insert_with(|| RateLimiter::new(rate, limits.burst));
+            if !limiter.try_acquire() {
+                return Err(ActionViolation::RateLimited);
+            }
+        }
+        self.engine
+            .validate_action(sender_id, slot, action)
+            .map_err(ActionViolation::Invalid)
+    }
+
+    /// Count a violation of a client, returning its number of violations so far
+    fn record_violation(&mut self, sender_id: &NodeId) -> u32 {
+        let client_limits = self.client_limits.entry(sender_id.clone()).or_default();
+        client_limits.violations += 1;
+        client_limits.violations
+    }
+
+    /// Check if the room has capacity for more clients
+    pub(crate) fn has_capacity(&self) -> bool {
+        match self.engine.max_clients() {
+            None => true, // Unlimited clients
+            Some(max_count) => self.connected_clients.len() < max_count,
+        }
+    }
+
+    /// Slot assigned to a player of the room
+    pub(crate) fn slot_of(&self, player_id: &NodeId) -> Option<PlayerSlot> {
+        self.slots
+            .iter()
+            .find(|(id, _)| id == player_id)
+            .map(|(_, slot)| *slot)
+    }
+
+    /// Check if a player is currently in the room
+    fn is_present(&self, player_id: &NodeId) -> bool {
+        self.host_player.as_ref() == Some(player_id) || self.connected_clients.contains(player_id)
+    }
+
+    /// Assign a slot to a joining client
+    ///
+    /// A client that played in the room before gets its previous slot back. Otherwise it
+    /// takes the lowest slot never assigned; when that slot would exceed the capacity of
+    /// the room, the lowest slot of a disconnected player is reassigned instead.
+    pub(crate) fn assign_slot(&mut self, client_id: &NodeId) -> PlayerSlot {
+        if let Some(slot) = self.slot_of(client_id) {
+            return slot;
+        }
+
+        let mut index = (0..)
+            .find(|index| self.slots.iter().all(|(_, slot)| slot.index != *index))
+            .expect("free slot index");
+        let capacity = self
+            .engine
+            .max_clients()
+            .map(|max| max + usize::from(self.host_player.is_some()));
+        if capacity.is_some_and(|capacity| index as usize >= capacity) {
+            let reclaimed = self
+                .slots
+                .iter()
+                .enumerate()
+                .filter(|(_, (id, _))| !self.is_present(id))
+                .min_by_key(|(_, (_, slot))| slot.index)
+                .map(|(pos, (_, slot))| (pos, slot.index));
+            if let Some((pos, reclaimed_index)) = reclaimed {
+                self.slots.remove(pos);
+                index = reclaimed_index;
+            }
+        }
+
+        let slot = PlayerSlot::new(index, self.engine.team_count());
+        self.slots.push((client_id.clone(), slot));
+        slot
+    }
+
+    /// Players currently in the room with their slots, ordered by slot
+    pub(crate) fn players(&self) -> Vec<(NodeId, PlayerSlot)> {
+        let mut players: Vec<_> = self
+            .slots
+            .iter()
+            .filter(|(id, _)| self.is_present(id))
+            .cloned()
+            .collect();
+        players.sort_by_key(|(_, slot)| slot.index);
+        players
+    }
+
+    /// Depth of the engine queues of the room and items they dropped
+    pub(crate) fn queue_stats(&self) -> QueueStats {
+        QueueStats {
+            room: self.id.clone(),
+            queued_actions: self.input_tx.len(),
+            queued_states: self.output_rx.len(),
+            dropped_actions: self.input_tx.dropped().load(Ordering::Relaxed),
+            dropped_states: self.output_rx.dropped().load(Ordering::Relaxed),
+        }
+    }
+
+    /// Public information about the room (None for a host without rooms)
+    pub(crate) fn info(&self) -> Option<RoomInfo> {
+        self.id.as_ref().map(|id| RoomInfo {
+            id: id.clone(),
+            connected_clients: self.connected_clients.clone(),
+            max_clients: self.engine.max_clients(),
+            players: self.players(),
+        })
+    }
+
+    /// Publish a state of the engine to all clients of the room
+    async fn publish_state(&self, node_id: &NodeId, state: &E::State) {
+        if let Err(e) = self.state_publisher.put(state).await {
+            tracing::error!(
+                "Node '{}' failed to publish game state: {}",
+                node_id,
+                e
+            );
+        }
+    }
+
+    /// Publish an event of the engine to its recipients among the clients of the room
+    ///
+    /// Events to everybody go on the broadcast link, others on the links of the recipients
+    /// present in the room.
+    async fn publish_event(&mut self, transport: &dyn Transport, node_id: &NodeId, event: &EngineEvent<E::Event>) {
+        let recipients = match &event.recipients {
+            None => {
+                if let Err(e) = self.event_publisher.put(&event.event).await {
+                    tracing::error!("Node '{}' failed to publish engine event: {}", node_id, e);
+                }
+                return;
+            }
+            Some(recipients) => recipients,
+        };
+        for recipient in recipients.iter().filter(|id| self.connected_clients.contains(id)) {
+            if !self.event_targets.contains_key(recipient) {
+                let publisher = match NodePublisher::new(
+                    transport,
+                    self.prefix.clone(),
+                    LinkType::Event,
+                    node_id,
+                    Some(recipient),
+                    self.id.clone(),
+                    self.event_qos,
+                )
+                .await
+                {
+                    Ok(publisher) => publisher.with_session(self.session),
+                    Err(e) => {
+                        tracing::error!("Node '{}' failed to declare event link to '{}': {}", node_id, recipient, e);
+                        continue;
+                    }
+                };
+                self.event_targets.insert(recipient.clone(), publisher);
+            }
+            if let Err(e) = self.event_targets[recipient].put(&event.event).await {
+                tracing::error!("Node '{}' failed to send engine event to '{}': {}", node_id, recipient, e);
+            }
+        }
+    }
+
+    /// Step result delivering a state of the engine (RoomState for a room with an id)
+    fn state_result(&self, state: E::State) -> StepResult<E::State, E::Event> {
+        match self.id.clone() {
+            Some(room_id) => StepResult::RoomState(room_id, state),
+            None => StepResult::GameState(state),
+        }
+    }
+
+    /// Time at which the next coalesced state is due (None without coalesced state)
+    fn next_state_deadline(&self) -> Option<tokio::time::Instant> {
+        self.publish_rate
+            .deadline()
+            .into_iter()
+            .chain(self.deliver_rate.deadline())
+            .min()
+    }
+
+    /// Wait for the next event emitted by the engine
+    ///
+    /// Never returns if the engine emits no events or dropped its event channel.
+    async fn next_engine_event(&self) -> EngineEvent<E::Event> {
+        match &self.events {
+            Some(events) => match events.recv_async().await {
+                Ok(event) => event,
+                Err(_) => std::future::pending().await,
+            },
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Wait for the next action from a client, or state or event from the engine
+    async fn next_event(&self) -> RoomEvent<E> {
+        tokio::select! {
+            action_result = self.action_subscriber.recv_payload() => RoomEvent::Action(action_result),
+            event = self.next_engine_event() => RoomEvent::Event(event),
+            state_result = self.output_rx.recv_async() => match state_result {
+                Ok(state) => RoomEvent::State(state),
+                Err(_) => RoomEvent::EngineExited,
+            },
+        }
+    }
+}
+
+/// Rate limiter and violation count of a client
+#[derive(Default)]
+struct ClientLimits {
+    /// Token bucket of the client (None until its first action, or without rate limit)
+    limiter: Option<RateLimiter>,
+    /// Number of actions of the client that broke the limits
+    violations: u32,
+}
+
+/// Reason an action of a client was refused
+#[derive(Debug)]
+enum ActionViolation {
+    /// The client exceeded its rate limit
+    RateLimited,
+    /// The action exceeds the maximum size (its size in bytes)
+    Oversized(usize),
+    /// The engine rejected the action (the reason)
+    Invalid(String),
+}
+
+impl std::fmt::Display for ActionViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActionViolation::RateLimited => write!(f, "rate limit exceeded"),
+            ActionViolation::Oversized(size) => write!(f, "action of {} bytes exceeds maximum size", size),
+            ActionViolation::Invalid(reason) => write!(f, "invalid action: {}", reason),
+        }
+    }
+}
+
+/// Event produced by a room
+enum RoomEvent<E: GameEngine> {
+    /// Encoded action received from a client with its sender information (or a reception error)
+    Action(Result<NodeMessage<ZBytes>>),
+    /// State produced by the engine
+    State(E::State),
+    /// Event emitted by the engine
+    Event(EngineEvent<E::Event>),
+    /// Engine output channel closed
+    EngineExited,
+}
+
+/// State while acting as a host
+pub(crate) struct HostState<E>
+where
+    E: GameEngine,
+{
+    /// Rooms run by the host (a single room with no id for a host without rooms)
+    pub(crate) rooms: Vec<HostRoom<E>>,
+    /// Chat shared by the clients of all the rooms
+    pub(crate) chat: HostChat,
+    /// Publisher for sending notices to all the clients
+    pub(crate) notice_publisher: NodePublisher<HostNotice, ZenohCodec>,
     /// Liveliness token for host discovery
     pub(crate) _liveliness_token: Option<crate::network::NodeLivelinessToken>,
     /// Queryable for host discovery
     pub(crate) queryable: Option<Arc<crate::network::HostQueryable>>,
     /// Liveliness watch to detect any client disconnect
     pub(crate) client_liveliness_watch: crate::network::NodeLivelinessWatch,
-    /// Subscriber to receive actions from clients
-    pub(crate) action_subscriber: NodeSubscriber<E::Action>,
-    /// Publisher to send game state to all clients
-    pub(crate) state_publisher: NodePublisher<E::State>,
+    /// Time of the next look for other hosts to merge with
+    pub(crate) next_merge_check: Option<tokio::time::Instant>,
+    /// Look for other hosts in progress, kept across steps until its query is finalized
+    pub(crate) merge_discovery: Option<Box<Discovery>>,
+    /// Random id of this hosting session, tagging actions and states
+    pub(crate) session: u64,
+    /// Number of actions ignored for coming from another session or an unadmitted node
+    pub(crate) ignored_samples: u64,
+    /// Number of actions dropped for coming from another transport session than their sender's
+    pub(crate) spoofed_samples: u64,
+    /// Number of handshake queries and actions dropped for being malformed
+    pub(crate) malformed_samples: u64,
+    /// Counters of actions breaking the action limits
+    pub(crate) action_violations: ActionViolations,
+    /// Clients kicked for repeated violations, with the time until which their connection
+    /// requests are rejected
+    pub(crate) kicked: HashMap<NodeId, tokio::time::Instant>,
+    /// Accepted clients whose liveliness token is not confirmed yet, with the time to check it
+    pub(crate) unconfirmed: HashMap<NodeId, tokio::time::Instant>,
 }
 
 impl<E> HostState<E>
 where
     E: GameEngine,
 {
+    /// List of connected client IDs across all rooms
+    pub(crate) fn connected_clients(&self) -> Vec<NodeId> {
+        self.rooms
+            .iter()
+            .flat_map(|room| room.connected_clients.iter().cloned())
+            .collect()
+    }
+
+    /// Public information about the rooms (empty for a host without rooms)
+    pub(crate) fn room_infos(&self) -> Vec<RoomInfo> {
+        self.rooms.iter().filter_map(HostRoom::info).collect()
+    }
+
     /// Check if host has capacity for more clients
     ///
-    /// Returns true if the current client count is below the maximum allowed.
+    /// Returns true if any room's client count is below its maximum allowed.
     /// Returns true if there's no maximum (unlimited clients).
     pub(crate) fn has_capacity(&self) -> bool {
-        match self.engine.max_clients() {
-            None => true, // Unlimited clients
-            Some(max_count) => self.connected_clients.len() < max_count,
-        }
+        self.rooms.iter().any(HostRoom::has_capacity)
     }
 
     /// Check if host is accepting new clients
@@ -67,111 +542,633 @@ where
         self.has_capacity()
     }
 
+    /// Deliver the actions buffered while searching to the engine of the first room
+    ///
+    /// An action leaves the buffer only once the engine queue has room for it, so that a
+    /// dropped step replays it again.
+    pub(crate) async fn replay_actions(&self, node_id: &NodeId, action_buffer: &mut ActionBuffer<E::Action>) {
+        let room = &self.rooms[0];
+        let slot = room.slot_of(node_id).expect("host plays in the first room");
+        while action_buffer.peek().is_some() {
+            room.input_tx.ready().await;
+            let Some(action) = action_buffer.pop() else {
+                break;
+            };
+            // Queued at once now that the queue has room
+            if let Err(e) = room.input_tx.send((node_id.clone(), slot, action)).await {
+                tracing::error!("Node '{}' failed to replay action to engine: {}", node_id, e);
+            }
+        }
+    }
+
+    /// Relay a chat message from a member of the session
+    ///
+    /// Returns the message if the host is one of its recipients. Messages to unknown
+    /// members and oversized messages are dropped.
+    async fn send_chat(
+        &mut self,
+        node_id: &NodeId,
+        transport: &dyn Transport,
+        sender: NodeId,
+        recipient: Option<NodeId>,
+        text: String,
+    ) -> Option<ChatMessage> {
+        if text.len() > MAX_CHAT_LEN {
+            tracing::warn!("Node '{}' dropping chat message of {} bytes from '{}'", node_id, text.len(), sender);
+            return None;
+        }
+        if let Some(recipient) = &recipient {
+            if recipient != node_id && !self.connected_clients().contains(recipient) {
+                tracing::debug!(
+                    "Node '{}' dropping chat message from '{}' to unknown member '{}'",
+                    node_id,
+                    sender,
+                    recipient
+                );
+                return None;
+            }
+        }
+        let to_host = recipient.as_ref().is_none_or(|recipient| recipient == node_id);
+        let message = HostChat::stamp(sender, recipient, text);
+        if message.recipient.as_ref() != Some(node_id) {
+            if let Err(e) = self.chat.relay(transport, node_id, &message).await {
+                tracing::error!("Node '{}' failed to relay chat message: {}", node_id, e);
+            }
+        }
+        to_host.then_some(message)
+    }
+
+    /// Handle a chat request received from a client
+    ///
+    /// Requests are only accepted from clients connected to this session, from the
+    /// transport session they connected from. Returns the relayed message if the host is
+    /// one of its recipients.
+    async fn handle_chat_request(
+        &mut self,
+        node_id: &NodeId,
+        transport: &dyn Transport,
+        message: NodeMessage<ChatRequest>,
+    ) -> Option<ChatMessage> {
+        let sender_id = message.sender_id;
+        let Some(room) = self.rooms.iter().find(|room| room.connected_clients.contains(&sender_id)) else {
+            tracing::debug!("Node '{}' ignoring chat request from unknown client '{}'", node_id, sender_id);
+            self.ignored_samples += 1;
+            return None;
+        };
+        if message.session != self.session {
+            tracing::debug!("Node '{}' ignoring chat request of client '{}' from another session", node_id, sender_id);
+            self.ignored_samples += 1;
+            return None;
+        }
+        if room
+            .client_sources
+            .get(&sender_id)
+            .is_some_and(|source| message.source.as_ref() != Some(source))
+        {
+            tracing::warn!(
+                "Node '{}' dropping chat request claiming to be from client '{}' sent from another transport session ({:?})",
+                node_id,
+                sender_id,
+                message.source
+            );
+            self.spoofed_samples += 1;
+            return None;
+        }
+        match message.value {
+            ChatRequest::Message { recipient, text } => {
+                self.send_chat(node_id, transport, sender_id, recipient, text).await
+            }
+            ChatRequest::History => {
+                if let Err(e) = self.chat.send_history(transport, node_id, &sender_id).await {
+                    tracing::error!("Node '{}' failed to send chat history to '{}': {}", node_id, sender_id, e);
+                }
+                None
+            }
+        }
+    }
+
+    /// Decode an action of a client, counting it as malformed if it fails
+    fn decode_action(&mut self, node_id: &NodeId, sender_id: &NodeId, payload: &ZBytes) -> Option<E::Action> {
+        match E::Codec::decode(payload) {
+            Ok(action) => Some(action),
+            Err(e) => {
+                tracing::debug!("Node '{}' dropping malformed action of client '{}': {}", node_id, sender_id, e);
+                self.malformed_samples += 1;
+                None
+            }
+        }
+    }
+
+    /// Send a notice to a single client
+    async fn send_notice(
+        &self,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        transport: &dyn Transport,
+        client_id: &NodeId,
+        notice: HostNotice,
+    ) {
+        let publisher = NodePublisher::<HostNotice, ZenohCodec>::new(
+            transport,
+            config.keyexpr_prefix.clone(),
+            LinkType::Notice,
+            node_id,
+            Some(client_id),
+            None,
+            config.notice_qos,
+        )
+        .await;
+        let result = match publisher {
+            Ok(publisher) => publisher.with_session(self.session).put(&notice).await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Node '{}' failed to send notice to client '{}': {}", node_id, client_id, e);
+        }
+    }
+
+    /// Advertise the current number of clients in discovery replies
+    fn advertise_players(&self) {
+        if let Some(queryable) = &self.queryable {
+            queryable.set_players(self.connected_clients().len() as u32);
+        }
+    }
+
+    /// Whether this host looks for other hosts to yield to
+    fn may_merge(&self, config: &NodeConfig) -> bool {
+        if config.force_host || !config.rooms.is_empty() {
+            return false;
+        }
+        match config.host_merge {
+            MergePolicy::Never => false,
+            MergePolicy::EmptyHosts => self.connected_clients().is_empty(),
+            MergePolicy::HandOff => true,
+        }
+    }
+
+    /// Wait for the end of the look for other hosts in progress, if any
+    async fn finish_merge_discovery(discovery: &mut Option<Box<Discovery>>) -> Vec<DiscoveredHost> {
+        match discovery {
+            Some(discovery) => discovery.finish().await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Find a discovered host ranking above this one
+    fn merge_target<'a>(&self, node_id: &NodeId, peers: &'a [DiscoveredHost]) -> Option<&'a DiscoveredHost> {
+        let players = self.connected_clients().len() as u32;
+        peers
+            .iter()
+            .find(|peer| peer.host_id != *node_id && peer.outranks(node_id, players))
+    }
+
+    /// Wait for the next event of any room
+    ///
+    /// Returns the index of the room together with its event.
+    async fn next_room_event(rooms: &[HostRoom<E>]) -> (usize, RoomEvent<E>) {
+        let futures_vec: Vec<_> = rooms
+            .iter()
+            .map(|room| Box::pin(room.next_event()))
+            .collect();
+        let (event, index, _remaining) = select_all(futures_vec).await;
+        (index, event)
+    }
+
+    /// Wait until the next coalesced state of a room is due, returning the room index
+    async fn next_due_state(rooms: &[HostRoom<E>]) -> usize {
+        let next = rooms
+            .iter()
+            .enumerate()
+            .filter_map(|(index, room)| room.next_state_deadline().map(|deadline| (deadline, index)))
+            .min();
+        match next {
+            Some((deadline, index)) => {
+                tokio::time::sleep_until(deadline).await;
+                index
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Wait until the token of an accepted client is due for a check, returning the client
+    async fn next_unconfirmed(unconfirmed: &HashMap<NodeId, tokio::time::Instant>) -> NodeId {
+        match unconfirmed.iter().min_by_key(|(_, deadline)| **deadline) {
+            Some((client_id, deadline)) => {
+                tokio::time::sleep_until(*deadline).await;
+                client_id.clone()
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Check that an accepted client declared its liveliness token
+    ///
+    /// A client whose step was dropped between the handshake and the declaration of its
+    /// token never shows up, so no disconnect would ever be seen for it: it is removed.
+    /// Disconnects seen before the check are settled by it as well.
+    async fn confirm_client(
+        &mut self,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        transport: &dyn Transport,
+        client_id: NodeId,
+    ) -> Result<()> {
+        let Some(room) = self.rooms.iter().find(|room| room.connected_clients.contains(&client_id)) else {
+            self.unconfirmed.remove(&client_id);
+            return Ok(());
+        };
+        let keyexpr = KeyexprNode::new(config.keyexpr_prefix.clone(), NodeType::Client, Some(client_id.clone()))
+            .with_room(room.id.clone());
+        let alive = match transport.get_liveliness(keyexpr.into()).await {
+            Ok(replies) => replies.recv_async().await.is_ok(),
+            Err(e) => {
+                tracing::warn!("Node '{}' failed to check liveliness of client '{}': {}", node_id, client_id, e);
+                false
+            }
+        };
+        self.unconfirmed.remove(&client_id);
+        if !alive {
+            tracing::info!("Node '{}' dropping client '{}' that never declared its token", node_id, client_id);
+            Self::handle_client_disconnect(self, config, node_id, transport, client_id).await?;
+        }
+        Ok(())
+    }
+
     /// Process the Host state - handle client connections and game actions
     ///
-    /// Consumes self and returns the next state (Host or Stop if stopped).
-    /// Handles commands from the command channel and processes game actions through the engine.
+    /// Returns the next state if the node leaves the Host state (searching or Stop).
+    /// Handles commands from the command channel and processes game actions through the engines.
     /// Also monitors client liveliness to detect disconnections.
     /// Returns when either:
-    /// - A new game state is produced by the engine
+    /// - A new game state is produced by an engine (GameState, or RoomState for a host with rooms),
+    ///   or a state coalesced by the configured delivery rate becomes due
     /// - The step timeout elapses
     /// - A Stop command is received (returns Stop)
-    /// - A client disconnects (handled and continues loop)
-    pub(crate) async fn step(
-        mut self,
+    /// - A client disconnects (handled and continues loop), or does not declare its liveliness
+    ///   token within the search timeout after being accepted (dropped and continues loop)
+    /// - A chat message to everybody or to the host is sent by a client or the host itself
+    /// - An engine emits an event to everybody or to the host (Event)
+    /// - A Leave or SearchAgain command is received (returns to searching)
+    /// - A higher ranked host is discovered and the merge policy makes this host yield
+    ///   (returns to searching to join it, handing its clients off to it)
+    ///
+    /// When the engine of a host without rooms exits, the node returns to searching.
+    /// The engine of a room is restarted instead, keeping the room's clients.
+    /// Engine panics are handled according to the configured restart policy and reported
+    /// as EngineCrashed.
+    pub(crate) async fn step<F>(
+        &mut self,
         config: &NodeConfig,
         node_id: &NodeId,
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         command_rx: &flume::Receiver<NodeCommand<E::Action>>,
-    ) -> Result<(NodeStateInternal<E>, StepResult<E::State>)> {
+        get_engine: &F,
+    ) -> Result<(Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)>
+    where
+        F: EngineFactory<E>,
+    {
         let timeout = tokio::time::Duration::from_millis(config.step_timeout_break_ms);
         let sleep = tokio::time::sleep(timeout);
         tokio::pin!(sleep);
 
+        let merge_interval = tokio::time::Duration::from_millis(config.search_timeout_ms);
+        let mut merge_at = *self
+            .next_merge_check
+            .get_or_insert_with(|| tokio::time::Instant::now() + merge_interval);
+
         // Process commands until timeout or new state
         while tokio::select! {
             // Timeout elapsed
             () = &mut sleep => {
                 false
             }
+            // Look for other hosts, e.g. after several nodes became host at the same time
+            () = tokio::time::sleep_until(merge_at), if self.merge_discovery.is_none() && self.may_merge(config) => {
+                match HostQuerier::start_discovery(
+                    transport,
+                    config.keyexpr_prefix.clone(),
+                    node_id.clone(),
+                    &E::handshake_info(),
+                    config.handshake_qos,
+                )
+                .await
+                {
+                    Ok(discovery) => self.merge_discovery = Some(Box::new(discovery)),
+                    Err(e) => {
+                        tracing::warn!("Node '{}' failed to look for other hosts: {}", node_id, e);
+                        merge_at = tokio::time::Instant::now() + merge_interval;
+                        self.next_merge_check = Some(merge_at);
+                    }
+                }
+                true
+            }
+            peers = Self::finish_merge_discovery(&mut self.merge_discovery), if self.merge_discovery.is_some() => {
+                self.merge_discovery = None;
+                merge_at = tokio::time::Instant::now() + merge_interval;
+                self.next_merge_check = Some(merge_at);
+                // Clients may have joined while the discovery was running
+                if self.may_merge(config) {
+                    if let Some(peer) = self.merge_target(node_id, &peers) {
+                        tracing::info!(
+                            "Node '{}' yielding to host '{}' with {} client(s)",
+                            node_id,
+                            peer.host_id,
+                            peer.players
+                        );
+                        // Hand the clients off to the peer, which this host joins as well
+                        if !self.connected_clients().is_empty() {
+                            let notice = HostNotice::HandOff { host: peer.host_id.clone() };
+                            if let Err(e) = self.notice_publisher.put(&notice).await {
+                                tracing::warn!("Node '{}' failed to hand off its clients: {}", node_id, e);
+                            }
+                        }
+                        return Ok((
+                            Some(NodeStateInternal::handed_off(
+                                peer.host_id.clone(),
+                                tokio::time::Instant::now() + merge_interval,
+                            )),
+                            StepResult::RoleChanged(crate::NodeRole::SearchingHost),
+                        ));
+                    }
+                }
+                true
+            }
             // Query received from a client (connection request)
             request_result = async {
                 let queryable = self.queryable.clone().expect("queryable available");
                 queryable.expect_connection().await
             }, if self.queryable.is_some() => {
-                if let Ok(request) = request_result {
-                    Self::handle_connection_request(&mut self, config, node_id, session, request).await?;
+                match request_result {
+                    Ok(request) => {
+                        Self::handle_connection_request(self, config, node_id, transport, request).await?;
+                    }
+                    Err(ArenaError::MalformedInput(reason)) => {
+                        tracing::debug!("Node '{}' dropping malformed handshake query: {}", node_id, reason);
+                        self.malformed_samples += 1;
+                    }
+                    Err(_) => {}
                 }
                 true
             }
             // Client disconnect detected via liveliness watch
-            disconnect_result = self.client_liveliness_watch.disconnected() => {
-                if self.client_liveliness_watch.has_subscribers() {
-                    if let Ok(disconnected_id) = disconnect_result {
-                        Self::handle_client_disconnect(&mut self, config, node_id, session, disconnected_id).await?;
+            disconnect_result = self.client_liveliness_watch.disconnected(), if self.client_liveliness_watch.has_subscribers() => {
+                match disconnect_result {
+                    // The drop may be the token of a previous connection of a client that
+                    // reconnected since, its current token is checked when due instead
+                    Ok(disconnected_id) if self.unconfirmed.contains_key(&disconnected_id) => {
+                        tracing::debug!(
+                            "Node '{}' deferring disconnect of client '{}' until its token is checked",
+                            node_id,
+                            disconnected_id
+                        );
+                    }
+                    Ok(disconnected_id) => {
+                        Self::handle_client_disconnect(self, config, node_id, transport, disconnected_id).await?;
                     }
+                    Err(_) => {}
                 }
                 true
             }
-            // Action received from a client
-            action_result = self.action_subscriber.recv() => {
-                match action_result {
-                    Ok((sender_id, action)) => {
-                        tracing::debug!(
-                            "Node '{}' received action from client '{}'",
-                            node_id,
-                            sender_id
-                        );
-                        // Send action to the engine via input channel
-                        if let Err(e) = self.input_tx.send((sender_id, action)) {
-                            tracing::error!(
-                                "Node '{}' failed to send action to engine: {}",
-                                node_id,
-                                e
-                            );
+            // Accepted client due for a check of its liveliness token
+            client_id = Self::next_unconfirmed(&self.unconfirmed) => {
+                self.confirm_client(config, node_id, transport, client_id).await?;
+                true
+            }
+            // Chat request received from a client
+            chat_result = self.chat.recv() => {
+                match chat_result {
+                    Ok(message) => {
+                        if let Some(message) = self.handle_chat_request(node_id, transport, message).await {
+                            return Ok((None, StepResult::Chat(message)));
                         }
-
-                        true
+                    }
+                    Err(ArenaError::MalformedInput(reason)) => {
+                        tracing::debug!("Node '{}' dropping malformed chat request: {}", node_id, reason);
+                        self.malformed_samples += 1;
                     }
                     Err(e) => {
-                        tracing::warn!(
-                            "Node '{}' failed to receive action: {}",
-                            node_id,
-                            e
-                        );
-                        true
+                        tracing::warn!("Node '{}' failed to receive chat request: {}", node_id, e);
                     }
                 }
+                true
             }
-            // State received from engine
-            state_result = self.output_rx.recv_async() => {
-                match state_result {
-                    Ok(new_game_state) => {
-                        // Publish game state to all clients
-                        if let Err(e) = self.state_publisher.put(&new_game_state).await {
-                            tracing::error!(
-                                "Node '{}' failed to publish game state: {}",
+            // Action received from a client or state produced by an engine
+            (index, event) = Self::next_room_event(&self.rooms) => match event {
+                RoomEvent::Action(Ok(message)) => {
+                    let sender_id = message.sender_id;
+                    tracing::debug!(
+                        "Node '{}' received action from client '{}'",
+                        node_id,
+                        sender_id
+                    );
+                    let room = &self.rooms[index];
+                    // Only players present in the room have a slot
+                    let slot = room.slot_of(&sender_id).filter(|_| room.is_present(&sender_id));
+                    // The sender id comes from the key expression, anyone can claim it
+                    let spoofed = room
+                        .client_sources
+                        .get(&sender_id)
+                        .is_some_and(|source| message.source.as_ref() != Some(source));
+                    match slot {
+                        Some(_) if message.session != self.session => {
+                            tracing::debug!(
+                                "Node '{}' ignoring action of client '{}' from another session",
                                 node_id,
-                                e
+                                sender_id
                             );
+                            self.ignored_samples += 1;
                         }
+                        Some(_) if spoofed => {
+                            tracing::warn!(
+                                "Node '{}' dropping action claiming to be from client '{}' sent from another transport session ({:?})",
+                                node_id,
+                                sender_id,
+                                message.source
+                            );
+                            self.spoofed_samples += 1;
+                        }
+                        Some(slot) => {
+                            let limits = &config.action_limits;
+                            // Oversized actions are refused without decoding them
+                            let (action, violation) = match HostRoom::<E>::check_size(limits, message.size) {
+                                Err(violation) => (None, Some(violation)),
+                                Ok(()) => match self.decode_action(node_id, &sender_id, &message.value) {
+                                    Some(action) => {
+                                        let violation = self.rooms[index].check_action(limits, &sender_id, slot, &action).err();
+                                        (Some(action), violation)
+                                    }
+                                    None => (None, None),
+                                },
+                            };
+                            let room = &mut self.rooms[index];
+                            let forward = match violation {
+                                None => action.is_some(),
+                                Some(violation) => {
+                                    match violation {
+                                        ActionViolation::RateLimited => self.action_violations.rate_limited += 1,
+                                        ActionViolation::Oversized(_) => self.action_violations.oversized += 1,
+                                        ActionViolation::Invalid(_) => self.action_violations.invalid += 1,
+                                    }
+                                    let violations = room.record_violation(&sender_id);
+                                    match limits.on_violation {
+                                        ViolationPolicy::Warn => {
+                                            tracing::warn!(
+                                                "Node '{}' forwarding action of client '{}' despite violation: {}",
+                                                node_id,
+                                                sender_id,
+                                                violation
+                                            );
+                                            true
+                                        }
+                                        ViolationPolicy::Drop => {
+                                            tracing::debug!(
+                                                "Node '{}' dropping action of client '{}': {}",
+                                                node_id,
+                                                sender_id,
+                                                violation
+                                            );
+                                            false
+                                        }
+                                        ViolationPolicy::Kick { after } => {
+                                            tracing::debug!(
+                                                "Node '{}' dropping action of client '{}': {}",
+                                                node_id,
+                                                sender_id,
+                                                violation
+                                            );
+                                            if violations >= after {
+                                                tracing::warn!(
+                                                    "Node '{}' kicking client '{}' after {} violation(s)",
+                                                    node_id,
+                                                    sender_id,
+                                                    violations
+                                                );
+                                                // Expired kicks are dropped, so only recent ones are kept
+                                                let now = tokio::time::Instant::now();
+                                                self.kicked.retain(|_, until| *until > now);
+                                                let ban = std::time::Duration::from_millis(config.search_timeout_ms);
+                                                self.kicked.insert(sender_id.clone(), now + ban);
+                                                self.action_violations.kicked += 1;
+                                                self.send_notice(config, node_id, transport, &sender_id, HostNotice::Kicked).await;
+                                                Self::handle_client_disconnect(
+                                                    self,
+                                                    config,
+                                                    node_id,
+                                                    transport,
+                                                    sender_id.clone(),
+                                                )
+                                                .await?;
+                                            }
+                                            false
+                                        }
+                                    }
+                                }
+                            };
+                            // Send action to the engine via input channel
+                            if forward {
+                                // An oversized action forwarded despite the violation is decoded only now
+                                let action = match action {
+                                    Some(action) => Some(action),
+                                    None => self.decode_action(node_id, &sender_id, &message.value),
+                                };
+                                if let Some(action) = action {
+                                    if let Err(e) = self.rooms[index].input_tx.send((sender_id, slot, action)).await {
+                                        tracing::error!(
+                                            "Node '{}' failed to send action to engine: {}",
+                                            node_id,
+                                            e
+                                        );
+                                    }
+                                }
+                            }
+                        }
+                        None => {
+                            tracing::warn!(
+                                "Node '{}' ignoring action from unknown client '{}'",
+                                node_id,
+                                sender_id
+                            );
+                            self.ignored_samples += 1;
+                        }
+                    }
 
-                        // Return immediately with the new game state
-                        return Ok((
-                            NodeStateInternal::Host(self),
-                            StepResult::GameState(new_game_state),
-                        ));
+                    true
+                }
+                RoomEvent::Action(Err(ArenaError::MalformedInput(reason))) => {
+                    tracing::debug!("Node '{}' dropping malformed action: {}", node_id, reason);
+                    self.malformed_samples += 1;
+                    true
+                }
+                RoomEvent::Action(Err(e)) => {
+                    tracing::warn!(
+                        "Node '{}' failed to receive action: {}",
+                        node_id,
+                        e
+                    );
+                    true
+                }
+                RoomEvent::State(new_game_state) => {
+                    let room = &mut self.rooms[index];
+                    room.last_state = Some(new_game_state.clone());
+                    // Publish game state to all clients of the room, unless it comes too early
+                    if let Some(state) = room.publish_rate.offer(new_game_state.clone()) {
+                        room.publish_state(node_id, &state).await;
                     }
-                    Err(_) => {
-                        tracing::info!(
-                            "Node '{}' engine thread exited (game over)",
-                            node_id
-                        );
-                        return Ok((
-                            NodeStateInternal::searching(),
-                            StepResult::RoleChanged(crate::NodeRole::SearchingHost),
-                        ));
+
+                    // Return immediately with the new game state, unless it comes too early
+                    match room.deliver_rate.offer(new_game_state) {
+                        Some(state) => return Ok((None, room.state_result(state))),
+                        None => true,
+                    }
+                }
+                RoomEvent::Event(event) => {
+                    let room = &mut self.rooms[index];
+                    room.publish_event(transport, node_id, &event).await;
+                    // Return the event if the host is one of its recipients
+                    if event.is_for(node_id) {
+                        return Ok((None, StepResult::Event(room.id.clone(), event.event)));
                     }
+                    true
+                }
+                RoomEvent::EngineExited => {
+                    let room = &mut self.rooms[index];
+                    if let EngineExit::Crashed(reason) = room.engine.exited().await {
+                        return Ok(self.handle_engine_crash(config, node_id, get_engine, index, reason));
+                    }
+                    match &room.id {
+                        Some(room_id) => {
+                            tracing::info!(
+                                "Node '{}' engine of room '{}' exited (game over), restarting",
+                                node_id,
+                                room_id
+                            );
+                            room.restart_engine(get_engine, node_id, None);
+                            room.last_state = None;
+                            room.restarts = 0;
+                            true
+                        }
+                        None => {
+                            tracing::info!(
+                                "Node '{}' engine thread exited (game over)",
+                                node_id
+                            );
+                            return Ok((
+                                Some(NodeStateInternal::searching()),
+                                StepResult::RoleChanged(crate::NodeRole::SearchingHost),
+                            ));
+                        }
+                    }
+                }
+            },
+            // Coalesced state of an engine whose publish or delivery interval has elapsed
+            index = Self::next_due_state(&self.rooms) => {
+                let room = &mut self.rooms[index];
+                if let Some(state) = room.publish_rate.take_due() {
+                    room.publish_state(node_id, &state).await;
+                }
+                match room.deliver_rate.take_due() {
+                    Some(state) => return Ok((None, room.state_result(state))),
+                    None => true,
                 }
             }
             // Command received
@@ -179,14 +1176,14 @@ where
                 Err(_) => {
                     tracing::info!("Node '{}' command channel closed", node_id);
                     return Ok((
-                        NodeStateInternal::Stop,
+                        Some(NodeStateInternal::Stop),
                         StepResult::Stop,
                     ));
                 }
                 Ok(NodeCommand::Stop) => {
                     tracing::info!("Node '{}' received Stop command, exiting", node_id);
                     return Ok((
-                        NodeStateInternal::Stop,
+                        Some(NodeStateInternal::Stop),
                         StepResult::Stop,
                     ));
                 }
@@ -195,8 +1192,10 @@ where
                         "Node '{}' processing action in host mode",
                         node_id
                     );
-                    // Send action to the engine via input channel
-                    if let Err(e) = self.input_tx.send((node_id.clone(), action)) {
+                    // Send action to the engine of the first room, where the host plays
+                    let room = &self.rooms[0];
+                    let slot = room.slot_of(node_id).expect("host plays in the first room");
+                    if let Err(e) = room.input_tx.send((node_id.clone(), slot, action)).await {
                         tracing::error!(
                             "Node '{}' failed to send action to engine: {}",
                             node_id,
@@ -206,95 +1205,251 @@ where
 
                     true
                 }
+                Ok(NodeCommand::Chat { recipient, text }) => {
+                    match self.send_chat(node_id, transport, node_id.clone(), recipient, text).await {
+                        Some(message) => return Ok((None, StepResult::Chat(message))),
+                        None => true,
+                    }
+                }
+                Ok(NodeCommand::BecomeHost) => {
+                    tracing::debug!("Node '{}' already hosting", node_id);
+                    true
+                }
+                Ok(NodeCommand::Leave | NodeCommand::SearchAgain) if config.force_host => {
+                    tracing::warn!("Node '{}' is forced to host, ignoring role command", node_id);
+                    true
+                }
+                Ok(NodeCommand::Leave) => {
+                    tracing::info!("Node '{}' closing its game", node_id);
+                    return Ok((
+                        Some(NodeStateInternal::leaving(None)),
+                        StepResult::RoleChanged(crate::NodeRole::SearchingHost),
+                    ));
+                }
+                Ok(NodeCommand::SearchAgain) => {
+                    tracing::info!("Node '{}' closing its game to search again", node_id);
+                    return Ok((
+                        Some(NodeStateInternal::searching()),
+                        StepResult::RoleChanged(crate::NodeRole::SearchingHost),
+                    ));
+                }
             }
         } {}
 
         // Timeout occurred without receiving new game state
         Ok((
-            NodeStateInternal::Host(self),
+            None,
             StepResult::Timeout,
         ))
     }
 
+    /// Handle a panic of the engine of a room
+    ///
+    /// Restarts the engine from the last published state if the restart policy allows it.
+    /// Otherwise the crash ends the game: a room gets a fresh engine and a host without
+    /// rooms returns to searching. The crash is reported in either case.
+    #[allow(clippy::type_complexity)]
+    fn handle_engine_crash<F>(
+        &mut self,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        get_engine: &F,
+        index: usize,
+        reason: String,
+    ) -> (Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)
+    where
+        F: EngineFactory<E>,
+    {
+        let room = &mut self.rooms[index];
+        let room_id = room.id.clone();
+        let room_name = room_id
+            .as_ref()
+            .map(|id| format!(" of room '{}'", id))
+            .unwrap_or_default();
+        let restart = match config.engine_restart {
+            RestartPolicy::Never => false,
+            RestartPolicy::Restart { max_restarts } => room.restarts < max_restarts,
+        };
+
+        if restart {
+            room.restarts += 1;
+            tracing::error!(
+                "Node '{}' engine{} crashed: {}, restarting from last state ({}/{:?})",
+                node_id,
+                room_name,
+                reason,
+                room.restarts,
+                config.engine_restart
+            );
+            let last_state = room.last_state.clone();
+            room.restart_engine(get_engine, node_id, last_state);
+        } else if room_id.is_some() {
+            tracing::error!("Node '{}' engine{} crashed: {}, starting a new game", node_id, room_name, reason);
+            room.restart_engine(get_engine, node_id, None);
+            room.last_state = None;
+            room.restarts = 0;
+        } else {
+            tracing::error!("Node '{}' engine crashed: {}, game over", node_id, reason);
+            return (Some(NodeStateInternal::searching()), StepResult::EngineCrashed(room_id, reason));
+        }
+        (None, StepResult::EngineCrashed(room_id, reason))
+    }
+
+    /// Select the room for a connection request
+    ///
+    /// Returns the requested room if it exists and has capacity, otherwise the first
+    /// room with capacity. A client still in a room, reconnecting before its disconnect
+    /// was seen, gets that room back. Returns the rejection reason if no room can take
+    /// the client or the client was kicked.
+    fn select_room(&self, request: &HostRequest) -> std::result::Result<usize, String> {
+        if self
+            .kicked
+            .get(request.client_id())
+            .is_some_and(|until| *until > tokio::time::Instant::now())
+        {
+            return Err("Kicked by host".to_string());
+        }
+        if let Some(index) = self
+            .rooms
+            .iter()
+            .position(|room| room.connected_clients.contains(request.client_id()))
+        {
+            return Ok(index);
+        }
+        match request.requested_room() {
+            Some(requested) => {
+                match self.rooms.iter().position(|room| room.id.as_ref() == Some(requested)) {
+                    Some(index) if self.rooms[index].has_capacity() => Ok(index),
+                    Some(_) => Err(format!("Room '{}' is full", requested)),
+                    None => Err(format!("Unknown room '{}'", requested)),
+                }
+            }
+            None => self
+                .rooms
+                .iter()
+                .position(HostRoom::has_capacity)
+                .ok_or_else(|| "Maximum number of clients reached".to_string()),
+        }
+    }
+
     /// Handle a connection request from a client
     ///
-    /// Checks if the current client count is below the maximum.
-    /// Accepts the connection if capacity is available, otherwise rejects it.
+    /// Selects a room for the client (the requested one, or the first with free capacity).
+    /// Accepts the connection into that room, otherwise rejects it with the reason.
+    /// A client already in the room is accepted again without being counted twice.
+    /// Handshakes without a source are rejected if the transport reports sources; otherwise
+    /// the client is accepted unauthenticated, its actions are not checked for spoofing.
     async fn handle_connection_request(
         host_state: &mut Self,
         config: &NodeConfig,
         node_id: &NodeId,
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         request: HostRequest,
     ) -> Result<()> {
-        let should_accept = host_state.has_capacity();
-
-        if should_accept {
-            match request.accept().await {
-                Ok(client_id) => {
-                    let max_clients = host_state.engine.max_clients();
-                    tracing::info!(
-                        "Node '{}' accepted connection from client '{}' ({}/{})",
-                        node_id,
-                        client_id,
-                        host_state.connected_clients.len() + 1,
-                        max_clients
-                            .map(|m| m.to_string())
-                            .unwrap_or_else(|| "unlimited".to_string())
-                    );
-                    // Track accepted client
-                    host_state.connected_clients.push(client_id.clone());
-
-                    // Subscribe to liveliness events for the client so we can detect disconnects
-                    match host_state
-                        .client_liveliness_watch
-                        .subscribe(
-                            session,
-                            config.keyexpr_prefix.clone(),
-                            NodeType::Client,
-                            Some(client_id.clone()),
-                        )
-                        .await
-                    {
-                        Ok(()) => {
-                            tracing::debug!(
-                                "Node '{}' subscribed to liveliness for client '{}'",
-                                node_id,
-                                client_id
-                            );
+        let selected = if request.source().is_none() && transport.reports_sources() {
+            Err("Handshake without source".to_string())
+        } else {
+            host_state.select_room(&request)
+        };
+        match selected {
+            Ok(index) => {
+                let room_id = host_state.rooms[index].id.clone();
+                let slot = host_state.rooms[index].assign_slot(request.client_id());
+                let source = request.source().map(str::to_string);
+                let rejoining = host_state.rooms[index].connected_clients.contains(request.client_id());
+                match request.accept(room_id.clone(), slot).await {
+                    Ok(client_id) => {
+                        let room = &mut host_state.rooms[index];
+                        let max_clients = room.engine.max_clients();
+                        tracing::info!(
+                            "Node '{}' {} connection from client '{}' as {}{} ({}/{})",
+                            node_id,
+                            if rejoining { "accepted again" } else { "accepted" },
+                            client_id,
+                            slot,
+                            room_id
+                                .as_ref()
+                                .map(|id| format!(" into room '{}'", id))
+                                .unwrap_or_default(),
+                            room.connected_clients.len() + usize::from(!rejoining),
+                            max_clients
+                                .map(|m| m.to_string())
+                                .unwrap_or_else(|| "unlimited".to_string())
+                        );
+                        // Track accepted client, bound to the session it connected from
+                        if !rejoining {
+                            room.connected_clients.push(client_id.clone());
                         }
-                        Err(e) => {
-                            tracing::warn!(
-                                "Node '{}' failed to subscribe to liveliness for client '{}': {}",
-                                node_id,
-                                client_id,
-                                e
-                            );
+                        match source {
+                            Some(source) => room.client_sources.insert(client_id.clone(), source),
+                            None => {
+                                tracing::warn!(
+                                    "Node '{}' accepted client '{}' without authentication, the transport does not report sources",
+                                    node_id,
+                                    client_id
+                                );
+                                room.client_sources.remove(&client_id)
+                            }
+                        };
+                        // The client declares its token after the handshake, check it shows up
+                        let confirm_at = tokio::time::Instant::now()
+                            + tokio::time::Duration::from_millis(config.search_timeout_ms);
+                        host_state.unconfirmed.insert(client_id.clone(), confirm_at);
+
+                        // Subscribe to liveliness events for the client so we can detect disconnects
+                        // (a rejoining client is already watched)
+                        if !rejoining {
+                            match host_state
+                                .client_liveliness_watch
+                                .subscribe(
+                                    transport,
+                                    config.keyexpr_prefix.clone(),
+                                    NodeType::Client,
+                                    Some(client_id.clone()),
+                                    room_id,
+                                )
+                                .await
+                            {
+                                Ok(()) => {
+                                    tracing::debug!(
+                                        "Node '{}' subscribed to liveliness for client '{}'",
+                                        node_id,
+                                        client_id
+                                    );
+                                }
+                                Err(e) => {
+                                    tracing::warn!(
+                                        "Node '{}' failed to subscribe to liveliness for client '{}': {}",
+                                        node_id,
+                                        client_id,
+                                        e
+                                    );
+                                }
+                            }
                         }
-                    }
 
-                    // Update queryable if we've reached capacity
-                    if !host_state.has_capacity() && host_state.queryable.is_some() {
-                        host_state.queryable = None;
-                        tracing::debug!("Host '{}' capacity reached (dropped queryable)", node_id);
+                        // Update queryable if we've reached capacity
+                        if !host_state.has_capacity() && host_state.queryable.is_some() {
+                            host_state.queryable = None;
+                            tracing::debug!("Host '{}' capacity reached (dropped queryable)", node_id);
+                        }
+                        host_state.advertise_players();
+                    }
+                    Err(e) => {
+                        tracing::warn!("Node '{}' failed to accept connection: {:?}", node_id, e);
                     }
-                }
-                Err(e) => {
-                    tracing::warn!("Node '{}' failed to accept connection: {:?}", node_id, e);
                 }
             }
-        } else {
-            let current_count = host_state.connected_clients.len();
-            let max_clients = host_state.engine.max_clients();
-            tracing::info!(
-                "Node '{}' rejected connection from client '{}' (limit reached: {}/{})",
-                node_id,
-                request.client_id().as_str(),
-                current_count,
-                max_clients.unwrap_or(0)
-            );
-            if let Err(e) = request.reject("Maximum number of clients reached").await {
-                tracing::warn!("Node '{}' failed to reject connection: {:?}", node_id, e);
+            Err(reason) => {
+                tracing::info!(
+                    "Node '{}' rejected connection from client '{}': {}",
+                    node_id,
+                    request.client_id().as_str(),
+                    reason
+                );
+                if let Err(e) = request.reject(&reason).await {
+                    tracing::warn!("Node '{}' failed to reject connection: {:?}", node_id, e);
+                }
             }
         }
         Ok(())
@@ -305,7 +1460,7 @@ where
         host_state: &mut Self,
         config: &NodeConfig,
         node_id: &NodeId,
-        session: &zenoh::Session,
+        transport: &dyn Transport,
         disconnected_id: NodeId,
     ) -> Result<()> {
         tracing::info!(
@@ -314,16 +1469,22 @@ where
             disconnected_id
         );
 
-        let removed = if let Some(pos) = host_state
-            .connected_clients
-            .iter()
-            .position(|id| id == &disconnected_id)
-        {
-            host_state.connected_clients.remove(pos);
-            true
-        } else {
-            false
-        };
+        let mut removed = false;
+        for room in host_state.rooms.iter_mut() {
+            if let Some(pos) = room
+                .connected_clients
+                .iter()
+                .position(|id| id == &disconnected_id)
+            {
+                room.connected_clients.remove(pos);
+                room.client_sources.remove(&disconnected_id);
+                room.client_limits.remove(&disconnected_id);
+                room.event_targets.remove(&disconnected_id);
+                removed = true;
+            }
+        }
+        host_state.unconfirmed.remove(&disconnected_id);
+        host_state.chat.forget(&disconnected_id);
 
         if !removed {
             tracing::debug!(
@@ -336,14 +1497,17 @@ where
         // Resume accepting clients if we now have capacity and queryable was dropped
         if host_state.has_capacity() && host_state.queryable.is_none() {
             let new_queryable = crate::network::HostQueryable::declare(
-                session,
+                transport,
                 config.keyexpr_prefix.clone(),
                 node_id.clone(),
+                E::handshake_info().with_session(host_state.session),
+                config.handshake_qos,
             )
             .await?;
             host_state.queryable = Some(Arc::new(new_queryable));
             tracing::debug!("Host '{}' resumed accepting clients", node_id);
         }
+        host_state.advertise_players();
 
         Ok(())
     }
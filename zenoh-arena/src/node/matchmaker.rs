@@ -0,0 +1,498 @@
+/// Standalone matchmaking service node
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use zenoh::{Resolvable, key_expr::KeyExpr};
+
+use super::config::NodeConfig;
+use super::types::NodeId;
+use crate::codec::ZenohCodec;
+use crate::error::Result;
+use crate::network::keyexpr::{LinkType, NodeType};
+use crate::network::{
+    MatchAssignment, MatchTicket, NodeLivelinessToken, NodeLivelinessWatch, NodePublisher,
+    NodeSubscriber, Transport,
+};
+
+/// Party waiting in the matchmaker queue
+///
+/// A party is queued once tickets from all of its members have been received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueEntry {
+    /// Members of the party, sorted by node ID
+    pub members: Vec<NodeId>,
+    /// Average rating of the members
+    pub rating: i32,
+    /// Region tag of the party
+    pub region: String,
+    /// Time when the party became complete in the queue
+    pub enqueued_at: tokio::time::Instant,
+}
+
+/// Match proposed by the rules
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchProposal {
+    /// Indices of the matched entries in the queue
+    pub entries: Vec<usize>,
+    /// Member of one of the entries that hosts the match
+    pub host: NodeId,
+}
+
+/// Rules deciding which queued parties are matched together
+///
+/// Implemented for closures taking the queue and returning the proposed matches.
+pub trait MatchRules: Send + Sync + 'static {
+    /// Form matches from the current queue
+    ///
+    /// Proposals referencing an entry twice, an unknown entry or a host outside of
+    /// the matched entries are ignored by the matchmaker.
+    fn form_matches(&self, queue: &[QueueEntry]) -> Vec<MatchProposal>;
+}
+
+impl<F> MatchRules for F
+where
+    F: Fn(&[QueueEntry]) -> Vec<MatchProposal> + Send + Sync + 'static,
+{
+    fn form_matches(&self, queue: &[QueueEntry]) -> Vec<MatchProposal> {
+        self(queue)
+    }
+}
+
+/// Rating and region based rules
+///
+/// Matches parties of the same region whose ratings differ by at most `max_rating_gap`,
+/// until exactly `players_per_match` players are gathered. The match is hosted by the
+/// party that waited the longest.
+#[derive(Debug, Clone)]
+pub struct StandardMatchRules {
+    /// Number of players in a match
+    pub players_per_match: usize,
+    /// Maximum rating difference between matched parties
+    pub max_rating_gap: u32,
+}
+
+impl StandardMatchRules {
+    /// Create rules for matches of the given size without rating restriction
+    pub fn new(players_per_match: usize) -> Self {
+        Self {
+            players_per_match,
+            max_rating_gap: u32::MAX,
+        }
+    }
+
+    /// Set the maximum rating difference between matched parties
+    pub fn max_rating_gap(mut self, max_rating_gap: u32) -> Self {
+        self.max_rating_gap = max_rating_gap;
+        self
+    }
+}
+
+impl MatchRules for StandardMatchRules {
+    fn form_matches(&self, queue: &[QueueEntry]) -> Vec<MatchProposal> {
+        // Sort by region, then rating, so that compatible parties are adjacent
+        let mut order: Vec<usize> = (0..queue.len()).collect();
+        order.sort_by(|&a, &b| {
+            (&queue[a].region, queue[a].rating).cmp(&(&queue[b].region, queue[b].rating))
+        });
+
+        let mut used = vec![false; queue.len()];
+        let mut proposals = Vec::new();
+        for (pos, &first) in order.iter().enumerate() {
+            if used[first] || queue[first].members.len() > self.players_per_match {
+                continue;
+            }
+            let mut entries = vec![first];
+            let mut count = queue[first].members.len();
+            for &next in &order[pos + 1..] {
+                if count == self.players_per_match {
+                    break;
+                }
+                if queue[next].region != queue[first].region
+                    || (queue[next].rating as i64 - queue[first].rating as i64)
+                        > self.max_rating_gap as i64
+                {
+                    break;
+                }
+                if !used[next] && count + queue[next].members.len() <= self.players_per_match {
+                    entries.push(next);
+                    count += queue[next].members.len();
+                }
+            }
+            if count != self.players_per_match {
+                continue;
+            }
+            for &index in &entries {
+                used[index] = true;
+            }
+            let host_entry = entries
+                .iter()
+                .copied()
+                .min_by_key(|&index| queue[index].enqueued_at)
+                .expect("match has entries");
+            proposals.push(MatchProposal {
+                host: queue[host_entry].members[0].clone(),
+                entries,
+            });
+        }
+        proposals
+    }
+}
+
+/// Matchmaking service node
+///
+/// Collects tickets from nodes waiting for a match, forms matches according to its
+/// [`MatchRules`] and tells the participants which of them hosts. The participants then
+/// finalize the connection with the regular handshake. Tickets of nodes that leave the
+/// network are dropped from the queue.
+///
+/// A single matchmaker is expected per keyexpr prefix.
+pub struct Matchmaker {
+    /// Matchmaker identifier
+    id: NodeId,
+    /// Configuration (prefix and step timeout)
+    config: NodeConfig,
+    /// Network transport
+    transport: Arc<dyn Transport>,
+    /// Rules forming matches
+    rules: Box<dyn MatchRules>,
+    /// Latest ticket of each waiting node with the time it was first received
+    tickets: HashMap<NodeId, (MatchTicket, tokio::time::Instant)>,
+    /// Subscriber receiving tickets
+    ticket_subscriber: NodeSubscriber<MatchTicket, ZenohCodec>,
+    /// Liveliness watch detecting nodes leaving the network
+    node_watch: NodeLivelinessWatch,
+    /// Liveliness token protecting the matchmaker name
+    _node_liveliness_token: NodeLivelinessToken,
+    /// Liveliness token advertising the matchmaker
+    _matchmaker_liveliness_token: NodeLivelinessToken,
+}
+
+impl Matchmaker {
+    async fn new_internal(
+        config: NodeConfig,
+        transport: Arc<dyn Transport>,
+        rules: Box<dyn MatchRules>,
+    ) -> Result<Self> {
+        let id = config.node_id.clone();
+        let prefix = config.keyexpr_prefix.clone();
+
+        let node_liveliness_token =
+            NodeLivelinessToken::declare(&*transport, prefix.clone(), NodeType::Node, id.clone(), None)
+                .await?;
+        let matchmaker_liveliness_token = NodeLivelinessToken::declare(
+            &*transport,
+            prefix.clone(),
+            NodeType::Matchmaker,
+            id.clone(),
+            None,
+        )
+        .await?;
+
+        // Receive tickets sent to any matchmaker: <prefix>/ticket/*/<matchmaker_id>
+        let ticket_subscriber =
+            NodeSubscriber::new(&*transport, prefix.clone(), LinkType::Ticket, &id, None).await?;
+
+        let mut node_watch = NodeLivelinessWatch::new();
+        node_watch
+            .subscribe(&*transport, prefix, NodeType::Node, None, None)
+            .await?;
+
+        tracing::info!("Matchmaker '{}' initialized", id);
+
+        Ok(Self {
+            id,
+            config,
+            transport,
+            rules,
+            tickets: HashMap::new(),
+            ticket_subscriber,
+            node_watch,
+            _node_liveliness_token: node_liveliness_token,
+            _matchmaker_liveliness_token: matchmaker_liveliness_token,
+        })
+    }
+
+    /// Get matchmaker ID
+    pub fn id(&self) -> &NodeId {
+        &self.id
+    }
+
+    /// Get the parties currently waiting in the queue
+    ///
+    /// Only parties whose members all sent a ticket are listed, oldest first.
+    pub fn queue(&self) -> Vec<QueueEntry> {
+        let mut tickets: Vec<_> = self.tickets.iter().collect();
+        tickets.sort_by(|(a_id, (_, a_at)), (b_id, (_, b_at))| (a_at, a_id.as_str()).cmp(&(b_at, b_id.as_str())));
+
+        let mut seen = HashSet::new();
+        let mut queue = Vec::new();
+        for (owner, (ticket, _)) in tickets {
+            let members = party_members(owner, ticket);
+            if !seen.insert(members.clone()) {
+                continue;
+            }
+            // Every member must have sent a ticket listing the same party
+            let member_tickets: Option<Vec<_>> = members
+                .iter()
+                .map(|member| {
+                    self.tickets
+                        .get(member)
+                        .filter(|(ticket, _)| party_members(member, ticket) == members)
+                })
+                .collect();
+            let Some(member_tickets) = member_tickets else {
+                continue;
+            };
+            let rating_sum: i64 = member_tickets.iter().map(|(t, _)| t.rating as i64).sum();
+            let enqueued_at = member_tickets
+                .iter()
+                .map(|(_, at)| *at)
+                .max()
+                .expect("party has members");
+            queue.push(QueueEntry {
+                rating: (rating_sum / members.len() as i64) as i32,
+                region: ticket.region.clone(),
+                members,
+                enqueued_at,
+            });
+        }
+        queue.sort_by(|a, b| {
+            let a_names: Vec<&str> = a.members.iter().map(NodeId::as_str).collect();
+            let b_names: Vec<&str> = b.members.iter().map(NodeId::as_str).collect();
+            (a.enqueued_at, a_names).cmp(&(b.enqueued_at, b_names))
+        });
+        queue
+    }
+
+    /// Execute one step of the matchmaker
+    ///
+    /// Receives tickets and tracks nodes leaving the network until at least one match is
+    /// formed or the step timeout elapses. Returns the matches formed during the step
+    /// (empty on timeout); their participants have already been notified.
+    pub async fn step(&mut self) -> Result<Vec<MatchAssignment>> {
+        let timeout = tokio::time::Duration::from_millis(self.config.step_timeout_break_ms);
+        let sleep = tokio::time::sleep(timeout);
+        tokio::pin!(sleep);
+
+        loop {
+            tokio::select! {
+                () = &mut sleep => {
+                    return Ok(Vec::new());
+                }
+                ticket_result = self.ticket_subscriber.recv() => match ticket_result {
+                    Ok((node_id, ticket)) => {
+                        tracing::debug!("Matchmaker '{}' received ticket from '{}'", self.id, node_id);
+                        let enqueued_at = self
+                            .tickets
+                            .get(&node_id)
+                            .map(|(_, at)| *at)
+                            .unwrap_or_else(tokio::time::Instant::now);
+                        self.tickets.insert(node_id, (ticket, enqueued_at));
+
+                        let assignments = self.form_matches().await?;
+                        if !assignments.is_empty() {
+                            return Ok(assignments);
+                        }
+                    }
+                    Err(e) => {
+                        tracing::warn!("Matchmaker '{}' failed to receive ticket: {}", self.id, e);
+                    }
+                },
+                left_result = self.node_watch.disconnected() => {
+                    if let Ok(node_id) = left_result {
+                        if self.tickets.remove(&node_id).is_some() {
+                            tracing::info!("Matchmaker '{}' dropped ticket of '{}' (left)", self.id, node_id);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Apply the rules to the queue and notify the participants of the formed matches
+    async fn form_matches(&mut self) -> Result<Vec<MatchAssignment>> {
+        let queue = self.queue();
+        let proposals = self.rules.form_matches(&queue);
+
+        let mut used = HashSet::new();
+        let mut assignments = Vec::new();
+        for proposal in proposals {
+            let valid = !proposal.entries.is_empty()
+                && proposal
+                    .entries
+                    .iter()
+                    .all(|&index| index < queue.len() && !used.contains(&index))
+                && proposal.entries.iter().collect::<HashSet<_>>().len() == proposal.entries.len();
+            if !valid {
+                tracing::warn!("Matchmaker '{}' ignored invalid proposal: {:?}", self.id, proposal);
+                continue;
+            }
+            let members: Vec<NodeId> = proposal
+                .entries
+                .iter()
+                .flat_map(|&index| queue[index].members.iter().cloned())
+                .collect();
+            if !members.contains(&proposal.host) {
+                tracing::warn!(
+                    "Matchmaker '{}' ignored proposal hosted outside of the match: {:?}",
+                    self.id,
+                    proposal
+                );
+                continue;
+            }
+            used.extend(proposal.entries.iter().copied());
+
+            let assignment = MatchAssignment {
+                host: proposal.host,
+                members,
+            };
+            for member in &assignment.members {
+                self.tickets.remove(member);
+                let publisher: NodePublisher<MatchAssignment, ZenohCodec> = NodePublisher::new(
+                    &*self.transport,
+                    self.config.keyexpr_prefix.clone(),
+                    LinkType::Match,
+                    &self.id,
+                    Some(member),
+                    None,
+                    self.config.handshake_qos,
+                )
+                .await?;
+                publisher.put(&assignment).await?;
+            }
+            tracing::info!(
+                "Matchmaker '{}' formed match hosted by '{}' with {} player(s)",
+                self.id,
+                assignment.host,
+                assignment.members.len()
+            );
+            assignments.push(assignment);
+        }
+        Ok(assignments)
+    }
+}
+
+/// All members of the party of a ticket owner, sorted and deduplicated
+fn party_members(owner: &NodeId, ticket: &MatchTicket) -> Vec<NodeId> {
+    let mut members: Vec<NodeId> = ticket.party.clone();
+    members.push(owner.clone());
+    members.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    members.dedup();
+    members
+}
+
+/// Builder for matchmaker nodes
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+pub struct MatchmakerBuilder<'a> {
+    transport: Arc<dyn Transport>,
+    rules: Box<dyn MatchRules>,
+    config: NodeConfig,
+    _phantom: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> MatchmakerBuilder<'a> {
+    /// Create a new MatchmakerBuilder
+    pub(crate) fn new(transport: Arc<dyn Transport>, rules: Box<dyn MatchRules>) -> Self {
+        Self {
+            transport,
+            rules,
+            config: NodeConfig::default(),
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Set the matchmaker name
+    pub fn name(mut self, name: String) -> Result<Self> {
+        self.config.node_id = NodeId::from_name(name)?;
+        Ok(self)
+    }
+
+    /// Set the step timeout in milliseconds
+    pub fn step_timeout_break_ms(mut self, timeout_ms: u64) -> Self {
+        self.config.step_timeout_break_ms = timeout_ms;
+        self
+    }
+
+    /// Set the key expression prefix
+    pub fn prefix(mut self, prefix: KeyExpr<'static>) -> Self {
+        self.config.keyexpr_prefix = prefix;
+        self
+    }
+}
+
+impl<'a> Resolvable for MatchmakerBuilder<'a> {
+    type To = Result<Matchmaker>;
+}
+
+impl<'a> std::future::IntoFuture for MatchmakerBuilder<'a> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move { Matchmaker::new_internal(self.config, self.transport, self.rules).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, rating: i32, region: &str, waited_ms: u64) -> QueueEntry {
+        QueueEntry {
+            members: vec![NodeId::from_name(name.to_string()).unwrap()],
+            rating,
+            region: region.to_string(),
+            enqueued_at: tokio::time::Instant::now() - std::time::Duration::from_millis(waited_ms),
+        }
+    }
+
+    #[test]
+    fn test_standard_rules_match_by_region_and_rating() {
+        let queue = vec![
+            entry("a", 1000, "eu", 0),
+            entry("b", 1500, "eu", 0),
+            entry("c", 1050, "eu", 500),
+            entry("d", 1000, "us", 0),
+        ];
+        let rules = StandardMatchRules::new(2).max_rating_gap(100);
+        let proposals = rules.form_matches(&queue);
+
+        assert_eq!(proposals.len(), 1);
+        let mut entries = proposals[0].entries.clone();
+        entries.sort();
+        assert_eq!(entries, vec![0, 2]);
+        // The longest waiting party hosts
+        assert_eq!(proposals[0].host.as_str(), "c");
+    }
+
+    #[test]
+    fn test_standard_rules_party_size() {
+        let mut party = entry("a", 1000, "eu", 0);
+        party.members.push(NodeId::from_name("b".to_string()).unwrap());
+        let queue = vec![party, entry("c", 1000, "eu", 0), entry("d", 1000, "eu", 0)];
+
+        let proposals = StandardMatchRules::new(3).form_matches(&queue);
+        assert_eq!(proposals.len(), 1);
+        assert_eq!(proposals[0].entries.len(), 2);
+        assert!(proposals[0].entries.contains(&0));
+
+        // A party larger than a match is never matched
+        assert!(StandardMatchRules::new(1).form_matches(&queue[..1]).is_empty());
+    }
+
+    #[test]
+    fn test_party_members() {
+        let owner = NodeId::from_name("b".to_string()).unwrap();
+        let ticket = MatchTicket::new(0, "eu").with_party(vec![
+            NodeId::from_name("c".to_string()).unwrap(),
+            NodeId::from_name("a".to_string()).unwrap(),
+            owner.clone(),
+        ]);
+        let members: Vec<_> = party_members(&owner, &ticket)
+            .iter()
+            .map(|id| id.as_str().to_string())
+            .collect();
+        assert_eq!(members, vec!["a", "b", "c"]);
+    }
+}
@@ -1,11 +1,20 @@
 // Module declarations
+pub(crate) mod action_buffer;
 pub(crate) mod arena_node;
+pub(crate) mod async_engine;
+pub(crate) mod chat;
 pub(crate) mod config;
+pub(crate) mod engine_queue;
 pub(crate) mod client_state;
+pub(crate) mod coalescer;
 pub(crate) mod game_engine;
 pub(crate) mod host_state;
+pub(crate) mod matchmaker;
+pub(crate) mod observer;
+pub(crate) mod rate_limiter;
 pub(crate) mod searching_host_state;
 pub(crate) mod session_ext;
+pub(crate) mod tick_engine;
 pub(crate) mod types;
 pub(crate) mod name_generator;
 
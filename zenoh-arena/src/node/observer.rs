@@ -0,0 +1,518 @@
+/// Arena observer listing the nodes of an arena from their liveliness tokens
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use zenoh::key_expr::KeyExpr;
+use zenoh::sample::SampleKind;
+use zenoh::Resolvable;
+
+use super::config::NodeConfig;
+use super::types::{NodeId, RoomId};
+use crate::error::Result;
+use crate::network::keyexpr::{KeyexprLink, KeyexprNode, LinkType, NodeType};
+use crate::network::{HandshakeInfo, HostQuerier, LinkQos, Transport, TransportReceiver, TransportSample};
+
+/// Role of an observed node, derived from the liveliness tokens it declares
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ObservedRole {
+    /// Searching for a host (only the node token is declared)
+    SearchingHost,
+    /// Connected to a host
+    Client,
+    /// Hosting a game
+    Host,
+    /// Matchmaker node
+    Matchmaker,
+}
+
+impl std::fmt::Display for ObservedRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ObservedRole::SearchingHost => write!(f, "searching"),
+            ObservedRole::Client => write!(f, "client"),
+            ObservedRole::Host => write!(f, "host"),
+            ObservedRole::Matchmaker => write!(f, "matchmaker"),
+        }
+    }
+}
+
+/// Node seen by an [`ArenaObserver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObservedNode {
+    /// Key expression prefix of the arena the node belongs to
+    pub prefix: KeyExpr<'static>,
+    /// Node identifier
+    pub id: NodeId,
+    /// Current role of the node
+    pub role: ObservedRole,
+    /// Room of a client (None for other roles, or if the host does not serve rooms)
+    pub room: Option<RoomId>,
+    /// Host of a client, where known (clients of older versions do not advertise it)
+    pub host: Option<NodeId>,
+}
+
+/// Snapshot of the nodes seen by an [`ArenaObserver`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArenaRoster {
+    /// Nodes ordered by prefix, then by id
+    pub nodes: Vec<ObservedNode>,
+}
+
+impl ArenaRoster {
+    /// Nodes acting as host
+    pub fn hosts(&self) -> impl Iterator<Item = &ObservedNode> {
+        self.with_role(ObservedRole::Host)
+    }
+
+    /// Nodes connected to a host
+    pub fn clients(&self) -> impl Iterator<Item = &ObservedNode> {
+        self.with_role(ObservedRole::Client)
+    }
+
+    /// Clients known to be connected to a host
+    pub fn clients_of<'a>(&'a self, host: &'a ObservedNode) -> impl Iterator<Item = &'a ObservedNode> {
+        self.clients()
+            .filter(move |client| client.prefix == host.prefix && client.host.as_ref() == Some(&host.id))
+    }
+
+    /// Nodes with the given role
+    pub fn with_role(&self, role: ObservedRole) -> impl Iterator<Item = &ObservedNode> {
+        self.nodes.iter().filter(move |node| node.role == role)
+    }
+}
+
+/// Messages published on a type of link, counted by an [`ArenaObserver`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LinkTraffic {
+    /// Type of link
+    pub link: LinkType,
+    /// Number of messages published since the observer was created
+    pub messages: u64,
+    /// Number of payload bytes published since the observer was created
+    pub bytes: u64,
+}
+
+/// Host answering a probe of an [`ArenaObserver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbedHost {
+    /// Key expression prefix of the arena of the host
+    pub prefix: KeyExpr<'static>,
+    /// Host identifier
+    pub id: NodeId,
+    /// Number of clients connected to the host
+    pub players: u32,
+}
+
+/// Change of the roster of an [`ArenaObserver`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RosterChange {
+    /// A node appeared
+    Joined(ObservedNode),
+    /// The role, room or host of a node changed (the node as it is now)
+    Updated(ObservedNode),
+    /// A node left (the node as it was last seen)
+    Left(ObservedNode),
+}
+
+/// Liveliness tokens currently declared by a node
+#[derive(Debug, Default)]
+struct NodeTokens {
+    node: bool,
+    host: bool,
+    matchmaker: bool,
+    /// Room of the client token, if declared
+    client: Option<Option<RoomId>>,
+    /// Host of the connection token, if declared
+    connection: Option<NodeId>,
+}
+
+impl NodeTokens {
+    /// Whether no token of the node is left
+    fn is_empty(&self) -> bool {
+        !self.node && !self.host && !self.matchmaker && self.client.is_none() && self.connection.is_none()
+    }
+
+    /// The node as described by its tokens
+    ///
+    /// Tokens of the previous and next roles may overlap during a transition; the host
+    /// and matchmaker tokens take precedence over the client token.
+    fn observed(&self, prefix: &KeyExpr<'static>, id: &NodeId) -> ObservedNode {
+        let role = if self.matchmaker {
+            ObservedRole::Matchmaker
+        } else if self.host {
+            ObservedRole::Host
+        } else if self.client.is_some() {
+            ObservedRole::Client
+        } else {
+            ObservedRole::SearchingHost
+        };
+        let client = role == ObservedRole::Client;
+        ObservedNode {
+            prefix: prefix.clone(),
+            id: id.clone(),
+            role,
+            room: self.client.clone().flatten().filter(|_| client),
+            host: self.connection.clone().filter(|_| client),
+        }
+    }
+}
+
+/// Liveliness token parsed from a sample key expression
+enum Token {
+    /// Node, host, client or matchmaker token
+    Node(KeyexprNode),
+    /// Connection token of a client (the client and its host)
+    Connection(KeyexprLink),
+}
+
+impl Token {
+    /// Parse a token declared under `prefix`, or under any prefix if None
+    ///
+    /// With a prefix, tokens of nested prefixes do not parse.
+    fn parse(key_expr: &KeyExpr<'static>, prefix: Option<&KeyExpr<'static>>) -> Option<Self> {
+        let link = match prefix {
+            Some(prefix) => KeyexprLink::parse(key_expr, prefix),
+            None => KeyexprLink::try_from(key_expr.clone()),
+        };
+        if let Ok(link) = link {
+            if link.link_type() == LinkType::Connection && link.node_src().is_some() && link.node_dst().is_some() {
+                return Some(Token::Connection(link));
+            }
+        }
+        let node = match prefix {
+            Some(prefix) => KeyexprNode::parse(key_expr, prefix),
+            None => KeyexprNode::try_from(key_expr.clone()),
+        };
+        node.ok()
+            .filter(|node| node.node().is_some())
+            .map(Token::Node)
+    }
+}
+
+/// Observer maintaining a live roster of the nodes of an arena
+///
+/// Reads the liveliness tokens declared by the nodes: every node declares a node token,
+/// hosts, clients and matchmakers a token of their role, and clients advertise the host
+/// they are connected to. The observer declares no token and is invisible to the nodes.
+///
+/// The roster is complete as soon as the observer is created; it is then kept up to date
+/// while changes are read with [`next_change`](ArenaObserver::next_change).
+///
+/// Optionally, the observer also counts the messages published on each type of link (see
+/// [`ArenaObserverBuilder::count_traffic`]); queries of the handshake are not counted.
+pub struct ArenaObserver {
+    /// Network transport, used to probe hosts
+    transport: Arc<dyn Transport>,
+    /// Observed prefix (None for all prefixes)
+    prefix: Option<KeyExpr<'static>>,
+    /// Liveliness subscriber receiving token changes
+    subscriber: TransportReceiver<TransportSample>,
+    /// Tokens of each node, by prefix and id
+    tokens: HashMap<(KeyExpr<'static>, NodeId), NodeTokens>,
+    /// Subscriber receiving the messages published in the arena, if traffic is counted
+    traffic_subscriber: Option<TransportReceiver<TransportSample>>,
+    /// Messages counted per type of link
+    traffic: HashMap<LinkType, LinkTraffic>,
+}
+
+impl ArenaObserver {
+    async fn new_internal(
+        transport: Arc<dyn Transport>,
+        prefix: Option<KeyExpr<'static>>,
+        count_traffic: bool,
+    ) -> Result<Self> {
+        let key_expr = match &prefix {
+            Some(prefix) => KeyExpr::try_from(format!("{}/**", prefix))?.into_owned(),
+            None => KeyExpr::try_from("**")?.into_owned(),
+        };
+
+        // Subscribe first so that no token change is missed between the query and the subscription
+        let subscriber = transport.declare_liveliness_subscriber(key_expr.clone(), true).await?;
+        let traffic_subscriber = match count_traffic {
+            true => Some(transport.declare_subscriber(key_expr.clone()).await?),
+            false => None,
+        };
+        let mut observer = Self {
+            transport,
+            prefix,
+            subscriber,
+            tokens: HashMap::new(),
+            traffic_subscriber,
+            traffic: HashMap::new(),
+        };
+
+        let replies = observer.transport.get_liveliness(key_expr).await?;
+        while let Ok(sample) = replies.recv_async().await {
+            observer.apply(&sample);
+        }
+
+        tracing::info!(
+            "Arena observer initialized with {} node(s)",
+            observer.tokens.len()
+        );
+        Ok(observer)
+    }
+
+    /// Current roster of the observed nodes
+    pub fn roster(&self) -> ArenaRoster {
+        let mut nodes: Vec<ObservedNode> = self
+            .tokens
+            .iter()
+            .map(|((prefix, id), tokens)| tokens.observed(prefix, id))
+            .collect();
+        nodes.sort_by(|a, b| (a.prefix.as_str(), a.id.as_str()).cmp(&(b.prefix.as_str(), b.id.as_str())));
+        ArenaRoster { nodes }
+    }
+
+    /// Messages counted on each type of link, ordered by link type
+    ///
+    /// Empty unless the observer counts traffic.
+    pub fn traffic(&self) -> Vec<LinkTraffic> {
+        let mut traffic: Vec<LinkTraffic> = self.traffic.values().copied().collect();
+        traffic.sort_by_key(|traffic| traffic.link.as_str());
+        traffic
+    }
+
+    /// Ask the hosts running a game how many clients they have
+    ///
+    /// Sends a discovery query with the given game identifier and version to the observed
+    /// prefix, or to the prefixes of the hosts in the roster when observing all prefixes.
+    /// Only hosts running that game and accepting clients answer.
+    pub async fn probe_hosts(&self, game_id: &str, game_version: u32) -> Result<Vec<ProbedHost>> {
+        let mut prefixes: Vec<KeyExpr<'static>> = match &self.prefix {
+            Some(prefix) => vec![prefix.clone()],
+            None => self.roster().hosts().map(|host| host.prefix.clone()).collect(),
+        };
+        prefixes.dedup();
+
+        let info = HandshakeInfo::new(game_id, game_version);
+        let mut hosts = Vec::new();
+        for prefix in prefixes {
+            let discovered =
+                HostQuerier::discover(&*self.transport, prefix.clone(), NodeId::generate(), &info, LinkQos::request())
+                    .await?;
+            hosts.extend(discovered.into_iter().map(|host| ProbedHost {
+                prefix: prefix.clone(),
+                id: host.host_id,
+                players: host.players,
+            }));
+        }
+        Ok(hosts)
+    }
+
+    /// Wait for the next change of the roster
+    ///
+    /// Token changes that leave the roster unchanged are skipped, and messages are
+    /// counted meanwhile if the observer counts traffic. Cancel safe.
+    pub async fn next_change(&mut self) -> Result<RosterChange> {
+        loop {
+            tokio::select! {
+                sample = self.subscriber.recv_async() => {
+                    if let Some(change) = self.apply(&sample?) {
+                        return Ok(change);
+                    }
+                }
+                sample = async {
+                    match &self.traffic_subscriber {
+                        Some(subscriber) => subscriber.recv_async().await,
+                        None => std::future::pending().await,
+                    }
+                } => self.count(&sample?),
+            }
+        }
+    }
+
+    /// Count a message published in the arena
+    fn count(&mut self, sample: &TransportSample) {
+        let link = match &self.prefix {
+            Some(prefix) => KeyexprLink::parse(&sample.key_expr, prefix),
+            None => KeyexprLink::try_from(sample.key_expr.clone()),
+        };
+        let Ok(link) = link else {
+            return;
+        };
+        let traffic = self.traffic.entry(link.link_type()).or_insert(LinkTraffic {
+            link: link.link_type(),
+            messages: 0,
+            bytes: 0,
+        });
+        traffic.messages += 1;
+        traffic.bytes += sample.payload.len() as u64;
+    }
+
+    /// Apply a token change to the roster, returning the change of the roster if any
+    fn apply(&mut self, sample: &TransportSample) -> Option<RosterChange> {
+        let Some(token) = Token::parse(&sample.key_expr, self.prefix.as_ref()) else {
+            tracing::trace!("Arena observer ignoring liveliness token '{}'", sample.key_expr);
+            return None;
+        };
+        let put = sample.kind == SampleKind::Put;
+        let (prefix, id) = match &token {
+            Token::Node(node) => (node.prefix().clone(), node.node().clone().expect("parsed node id")),
+            Token::Connection(link) => (link.prefix().clone(), link.node_src().clone().expect("parsed client id")),
+        };
+        let key = (prefix, id);
+        let before = self.tokens.get(&key).map(|tokens| tokens.observed(&key.0, &key.1));
+        let tokens = self.tokens.entry(key.clone()).or_default();
+        match token {
+            Token::Node(node) => match node.node_type() {
+                NodeType::Node => tokens.node = put,
+                NodeType::Host => tokens.host = put,
+                NodeType::Matchmaker => tokens.matchmaker = put,
+                NodeType::Client => tokens.client = put.then(|| node.room().clone()),
+            },
+            Token::Connection(link) => tokens.connection = put.then(|| link.node_dst().clone()).flatten(),
+        }
+
+        if tokens.is_empty() {
+            self.tokens.remove(&key);
+            return before.map(RosterChange::Left);
+        }
+        let after = tokens.observed(&key.0, &key.1);
+        match before {
+            None => Some(RosterChange::Joined(after)),
+            Some(before) if before != after => Some(RosterChange::Updated(after)),
+            Some(_) => None,
+        }
+    }
+}
+
+/// Builder for arena observers
+#[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
+pub struct ArenaObserverBuilder<'a> {
+    transport: Arc<dyn Transport>,
+    prefix: Option<KeyExpr<'static>>,
+    count_traffic: bool,
+    _phantom: std::marker::PhantomData<&'a ()>,
+}
+
+impl<'a> ArenaObserverBuilder<'a> {
+    /// Create a new ArenaObserverBuilder observing the default prefix
+    pub(crate) fn new(transport: Arc<dyn Transport>) -> Self {
+        Self {
+            transport,
+            prefix: Some(NodeConfig::default().keyexpr_prefix),
+            count_traffic: false,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Set the key expression prefix of the observed arena
+    pub fn prefix(mut self, prefix: KeyExpr<'static>) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    /// Observe the arenas of all prefixes
+    pub fn all_prefixes(mut self) -> Self {
+        self.prefix = None;
+        self
+    }
+
+    /// Count the messages published on each type of link
+    ///
+    /// The observer then receives every message published in the observed arena.
+    pub fn count_traffic(mut self, count_traffic: bool) -> Self {
+        self.count_traffic = count_traffic;
+        self
+    }
+}
+
+impl<'a> Resolvable for ArenaObserverBuilder<'a> {
+    type To = Result<ArenaObserver>;
+}
+
+impl<'a> std::future::IntoFuture for ArenaObserverBuilder<'a> {
+    type Output = <Self as Resolvable>::To;
+    type IntoFuture =
+        std::pin::Pin<Box<dyn std::future::Future<Output = Self::Output> + Send + 'a>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(async move { ArenaObserver::new_internal(self.transport, self.prefix, self.count_traffic).await })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn observer_with(prefix: Option<&str>) -> (crate::MemoryNetwork, ArenaObserver) {
+        let network = crate::MemoryNetwork::new();
+        let transport = network.open();
+        let subscriber = transport
+            .declare_liveliness_subscriber(KeyExpr::try_from("**").unwrap().into_owned(), false)
+            .await
+            .unwrap();
+        let observer = ArenaObserver {
+            transport: Arc::new(transport),
+            prefix: prefix.map(|p| KeyExpr::try_from(p).unwrap().into_owned()),
+            subscriber,
+            tokens: HashMap::new(),
+            traffic_subscriber: None,
+            traffic: HashMap::new(),
+        };
+        (network, observer)
+    }
+
+    fn put(key: &str) -> TransportSample {
+        TransportSample::put(KeyExpr::try_from(key).unwrap().into_owned(), Default::default())
+    }
+
+    fn delete(key: &str) -> TransportSample {
+        TransportSample::delete(KeyExpr::try_from(key).unwrap().into_owned())
+    }
+
+    #[tokio::test]
+    async fn test_roster_from_tokens() {
+        let (_network, mut observer) = observer_with(Some("arena")).await;
+
+        assert!(matches!(observer.apply(&put("arena/node/alpha")), Some(RosterChange::Joined(ref n)) if n.role == ObservedRole::SearchingHost));
+        assert!(matches!(observer.apply(&put("arena/host/alpha")), Some(RosterChange::Updated(ref n)) if n.role == ObservedRole::Host));
+        observer.apply(&put("arena/node/bravo"));
+        assert!(observer.apply(&put("arena/room/lobby/connection/bravo/alpha")).is_none());
+        let change = observer.apply(&put("arena/room/lobby/client/bravo"));
+        assert!(matches!(
+            change,
+            Some(RosterChange::Updated(ref n))
+                if n.role == ObservedRole::Client && n.room.as_ref().map(RoomId::as_str) == Some("lobby")
+                    && n.host.as_ref().map(NodeId::as_str) == Some("alpha")
+        ));
+
+        // Tokens of other prefixes and other applications are ignored
+        assert!(observer.apply(&put("arena/sub/node/charlie")).is_none());
+        assert!(observer.apply(&put("arena/something/else")).is_none());
+
+        let roster = observer.roster();
+        assert_eq!(roster.nodes.len(), 2);
+        let host = roster.hosts().next().unwrap();
+        assert_eq!(host.id.as_str(), "alpha");
+        assert_eq!(roster.clients_of(host).map(|n| n.id.as_str()).collect::<Vec<_>>(), vec!["bravo"]);
+
+        // A client leaving its host goes back to searching, then leaves the arena
+        assert!(matches!(observer.apply(&delete("arena/room/lobby/client/bravo")), Some(RosterChange::Updated(ref n)) if n.role == ObservedRole::SearchingHost && n.host.is_none()));
+        assert!(observer.apply(&delete("arena/room/lobby/connection/bravo/alpha")).is_none());
+        assert!(matches!(observer.apply(&delete("arena/node/bravo")), Some(RosterChange::Left(ref n)) if n.id.as_str() == "bravo"));
+        assert_eq!(observer.roster().nodes.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_roster_all_prefixes() {
+        let (_network, mut observer) = observer_with(None).await;
+        observer.apply(&put("arena/node/alpha"));
+        observer.apply(&put("arena/sub/node/alpha"));
+        observer.apply(&put("other/matchmaker/mm"));
+        let roster = observer.roster();
+        assert_eq!(
+            roster
+                .nodes
+                .iter()
+                .map(|n| (n.prefix.as_str(), n.id.as_str(), n.role))
+                .collect::<Vec<_>>(),
+            vec![
+                ("arena", "alpha", ObservedRole::SearchingHost),
+                ("arena/sub", "alpha", ObservedRole::SearchingHost),
+                ("other", "mm", ObservedRole::Matchmaker),
+            ]
+        );
+    }
+}
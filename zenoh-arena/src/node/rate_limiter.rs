@@ -0,0 +1,75 @@
+/// Token bucket limiting the action rate of a client
+use tokio::time::Instant;
+
+/// Token bucket refilled at `rate` tokens per second, holding at most `1 + burst` tokens
+///
+/// Starts full, so that a client may send `1 + burst` actions at once.
+pub(crate) struct RateLimiter {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(rate: u32, burst: u32) -> Self {
+        let capacity = 1.0 + f64::from(burst);
+        Self {
+            rate: f64::from(rate),
+            capacity,
+            tokens: capacity,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    /// Take a token for an action
+    ///
+    /// Returns false if the bucket is empty and the action exceeds the rate.
+    pub(crate) fn try_acquire(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_burst_and_refill() {
+        let mut limiter = RateLimiter::new(10, 2);
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        // One token per 100ms at 10 actions per second
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(limiter.try_acquire());
+        assert!(!limiter.try_acquire());
+
+        // The bucket never holds more than 1 + burst tokens
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        for _ in 0..3 {
+            assert!(limiter.try_acquire());
+        }
+        assert!(!limiter.try_acquire());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limiter_zero_rate() {
+        let mut limiter = RateLimiter::new(0, 0);
+        assert!(limiter.try_acquire());
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert!(!limiter.try_acquire());
+    }
+}
@@ -2,56 +2,279 @@
 use super::config::NodeConfig;
 use crate::{NodeRole, StepResult};
 use crate::error::Result;
-use crate::network::HostQuerier;
+use crate::codec::ZenohCodec;
+use crate::network::keyexpr::LinkType;
+use crate::network::{
+    HostConnection, HostQuerier, MatchAssignment, MatchTicket, NodePublisher, NodeSubscriber, Transport,
+};
 use super::game_engine::{EngineFactory, GameEngine};
+use super::action_buffer::ActionBuffer;
 use super::arena_node::NodeCommand;
 use super::types::{NodeId, NodeStateInternal};
 use rand::Rng;
 
+/// Delay between connection attempts to a host assigned by the matchmaker or handed off to
+const MATCH_CONNECT_RETRY_MS: u64 = 100;
+
+/// Delay before the first retry of a search that may not end in hosting
+const SEARCH_BACKOFF_MIN_MS: u64 = 500;
+
+/// Maximum delay between retries of a search that may not end in hosting
+const SEARCH_BACKOFF_MAX_MS: u64 = 10_000;
+
 /// State while searching for available hosts
 pub(crate) struct SearchingHostState<E: GameEngine> {
-    // Empty state - game_state is passed through step() method
+    /// Queue registration with the matchmaker, kept across steps (matchmaking mode only)
+    pub(crate) matchmaking: Option<MatchmakingSession>,
+    /// Assignment received from the matchmaker, kept across steps until the node joins its game
+    assignment: Option<PendingAssignment>,
+    /// Whether a search that finds no host makes this node host (unless client-only)
+    pub(crate) auto_host: bool,
+    /// Delay before the next search, doubled after each failed search without hosting
+    retry_delay_ms: u64,
+    /// Time of the next search after a failed one
+    retry_at: Option<tokio::time::Instant>,
+    /// Time the jitter before the current search ends, kept across cancelled steps
+    search_start: Option<tokio::time::Instant>,
+    /// Time the current search gives up, kept across cancelled steps (None between searches)
+    search_deadline: Option<tokio::time::Instant>,
+    /// Host skipped by the next search, e.g. the host just left
+    excluded_host: Option<NodeId>,
+    /// Host handed off to by the previous host, joined directly before searching
+    handed_off_to: Option<HandOffTarget>,
+    // game_state is passed through step() method
     pub(crate) _phantom: std::marker::PhantomData<E>,
 }
 
+/// Role command received while searching
+enum SearchCommand {
+    Stop,
+    BecomeHost,
+    SearchAgain,
+}
+
+/// Outcome of connecting to a known host for one step
+enum KnownHostOutcome {
+    /// The host accepted the node
+    Connected(HostConnection),
+    /// The host could not be reached before the deadline
+    Unreachable,
+    /// The step timeout elapsed first, the next step connects again
+    StepTimeout,
+    /// A command ending the step was received
+    Command(SearchCommand),
+}
+
+/// Links with the matchmaker while waiting in its queue
+pub(crate) struct MatchmakingSession {
+    /// Publishes the ticket to any matchmaker: <prefix>/ticket/<node_id>/*
+    ticket_publisher: NodePublisher<MatchTicket, ZenohCodec>,
+    /// Receives assignments: <prefix>/match/*/<node_id>
+    assignment_subscriber: NodeSubscriber<MatchAssignment, ZenohCodec>,
+    /// Time the ticket was last published
+    last_published: Option<tokio::time::Instant>,
+}
+
+/// Assignment being joined, with the time the node gives up connecting to its host
+struct PendingAssignment {
+    assignment: MatchAssignment,
+    deadline: tokio::time::Instant,
+}
+
+/// Host handed off to, with the time the node gives up connecting to it
+struct HandOffTarget {
+    host: NodeId,
+    deadline: tokio::time::Instant,
+}
+
+impl MatchmakingSession {
+    async fn open(transport: &dyn Transport, config: &NodeConfig, node_id: &NodeId) -> Result<Self> {
+        let ticket_publisher = NodePublisher::new(
+            transport,
+            config.keyexpr_prefix.clone(),
+            LinkType::Ticket,
+            node_id,
+            None,
+            None,
+            config.handshake_qos,
+        )
+        .await?;
+        let assignment_subscriber = NodeSubscriber::new(
+            transport,
+            config.keyexpr_prefix.clone(),
+            LinkType::Match,
+            node_id,
+            None,
+        )
+        .await?;
+        Ok(Self {
+            ticket_publisher,
+            assignment_subscriber,
+            last_published: None,
+        })
+    }
+}
+
 impl<E: GameEngine> SearchingHostState<E> {
+    /// Create the state; `auto_host` allows hosting when no host is found
+    pub(crate) fn new(auto_host: bool) -> Self {
+        Self {
+            matchmaking: None,
+            assignment: None,
+            auto_host,
+            retry_delay_ms: 0,
+            retry_at: None,
+            search_start: None,
+            search_deadline: None,
+            excluded_host: None,
+            handed_off_to: None,
+            _phantom: std::marker::PhantomData,
+        }
+    }
+
+    /// Skip `host` in the next search
+    pub(crate) fn excluding(mut self, host: Option<NodeId>) -> Self {
+        self.excluded_host = host;
+        self
+    }
+
+    /// Join `host` directly, until `deadline`, before searching
+    pub(crate) fn handed_off_to(mut self, host: NodeId, deadline: tokio::time::Instant) -> Self {
+        self.handed_off_to = Some(HandOffTarget { host, deadline });
+        self
+    }
+
     /// Process the SearchingHost state - search for available hosts and attempt to connect
     ///
-    /// Consumes self and returns the next state.
+    /// Returns the next state if the node leaves the SearchingHost state.
     /// Uses HostQuerier to find and connect to available hosts. If timeout expires or
     /// no hosts are available/accept connection, transitions to Host state.
+    /// In client-only mode, or after leaving a game, the search is retried with
+    /// exponential backoff instead. Role commands end the search early.
+    /// Actions issued while searching are kept in `action_buffer` for the next host.
+    /// A dropped step leaves the search in progress: the next call resumes its jitter and
+    /// timeout instead of starting over.
+    /// A node handed off to another host connects to it directly, and searches only if
+    /// that host cannot be reached within the search timeout.
+    /// Role commands and the step timeout are handled while connecting.
+    #[allow(clippy::too_many_arguments)]
     pub(crate) async fn step<F>(
-        self,
-        session: &zenoh::Session,
+        &mut self,
+        transport: &dyn Transport,
         config: &NodeConfig,
         node_id: &NodeId,
         command_rx: &flume::Receiver<NodeCommand<E::Action>>,
+        action_buffer: &mut ActionBuffer<E::Action>,
         get_engine: &F,
         game_state: Option<E::State>,
-    ) -> Result<(NodeStateInternal<E>, StepResult<E::State>)>
+    ) -> Result<(Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)>
     where
         F: EngineFactory<E>,
     {
-        tracing::info!("Node '{}' searching for hosts...", node_id);
-
-        // Add randomized jitter to prevent thundering herd when multiple clients
-        // lose their host simultaneously
-        if config.search_jitter_ms > 0 {
-            let jitter_ms = rand::rng().random_range(0..config.search_jitter_ms);
-            tracing::debug!(
-                "Node '{}' waiting {}ms jitter before searching",
-                node_id,
-                jitter_ms
-            );
-            tokio::time::sleep(tokio::time::Duration::from_millis(jitter_ms)).await;
+        if let Some(ticket) = &config.matchmaking {
+            return self
+                .step_matchmaking(transport, config, node_id, command_rx, action_buffer, get_engine, game_state, ticket)
+                .await;
         }
 
-        let search_timeout = tokio::time::Duration::from_millis(config.search_timeout_ms);
-        let sleep = tokio::time::sleep(search_timeout);
+        if let Some(target) = &self.handed_off_to {
+            let host_id = target.host.clone();
+            let deadline = target.deadline;
+            match self
+                .connect_until(transport, config, node_id, command_rx, action_buffer, &host_id, deadline)
+                .await
+            {
+                KnownHostOutcome::Connected(connection) => {
+                    self.handed_off_to = None;
+                    let next_state = NodeStateInternal::client(transport, config, connection, node_id.clone()).await?;
+                    return Ok((Some(next_state), StepResult::RoleChanged(NodeRole::Client)));
+                }
+                KnownHostOutcome::StepTimeout => return Ok((None, StepResult::Timeout)),
+                KnownHostOutcome::Command(command) => {
+                    self.handed_off_to = None;
+                    return Self::run_command(command, transport, config, node_id, get_engine, game_state).await;
+                }
+                KnownHostOutcome::Unreachable => {
+                    self.handed_off_to = None;
+                    tracing::info!(
+                        "Node '{}' could not connect to host '{}' handed off to, searching",
+                        node_id,
+                        host_id
+                    );
+                }
+            }
+        }
+
+        // Wait before searching again after a failed search that did not end in hosting
+        if let Some(retry_at) = self.retry_at {
+            let step_deadline = tokio::time::Instant::now()
+                + tokio::time::Duration::from_millis(config.step_timeout_break_ms);
+            let wait_until = retry_at.min(step_deadline);
+            loop {
+                tokio::select! {
+                    () = tokio::time::sleep_until(wait_until) => break,
+                    result = command_rx.recv_async() => {
+                        if let Some(command) = self.on_command(node_id, action_buffer, result) {
+                            return Self::run_command(command, transport, config, node_id, get_engine, game_state).await;
+                        }
+                    }
+                }
+            }
+            if wait_until < retry_at {
+                return Ok((None, StepResult::Timeout));
+            }
+            self.retry_at = None;
+        }
+
+        // Start a new search, unless a cancelled step left one in progress
+        let search_deadline = match self.search_deadline {
+            Some(search_deadline) => search_deadline,
+            None => {
+                tracing::info!("Node '{}' searching for hosts...", node_id);
+
+                // Add randomized jitter to prevent thundering herd when multiple clients
+                // lose their host simultaneously
+                let mut search_start = tokio::time::Instant::now();
+                if config.search_jitter_ms > 0 {
+                    let jitter_ms = rand::rng().random_range(0..config.search_jitter_ms);
+                    tracing::debug!(
+                        "Node '{}' waiting {}ms jitter before searching",
+                        node_id,
+                        jitter_ms
+                    );
+                    search_start += tokio::time::Duration::from_millis(jitter_ms);
+                }
+                let search_deadline =
+                    search_start + tokio::time::Duration::from_millis(config.search_timeout_ms);
+                self.search_start = Some(search_start);
+                self.search_deadline = Some(search_deadline);
+                search_deadline
+            }
+        };
+        if let Some(search_start) = self.search_start {
+            tokio::time::sleep_until(search_start).await;
+            self.search_start = None;
+        }
+
+        let handshake_info = E::handshake_info().with_room(config.room.clone());
+
+        let sleep = tokio::time::sleep_until(search_deadline);
         tokio::pin!(sleep);
 
+        // Created once, so that commands handled while searching do not restart the query
+        let excluded_host = self.excluded_host.clone();
+        let connect = HostQuerier::connect(
+            transport,
+            config.keyexpr_prefix.clone(),
+            node_id.clone(),
+            &handshake_info,
+            config.handshake_qos,
+            excluded_host.as_ref(),
+        );
+        tokio::pin!(connect);
+
         // Wait for connection success or timeout
-        // Returns None if should become host, Some(host_id) if connected
+        // Returns None if should become host, Some(connection) if connected
         let connected_host = loop {
             tokio::select! {
                 // Search timeout elapsed - no successful connection, become host
@@ -63,12 +286,12 @@ impl<E: GameEngine> SearchingHostState<E> {
                     break None;
                 }
                 // Try to connect to available hosts
-                connection_result = HostQuerier::connect(session, config.keyexpr_prefix.clone(), node_id.clone()) => {
+                connection_result = &mut connect => {
                     match connection_result {
-                        Ok(Some(host_id)) => {
+                        Ok(Some(connection)) => {
                             // Successfully connected to a host
-                            tracing::info!("Node '{}' connected to host: {}", node_id, host_id);
-                            break Some(host_id);
+                            tracing::info!("Node '{}' connected to host: {}", node_id, connection.host_id);
+                            break Some(connection);
                         }
                         Ok(None) => {
                             // No hosts available, become host
@@ -77,65 +300,322 @@ impl<E: GameEngine> SearchingHostState<E> {
                         }
                         Err(e) => {
                             tracing::warn!("Node '{}' query error during search: {}", node_id, e);
+                            self.search_deadline = None;
                             return Err(e);
                         }
                     }
                 }
-                // Check for Stop command while searching
-                result = command_rx.recv_async() => match result {
-                    Err(_) => {
-                        tracing::info!("Node '{}' command channel closed during search", node_id);
-                        return Ok((
-                            NodeStateInternal::Stop,
-                            StepResult::Stop,
-                        ));
-                    }
-                    Ok(NodeCommand::Stop) => {
-                        tracing::info!("Node '{}' received Stop command during search, exiting", node_id);
-                        return Ok((
-                            NodeStateInternal::Stop,
-                            StepResult::Stop,
-                        ));
-                    }
-                    Ok(NodeCommand::GameAction(_)) => {
-                        tracing::warn!(
-                            "Node '{}' received action while searching for host, ignoring",
-                            node_id
-                        );
-                        // Continue searching
+                // Check for role commands while searching
+                result = command_rx.recv_async() => {
+                    if let Some(command) = self.on_command(node_id, action_buffer, result) {
+                        return Self::run_command(command, transport, config, node_id, get_engine, game_state).await;
                     }
                 }
             }
         };
+        // The search is over, the next one starts afresh
+        self.search_deadline = None;
+        self.excluded_host = None;
 
         // Handle connection result - state transition after select!
-        if let Some(host_id) = connected_host {
+        if let Some(connection) = connected_host {
             // Transition to Client state
             let next_state = NodeStateInternal::client(
-                session,
-                config.keyexpr_prefix.clone(),
-                host_id,
+                transport,
+                config,
+                connection,
                 node_id.clone(),
             )
             .await?;
             Ok((
-                next_state,
+                Some(next_state),
                 StepResult::RoleChanged(NodeRole::Client)
             ))
+        } else if !self.auto_host || config.client_only {
+            // Not allowed to host on its own, search again after a growing delay
+            self.retry_delay_ms = (self.retry_delay_ms * 2).clamp(SEARCH_BACKOFF_MIN_MS, SEARCH_BACKOFF_MAX_MS);
+            self.retry_at = Some(
+                tokio::time::Instant::now() + tokio::time::Duration::from_millis(self.retry_delay_ms),
+            );
+            tracing::info!(
+                "Node '{}' not hosting, searching again in {}ms",
+                node_id,
+                self.retry_delay_ms
+            );
+            Ok((None, StepResult::Timeout))
         } else {
             // Transition to Host state with the preserved initial state or game state from Node
-            let next_state = NodeStateInternal::host(
-                get_engine,
-                session,
-                config.keyexpr_prefix.clone(),
-                node_id,
-                game_state,
-            )
-            .await?;
-            Ok((
-                next_state,
-                StepResult::RoleChanged(NodeRole::Host)
-            ))
+            Self::become_host(transport, config, node_id, get_engine, game_state).await
+        }
+    }
+
+    /// Interpret a command received while searching
+    ///
+    /// Returns the command to run if it ends the current step.
+    fn on_command(
+        &mut self,
+        node_id: &NodeId,
+        action_buffer: &mut ActionBuffer<E::Action>,
+        result: std::result::Result<NodeCommand<E::Action>, flume::RecvError>,
+    ) -> Option<SearchCommand> {
+        match result {
+            Err(_) => {
+                tracing::info!("Node '{}' command channel closed during search", node_id);
+                Some(SearchCommand::Stop)
+            }
+            Ok(NodeCommand::Stop) => {
+                tracing::info!("Node '{}' received Stop command during search, exiting", node_id);
+                Some(SearchCommand::Stop)
+            }
+            Ok(NodeCommand::GameAction(action)) => {
+                if action_buffer.push(action) {
+                    tracing::debug!("Node '{}' buffering action until connected", node_id);
+                } else {
+                    tracing::warn!(
+                        "Node '{}' received action while searching for host, ignoring",
+                        node_id
+                    );
+                }
+                None
+            }
+            Ok(NodeCommand::Chat { .. }) => {
+                tracing::warn!("Node '{}' received chat message while searching for host, ignoring", node_id);
+                None
+            }
+            Ok(NodeCommand::Leave) => {
+                tracing::info!("Node '{}' will not become host on its own", node_id);
+                self.auto_host = false;
+                None
+            }
+            Ok(NodeCommand::BecomeHost) => Some(SearchCommand::BecomeHost),
+            Ok(NodeCommand::SearchAgain) => Some(SearchCommand::SearchAgain),
+        }
+    }
+
+    /// Run a command ending the step
+    async fn run_command<F>(
+        command: SearchCommand,
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        get_engine: &F,
+        game_state: Option<E::State>,
+    ) -> Result<(Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)>
+    where
+        F: EngineFactory<E>,
+    {
+        match command {
+            SearchCommand::Stop => Ok((Some(NodeStateInternal::Stop), StepResult::Stop)),
+            SearchCommand::BecomeHost => {
+                tracing::info!("Node '{}' becoming host on request", node_id);
+                Self::become_host(transport, config, node_id, get_engine, game_state).await
+            }
+            SearchCommand::SearchAgain => {
+                tracing::info!("Node '{}' restarting search on request", node_id);
+                Ok((
+                    Some(NodeStateInternal::searching()),
+                    StepResult::RoleChanged(NodeRole::SearchingHost),
+                ))
+            }
+        }
+    }
+
+    /// Transition to Host state
+    async fn become_host<F>(
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        get_engine: &F,
+        game_state: Option<E::State>,
+    ) -> Result<(Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)>
+    where
+        F: EngineFactory<E>,
+    {
+        let next_state = NodeStateInternal::host(
+            get_engine,
+            transport,
+            config,
+            node_id,
+            game_state,
+        )
+        .await?;
+        Ok((Some(next_state), StepResult::RoleChanged(NodeRole::Host)))
+    }
+
+    /// Wait in the matchmaker queue for one step
+    ///
+    /// Publishes the ticket (again every search timeout, so that a restarted matchmaker
+    /// learns about the node) and waits for an assignment until the step timeout elapses.
+    /// The assigned host transitions to Host state, other members connect to it. If the
+    /// assigned host cannot be reached within the search timeout, the node queues again.
+    /// The assignment is kept across dropped steps until its game is joined.
+    #[allow(clippy::too_many_arguments)]
+    async fn step_matchmaking<F>(
+        &mut self,
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        command_rx: &flume::Receiver<NodeCommand<E::Action>>,
+        action_buffer: &mut ActionBuffer<E::Action>,
+        get_engine: &F,
+        game_state: Option<E::State>,
+        ticket: &MatchTicket,
+    ) -> Result<(Option<NodeStateInternal<E>>, StepResult<E::State, E::Event>)>
+    where
+        F: EngineFactory<E>,
+    {
+        let search_timeout = tokio::time::Duration::from_millis(config.search_timeout_ms);
+        if self.assignment.is_none() {
+            if self.matchmaking.is_none() {
+                tracing::info!("Node '{}' joining matchmaker queue", node_id);
+                self.matchmaking = Some(MatchmakingSession::open(transport, config, node_id).await?);
+            }
+            let session = self.matchmaking.as_mut().expect("matchmaking session opened");
+
+            if session
+                .last_published
+                .is_none_or(|published| published.elapsed() >= search_timeout)
+            {
+                session.ticket_publisher.put(ticket).await?;
+                session.last_published = Some(tokio::time::Instant::now());
+            }
+
+            let timeout = tokio::time::Duration::from_millis(config.step_timeout_break_ms);
+            let sleep = tokio::time::sleep(timeout);
+            tokio::pin!(sleep);
+
+            let assignment = loop {
+                tokio::select! {
+                    () = &mut sleep => {
+                        return Ok((None, StepResult::Timeout));
+                    }
+                    assignment_result = self.matchmaking.as_ref().expect("matchmaking session opened").assignment_subscriber.recv() => match assignment_result {
+                        Ok((matchmaker_id, assignment)) if assignment.members.contains(node_id) => {
+                            tracing::info!(
+                                "Node '{}' matched by '{}', host: {}",
+                                node_id,
+                                matchmaker_id,
+                                assignment.host
+                            );
+                            break assignment;
+                        }
+                        Ok((matchmaker_id, _)) => {
+                            tracing::warn!(
+                                "Node '{}' ignoring assignment from '{}' not listing it",
+                                node_id,
+                                matchmaker_id
+                            );
+                        }
+                        Err(e) => {
+                            tracing::warn!("Node '{}' failed to receive assignment: {}", node_id, e);
+                        }
+                    },
+                    result = command_rx.recv_async() => {
+                        if let Some(command) = self.on_command(node_id, action_buffer, result) {
+                            return Self::run_command(command, transport, config, node_id, get_engine, game_state).await;
+                        }
+                    }
+                }
+            };
+            // Leave the queue, keeping the assignment until its game is joined
+            self.matchmaking = None;
+            self.assignment = Some(PendingAssignment {
+                assignment,
+                deadline: tokio::time::Instant::now() + search_timeout,
+            });
+        }
+        let pending = self.assignment.as_ref().expect("assignment received");
+        let host_id = pending.assignment.host.clone();
+        let deadline = pending.deadline;
+
+        if host_id == *node_id {
+            return Self::become_host(transport, config, node_id, get_engine, game_state).await;
+        }
+
+        // The assigned host may still be starting up, retry until the search timeout
+        match self
+            .connect_until(transport, config, node_id, command_rx, action_buffer, &host_id, deadline)
+            .await
+        {
+            KnownHostOutcome::Connected(connection) => {
+                let next_state = NodeStateInternal::client(
+                    transport,
+                    config,
+                    connection,
+                    node_id.clone(),
+                )
+                .await?;
+                return Ok((Some(next_state), StepResult::RoleChanged(NodeRole::Client)));
+            }
+            KnownHostOutcome::StepTimeout => return Ok((None, StepResult::Timeout)),
+            KnownHostOutcome::Command(command) => {
+                return Self::run_command(command, transport, config, node_id, get_engine, game_state).await;
+            }
+            KnownHostOutcome::Unreachable => {}
+        }
+        self.assignment = None;
+
+        tracing::info!(
+            "Node '{}' could not connect to assigned host '{}', queueing again",
+            node_id,
+            host_id
+        );
+        Ok((None, StepResult::Timeout))
+    }
+
+    /// Connect to a known host, retrying until `deadline`
+    ///
+    /// Gives up at the step timeout, and handles commands between and during attempts.
+    #[allow(clippy::too_many_arguments)]
+    async fn connect_until(
+        &mut self,
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        node_id: &NodeId,
+        command_rx: &flume::Receiver<NodeCommand<E::Action>>,
+        action_buffer: &mut ActionBuffer<E::Action>,
+        host_id: &NodeId,
+        deadline: tokio::time::Instant,
+    ) -> KnownHostOutcome {
+        let handshake_info = E::handshake_info().with_room(config.room.clone());
+        let step_deadline =
+            tokio::time::Instant::now() + tokio::time::Duration::from_millis(config.step_timeout_break_ms);
+        loop {
+            // One attempt, followed by the delay before the next one if it failed in time
+            let attempt = async {
+                let connection = HostQuerier::connect_to(
+                    transport,
+                    config.keyexpr_prefix.clone(),
+                    node_id.clone(),
+                    host_id.clone(),
+                    &handshake_info,
+                    config.handshake_qos,
+                )
+                .await;
+                match connection {
+                    Some(connection) => Some(KnownHostOutcome::Connected(connection)),
+                    None if tokio::time::Instant::now() >= deadline => Some(KnownHostOutcome::Unreachable),
+                    None => {
+                        tokio::time::sleep(tokio::time::Duration::from_millis(MATCH_CONNECT_RETRY_MS)).await;
+                        None
+                    }
+                }
+            };
+            tokio::pin!(attempt);
+            loop {
+                tokio::select! {
+                    outcome = &mut attempt => match outcome {
+                        Some(outcome) => return outcome,
+                        None => break,
+                    },
+                    () = tokio::time::sleep_until(step_deadline) => return KnownHostOutcome::StepTimeout,
+                    result = command_rx.recv_async() => {
+                        if let Some(command) = self.on_command(node_id, action_buffer, result) {
+                            return KnownHostOutcome::Command(command);
+                        }
+                    }
+                }
+            }
         }
     }
 }
@@ -1,22 +1,33 @@
+use std::sync::Arc;
+
 use zenoh::{Resolvable, key_expr::KeyExpr};
-use crate::error::Result;
+use crate::error::{ArenaError, Result};
+use crate::network::{LinkQos, MatchTicket, MemoryTransport, Transport};
+#[cfg(feature = "shared-memory")]
+use crate::network::ShmPool;
+#[cfg(feature = "shared-memory")]
+use crate::node::types::SharedMemory;
 
-use crate::node::{config::NodeConfig, game_engine::{EngineFactory, GameEngine}, arena_node::Node, types::NodeId};
+use crate::node::matchmaker::{MatchRules, MatchmakerBuilder};
+use crate::node::observer::ArenaObserverBuilder;
+use crate::node::{config::NodeConfig, game_engine::{EngineFactory, GameEngine}, arena_node::Node, types::{ActionBuffering, ActionLimits, EngineQueues, MergePolicy, NodeId, RestartPolicy, RoomId, StateRates}};
 
 /// Extension trait for zenoh::Session to declare arena nodes
 /// Extension trait for zenoh::Session to add arena node declaration
+///
+/// Also implemented for [`MemoryTransport`] so that nodes can run on a simulated network.
 pub trait SessionExt {
     /// Declare an arena node
     ///
     /// # Example
     /// ```no_run
-    /// use zenoh_arena::{SessionExt, GameEngine, NodeId};
+    /// use zenoh_arena::{SessionExt, GameEngine, NodeId, PlayerSlot, ZenohCodec};
     ///
     /// # struct MyEngine;
     /// # impl MyEngine {
     /// #     fn new(
     /// #         _host_id: NodeId,
-    /// #         input_rx: flume::Receiver<(NodeId, String)>,
+    /// #         input_rx: flume::Receiver<(NodeId, PlayerSlot, String)>,
     /// #         output_tx: flume::Sender<String>,
     /// #         _initial_state: Option<String>,
     /// #     ) -> Self {
@@ -26,6 +37,8 @@ pub trait SessionExt {
     /// # impl GameEngine for MyEngine {
     /// #     type Action = String;
     /// #     type State = String;
+    /// #     type Event = ();
+    /// #     type Codec = ZenohCodec;
     /// #     fn max_clients(&self) -> Option<usize> { None }
     /// # }
     /// # async fn example() {
@@ -40,6 +53,17 @@ pub trait SessionExt {
     where
         E: GameEngine,
         F: EngineFactory<E>;
+
+    /// Declare a matchmaker node forming matches with the given rules
+    ///
+    /// Nodes join its queue with [`NodeBuilder::matchmaking`].
+    fn declare_matchmaker<R: MatchRules>(&self, rules: R) -> MatchmakerBuilder<'_>;
+
+    /// Declare an observer listing the nodes, hosts and clients of an arena
+    ///
+    /// Observes the default prefix unless configured with
+    /// [`ArenaObserverBuilder::prefix`] or [`ArenaObserverBuilder::all_prefixes`].
+    fn declare_arena_observer(&self) -> ArenaObserverBuilder<'_>;
 }
 
 impl SessionExt for zenoh::Session {
@@ -48,7 +72,33 @@ impl SessionExt for zenoh::Session {
         E: GameEngine,
         F: EngineFactory<E>,
     {
-        NodeBuilder::new(self, get_engine)
+        NodeBuilder::new(Arc::new(self.clone()), get_engine)
+    }
+
+    fn declare_matchmaker<R: MatchRules>(&self, rules: R) -> MatchmakerBuilder<'_> {
+        MatchmakerBuilder::new(Arc::new(self.clone()), Box::new(rules))
+    }
+
+    fn declare_arena_observer(&self) -> ArenaObserverBuilder<'_> {
+        ArenaObserverBuilder::new(Arc::new(self.clone()))
+    }
+}
+
+impl SessionExt for MemoryTransport {
+    fn declare_arena_node<E, F>(&self, get_engine: F) -> NodeBuilder<'_, E, F>
+    where
+        E: GameEngine,
+        F: EngineFactory<E>,
+    {
+        NodeBuilder::new(Arc::new(self.clone()), get_engine)
+    }
+
+    fn declare_matchmaker<R: MatchRules>(&self, rules: R) -> MatchmakerBuilder<'_> {
+        MatchmakerBuilder::new(Arc::new(self.clone()), Box::new(rules))
+    }
+
+    fn declare_arena_observer(&self) -> ArenaObserverBuilder<'_> {
+        ArenaObserverBuilder::new(Arc::new(self.clone()))
     }
 }
 
@@ -57,17 +107,17 @@ impl SessionExt for zenoh::Session {
 /// Allows configuring the node before creating it, similar to zenoh's builder pattern.
 #[must_use = "Resolvables do nothing unless you resolve them using `.await` or `zenoh::Wait::wait`"]
 pub struct NodeBuilder<'a, E: GameEngine, F: EngineFactory<E>> {
-    session: &'a zenoh::Session,
+    transport: Arc<dyn Transport>,
     get_engine: F,
     config: NodeConfig,
-    _phantom: std::marker::PhantomData<E>,
+    _phantom: std::marker::PhantomData<(&'a (), E)>,
 }
 
 impl<'a, E: GameEngine, F: EngineFactory<E>> NodeBuilder<'a, E, F> {
     /// Create a new NodeBuilder
-    fn new(session: &'a zenoh::Session, get_engine: F) -> Self {
+    fn new(transport: Arc<dyn Transport>, get_engine: F) -> Self {
         Self {
-            session,
+            transport,
             get_engine,
             config: NodeConfig::default(),
             _phantom: std::marker::PhantomData,
@@ -86,6 +136,14 @@ impl<'a, E: GameEngine, F: EngineFactory<E>> NodeBuilder<'a, E, F> {
         self
     }
 
+    /// Enable client-only mode
+    /// The node never becomes host on its own: a search that finds no host is retried
+    /// with exponential backoff. `NodeCommand::BecomeHost` still makes it host.
+    pub fn client_only(mut self, client_only: bool) -> Self {
+        self.config.client_only = client_only;
+        self
+    }
+
     /// Set the step timeout in milliseconds
     pub fn step_timeout_break_ms(mut self, timeout_ms: u64) -> Self {
         self.config.step_timeout_break_ms = timeout_ms;
@@ -114,6 +172,152 @@ impl<'a, E: GameEngine, F: EngineFactory<E>> NodeBuilder<'a, E, F> {
         self.config.keyexpr_prefix = prefix;
         self
     }
+
+    /// Set the rooms served when acting as host
+    /// Each room runs its own engine instance with its own clients and state channel.
+    /// States of the rooms are reported as `StepResult::RoomState`, and local actions
+    /// are delivered to the first room.
+    pub fn rooms(mut self, rooms: Vec<RoomId>) -> Self {
+        self.config.rooms = rooms;
+        self
+    }
+
+    /// Set the room to request when connecting to a host as client
+    /// Hosts reject the connection if they do not serve this room or it is full.
+    pub fn room(mut self, room: RoomId) -> Self {
+        self.config.room = Some(room);
+        self
+    }
+
+    /// Set what to do when an engine panics while hosting
+    /// By default the engine is restarted from the last published state up to 3 times
+    /// per room. Crashes are reported as `StepResult::EngineCrashed`.
+    pub fn engine_restart(mut self, policy: RestartPolicy) -> Self {
+        self.config.engine_restart = policy;
+        self
+    }
+
+    /// Set whether a host yields to other hosts of the same game it discovers
+    /// Resolves split-brain situations where several nodes became hosts at the same time.
+    /// By default hosts without clients yield to higher ranked hosts and join them.
+    pub fn host_merge(mut self, policy: MergePolicy) -> Self {
+        self.config.host_merge = policy;
+        self
+    }
+
+    /// Set how actions issued while searching for a host are buffered
+    /// By default up to 32 actions younger than 2 seconds are replayed to the next host,
+    /// or to the local engine if the node becomes host.
+    pub fn action_buffering(mut self, policy: ActionBuffering) -> Self {
+        self.config.action_buffering = policy;
+        self
+    }
+
+    /// Set the limits a host applies to the actions of each client
+    /// Actions breaking the rate or size limits, or rejected by the engine's
+    /// `validate_action`, are handled according to the violation policy and counted
+    /// by `Node::action_violations`. By default actions are not limited.
+    pub fn action_limits(mut self, limits: ActionLimits) -> Self {
+        self.config.action_limits = limits;
+        self
+    }
+
+    /// Set the capacities and backpressure policies of the channels between a host and its engines
+    /// Bounded queues either make the sender wait or drop the oldest items, e.g. to keep only
+    /// the latest state for latest-wins games. Queue depths are reported by
+    /// `Node::queue_stats`. By default both queues are unbounded.
+    pub fn engine_queues(mut self, queues: EngineQueues) -> Self {
+        self.config.engine_queues = queues;
+        self
+    }
+
+    /// Set the maximum rates at which a host publishes the states of its engines to clients
+    /// and returns them from `step`
+    /// States produced faster are coalesced into the most recent one. By default every
+    /// state is published and returned. Fails if a rate is 0.
+    pub fn state_rates(mut self, rates: StateRates) -> Result<Self> {
+        if rates.publish == Some(0) || rates.deliver == Some(0) {
+            return Err(ArenaError::InvalidConfig(format!(
+                "State rates must be at least 1 per second: {:?}",
+                rates
+            )));
+        }
+        self.config.state_rates = rates;
+        Ok(self)
+    }
+
+    /// Set the quality of service of the actions sent by clients to their host
+    /// E.g. `LinkQos::realtime()` keeps real-time actions from queuing behind bulky
+    /// states. Defaults to zenoh's default publisher QoS.
+    pub fn action_qos(mut self, qos: LinkQos) -> Self {
+        self.config.action_qos = qos;
+        self
+    }
+
+    /// Set the quality of service of the states published by hosts to their clients
+    /// Defaults to zenoh's default publisher QoS.
+    pub fn state_qos(mut self, qos: LinkQos) -> Self {
+        self.config.state_qos = qos;
+        self
+    }
+
+    /// Set the quality of service of the discovery and connection queries
+    /// Also applies to matchmaking tickets and assignments. Replies of hosts follow the
+    /// priority and congestion control of their query. Defaults to `LinkQos::request()`.
+    pub fn handshake_qos(mut self, qos: LinkQos) -> Self {
+        self.config.handshake_qos = qos;
+        self
+    }
+
+    /// Set the quality of service of the engine events published by hosts to their clients
+    /// Defaults to `LinkQos::reliable()`: unlike states, events are not superseded by the
+    /// next one and must not be dropped.
+    pub fn event_qos(mut self, qos: LinkQos) -> Self {
+        self.config.event_qos = qos;
+        self
+    }
+
+    /// Set the quality of service of the chat messages between hosts and their clients
+    /// Defaults to `LinkQos::reliable()`: a chat message is not superseded by the next one.
+    pub fn chat_qos(mut self, qos: LinkQos) -> Self {
+        self.config.chat_qos = qos;
+        self
+    }
+
+    /// Set the quality of service of the notices sent by hosts to their clients
+    /// Defaults to `LinkQos::reliable()`: a lost hand-off or kick notice leaves the client
+    /// waiting for its host to time out.
+    pub fn notice_qos(mut self, qos: LinkQos) -> Self {
+        self.config.notice_qos = qos;
+        self
+    }
+
+    /// Publish large states from shared memory when acting as host
+    /// Clients on the same machine map states of at least `threshold` bytes instead of
+    /// receiving a copy through the transport; remote clients receive them as usual.
+    /// Fails if the shared-memory segment cannot be created.
+    #[cfg(feature = "shared-memory")]
+    pub fn shared_memory(mut self, shared_memory: SharedMemory) -> Result<Self> {
+        self.config.shared_memory = Some(Arc::new(ShmPool::new(shared_memory)?));
+        Ok(self)
+    }
+
+    /// Set the number of chat messages to everybody a host keeps for clients joining later
+    /// Clients receive the history as `StepResult::Chat` right after connecting.
+    /// By default no history is kept.
+    pub fn chat_history(mut self, capacity: usize) -> Self {
+        self.config.chat_history = capacity;
+        self
+    }
+
+    /// Join the queue of a matchmaker instead of searching for hosts
+    /// While searching, the node publishes its ticket and waits for the matchmaker to
+    /// assign a match. The assigned host starts hosting and the other members connect
+    /// to it with the regular handshake.
+    pub fn matchmaking(mut self, ticket: MatchTicket) -> Self {
+        self.config.matchmaking = Some(ticket);
+        self
+    }
 }
 
 impl<'a, E: GameEngine, F: EngineFactory<E>> Resolvable for NodeBuilder<'a, E, F> {
@@ -129,9 +333,7 @@ impl<'a, E: GameEngine, F: EngineFactory<E> + Send + 'a> std::future::IntoFuture
 
     fn into_future(self) -> Self::IntoFuture {
         Box::pin(async move {
-            // Clone the session since Node::new_internal takes ownership
-            let session = self.session.clone();
-            Node::new_internal(self.config, session, self.get_engine).await
+            Node::new_internal(self.config, self.transport, self.get_engine).await
         })
     }
 }
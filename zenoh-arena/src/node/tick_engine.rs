@@ -0,0 +1,471 @@
+/// Tick-driven engines run by the library
+use std::time::Duration;
+
+use crate::codec::Codec;
+use crate::error::{ArenaError, Result};
+use crate::node::async_engine::{AsyncDriven, AsyncGameEngine};
+use crate::node::game_engine::{EngineEvent, EngineFactory};
+use crate::node::types::{NodeId, PlayerSlot};
+
+/// Synchronous game engine driven by the library at a fixed tick rate
+///
+/// An alternative to implementing [`GameEngine`] directly for games that need no threading
+/// code: the library owns the engine on its own task, feeds it the players' actions and
+/// calls [`tick`](TickEngine::tick) at the configured rate. Use [`tick_engine`] to turn
+/// a constructor into an [`EngineFactory`].
+///
+/// [`GameEngine`]: crate::GameEngine
+pub trait TickEngine: Send + 'static {
+    /// Action type from user/client
+    type Action: Send + 'static;
+
+    /// State type sent to clients
+    type State: Send + Clone + 'static;
+
+    /// Event type sent to clients apart from the state (see [`take_events`](TickEngine::take_events))
+    ///
+    /// Use `()` for engines without events.
+    type Event: Send + Sync + 'static;
+
+    /// Codec used to encode actions, states and events on the wire
+    type Codec: Codec<Self::Action> + Codec<Self::State> + Codec<Self::Event>;
+
+    /// Game identifier exchanged during the handshake
+    const GAME_ID: &'static str = "";
+
+    /// Game version exchanged during the handshake
+    const GAME_VERSION: u32 = 0;
+
+    /// Maximum number of clients allowed (None = unlimited)
+    fn max_clients(&self) -> Option<usize>;
+
+    /// Number of teams players are distributed over
+    fn team_count(&self) -> u32 {
+        1
+    }
+
+    /// Check an action received from a client before it is queued for the engine
+    ///
+    /// Like [`AsyncGameEngine::validate_action`], without access to the engine. Accepts
+    /// every action by default.
+    fn validate_action(_player: &NodeId, _slot: PlayerSlot, _action: &Self::Action) -> std::result::Result<(), String>
+    where
+        Self: Sized,
+    {
+        Ok(())
+    }
+
+    /// Hand over the receiving end of the engine's event channel
+    ///
+    /// Called once before the first tick; the engine sends events from
+    /// [`on_action`](TickEngine::on_action) and [`tick`](TickEngine::tick). Returns None by default.
+    fn take_events(&mut self) -> Option<flume::Receiver<EngineEvent<Self::Event>>> {
+        None
+    }
+
+    /// Apply an action of the player in `slot`
+    ///
+    /// Actions received between two ticks are applied before the next tick.
+    fn on_action(&mut self, from: &NodeId, slot: PlayerSlot, action: Self::Action);
+
+    /// Advance the game by `dt` since the previous tick
+    ///
+    /// Returns the state to publish, or None if nothing changed.
+    fn tick(&mut self, dt: Duration) -> Option<Self::State>;
+
+    /// Whether the game is over
+    ///
+    /// Checked after every tick. A finished engine stops, which the host handles like any
+    /// other engine exit. A panic in `on_action` or `tick` is reported as a crash.
+    fn is_finished(&self) -> bool {
+        false
+    }
+}
+
+/// [`TickEngine`] run as an [`AsyncGameEngine`], ticking it at a fixed interval
+pub struct Ticking<T: TickEngine> {
+    engine: T,
+    tick_interval: Duration,
+}
+
+impl<T: TickEngine> Ticking<T> {
+    /// Tick `engine` every `tick_interval`
+    ///
+    /// Fails if the tick interval is zero.
+    pub fn new(engine: T, tick_interval: Duration) -> Result<Self> {
+        check_tick_interval(tick_interval)?;
+        Ok(Self { engine, tick_interval })
+    }
+}
+
+impl<T: TickEngine> AsyncGameEngine for Ticking<T> {
+    type Action = T::Action;
+    type State = T::State;
+    type Event = T::Event;
+    type Codec = T::Codec;
+
+    const GAME_ID: &'static str = T::GAME_ID;
+    const GAME_VERSION: u32 = T::GAME_VERSION;
+
+    fn max_clients(&self) -> Option<usize> {
+        self.engine.max_clients()
+    }
+
+    fn team_count(&self) -> u32 {
+        self.engine.team_count()
+    }
+
+    fn validate_action(player: &NodeId, slot: PlayerSlot, action: &T::Action) -> std::result::Result<(), String> {
+        T::validate_action(player, slot, action)
+    }
+
+    fn take_events(&mut self) -> Option<flume::Receiver<EngineEvent<T::Event>>> {
+        self.engine.take_events()
+    }
+
+    async fn run(
+        self,
+        input_rx: flume::Receiver<(NodeId, PlayerSlot, T::Action)>,
+        output_tx: flume::Sender<T::State>,
+    ) {
+        drive(self.engine, self.tick_interval, input_rx, output_tx).await
+    }
+}
+
+/// [`GameEngine`](crate::GameEngine) running a [`TickEngine`] on a tokio task
+///
+/// The task stops when this value is dropped by the host.
+pub type TickDriven<T> = AsyncDriven<Ticking<T>>;
+
+/// Create an engine factory driving tick engines at the given interval
+///
+/// `new_engine` receives the host's NodeId and the optional initial state, like the
+/// other arguments of an [`EngineFactory`]. The engine is run on a tokio task of the
+/// node's runtime. Fails if the tick interval is zero.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use zenoh_arena::{tick_engine, NodeId, PlayerSlot, SessionExt, TickEngine, ZenohCodec};
+///
+/// struct Counter(i64, bool);
+///
+/// impl TickEngine for Counter {
+///     type Action = i64;
+///     type State = i64;
+///     type Event = ();
+///     type Codec = ZenohCodec;
+///
+///     fn max_clients(&self) -> Option<usize> { None }
+///
+///     fn on_action(&mut self, _from: &NodeId, _slot: PlayerSlot, action: i64) {
+///         self.0 += action;
+///         self.1 = true;
+///     }
+///
+///     fn tick(&mut self, _dt: Duration) -> Option<i64> {
+///         std::mem::take(&mut self.1).then_some(self.0)
+///     }
+/// }
+///
+/// # async fn example() {
+/// let session = zenoh::open(zenoh::Config::default()).await.unwrap();
+/// let node = session
+///     .declare_arena_node(tick_engine(Duration::from_millis(50), |_host_id, state: Option<i64>| {
+///         Counter(state.unwrap_or_default(), false)
+///     }).unwrap())
+///     .await
+///     .unwrap();
+/// # }
+/// ```
+pub fn tick_engine<T, F>(tick_interval: Duration, new_engine: F) -> Result<impl EngineFactory<TickDriven<T>>>
+where
+    T: TickEngine,
+    F: Fn(NodeId, Option<T::State>) -> T + Send + Sync,
+{
+    check_tick_interval(tick_interval)?;
+    Ok(move |host_id, input_rx, output_tx, initial_state| {
+        let engine = Ticking::new(new_engine(host_id, initial_state), tick_interval).expect("tick interval checked");
+        AsyncDriven::spawn(engine, input_rx, output_tx)
+    })
+}
+
+/// Reject a zero tick interval, which cannot drive a timer
+fn check_tick_interval(tick_interval: Duration) -> Result<()> {
+    if tick_interval.is_zero() {
+        return Err(ArenaError::InvalidConfig("tick interval must not be zero".to_string()));
+    }
+    Ok(())
+}
+
+/// Feed actions to the engine and tick it until it finishes or the host goes away
+async fn drive<T: TickEngine>(
+    mut engine: T,
+    tick_interval: Duration,
+    input_rx: flume::Receiver<(NodeId, PlayerSlot, T::Action)>,
+    output_tx: flume::Sender<T::State>,
+) {
+    let mut interval = tokio::time::interval(tick_interval);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut last_tick = tokio::time::Instant::now();
+
+    'drive: loop {
+        tokio::select! {
+            // Tick first, so that a steady flow of actions cannot hold the ticks back
+            biased;
+            now = interval.tick() => {
+                // Apply the actions queued before this tick
+                for _ in 0..input_rx.len() {
+                    match input_rx.try_recv() {
+                        Ok((from, slot, action)) => engine.on_action(&from, slot, action),
+                        Err(flume::TryRecvError::Empty) => break,
+                        Err(flume::TryRecvError::Disconnected) => break 'drive,
+                    }
+                }
+                let dt = now - last_tick;
+                last_tick = now;
+                if let Some(state) = engine.tick(dt) {
+                    if output_tx.send_async(state).await.is_err() {
+                        break;
+                    }
+                }
+                if engine.is_finished() {
+                    break;
+                }
+            }
+            action_result = input_rx.recv_async() => match action_result {
+                Ok((from, slot, action)) => {
+                    engine.on_action(&from, slot, action);
+                    // Yield once the task used its budget, letting the timer of the ticks fire
+                    tokio::task::consume_budget().await;
+                }
+                Err(_) => break,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::ZenohCodec;
+    use crate::node::game_engine::{EngineExit, GameEngine};
+
+    /// Sums actions weighted by slot, finishes once the sum reaches 10, panics on 0
+    struct Summer {
+        sum: u32,
+        elapsed: Duration,
+    }
+
+    impl TickEngine for Summer {
+        type Action = u32;
+        type State = (u32, u64);
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            Some(3)
+        }
+
+        fn on_action(&mut self, _from: &NodeId, slot: PlayerSlot, action: u32) {
+            assert_ne!(action, 0, "invalid action");
+            self.sum += action * (slot.index + 1);
+        }
+
+        fn tick(&mut self, dt: Duration) -> Option<Self::State> {
+            self.elapsed += dt;
+            Some((self.sum, self.elapsed.as_millis() as u64))
+        }
+
+        fn is_finished(&self) -> bool {
+            self.sum >= 10
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tick_engine_driven() {
+        let factory = tick_engine(Duration::from_millis(100), |_host_id, state: Option<(u32, u64)>| {
+            Summer {
+                sum: state.map(|(sum, _)| sum).unwrap_or_default(),
+                elapsed: Duration::ZERO,
+            }
+        })
+        .unwrap();
+        let (input_tx, input_rx) = flume::unbounded();
+        let (output_tx, output_rx) = flume::unbounded();
+        let engine = factory(NodeId::generate(), input_rx, output_tx, Some((1, 0)));
+        assert_eq!(engine.max_clients(), Some(3));
+
+        // The first tick happens immediately
+        assert_eq!(output_rx.recv_async().await.unwrap(), (1, 0));
+
+        input_tx.send((NodeId::generate(), PlayerSlot::new(1, 1), 2)).unwrap();
+        assert_eq!(output_rx.recv_async().await.unwrap(), (5, 100));
+
+        // Reaching the target finishes the engine, closing its output
+        input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), 5)).unwrap();
+        assert_eq!(output_rx.recv_async().await.unwrap(), (10, 200));
+        assert!(output_rx.recv_async().await.is_err());
+    }
+
+    /// Feeds itself an action on its first tick, then a new one for every action taken
+    struct Flooded {
+        input_tx: flume::Sender<(NodeId, PlayerSlot, u32)>,
+        ticks: u32,
+    }
+
+    impl TickEngine for Flooded {
+        type Action = u32;
+        type State = u32;
+        type Event = ();
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            None
+        }
+
+        fn on_action(&mut self, from: &NodeId, slot: PlayerSlot, action: u32) {
+            let _ = self.input_tx.send((from.clone(), slot, action));
+        }
+
+        fn tick(&mut self, _dt: Duration) -> Option<Self::State> {
+            if self.ticks == 0 {
+                let _ = self.input_tx.send((NodeId::generate(), PlayerSlot::new(0, 1), 1));
+            }
+            self.ticks += 1;
+            Some(self.ticks)
+        }
+
+        fn is_finished(&self) -> bool {
+            self.ticks >= 3
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tick_engine_ticks_under_action_flood() {
+        let (input_tx, input_rx) = flume::unbounded();
+        let factory = tick_engine(Duration::from_millis(10), move |_host_id, _state| Flooded {
+            input_tx: input_tx.clone(),
+            ticks: 0,
+        })
+        .unwrap();
+        let (output_tx, output_rx) = flume::unbounded();
+        let _engine = factory(NodeId::generate(), input_rx, output_tx, None);
+
+        // The engine keeps ticking while actions never stop coming. The flood keeps the
+        // runtime busy, so the paused clock only moves when advanced here.
+        let mut states = Vec::new();
+        for _ in 0..100 {
+            tokio::time::advance(Duration::from_millis(10)).await;
+            states.extend(output_rx.try_iter());
+            if states.len() >= 3 {
+                break;
+            }
+        }
+        assert_eq!(states, vec![1, 2, 3]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tick_engine_stops_on_drop() {
+        let factory = tick_engine(Duration::from_millis(10), |_host_id, _state| Summer {
+            sum: 0,
+            elapsed: Duration::ZERO,
+        })
+        .unwrap();
+        let (_input_tx, input_rx) = flume::unbounded();
+        let (output_tx, output_rx) = flume::unbounded();
+        let engine = factory(NodeId::generate(), input_rx, output_tx, None);
+        output_rx.recv_async().await.unwrap();
+
+        drop(engine);
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        while output_rx.try_recv().is_ok() {}
+        assert!(output_rx.recv_async().await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tick_engine_panic_reported() {
+        let factory = tick_engine(Duration::from_millis(10), |_host_id, _state| Summer {
+            sum: 0,
+            elapsed: Duration::ZERO,
+        })
+        .unwrap();
+        let (input_tx, input_rx) = flume::unbounded();
+        let (output_tx, output_rx) = flume::unbounded();
+        let mut engine = factory(NodeId::generate(), input_rx, output_tx, None);
+
+        input_tx.send((NodeId::generate(), PlayerSlot::new(1, 1), 0)).unwrap();
+        while output_rx.recv_async().await.is_ok() {}
+        let exit = engine.exited().await;
+        assert!(matches!(exit, EngineExit::Crashed(ref message) if message.contains("invalid action")));
+    }
+
+    /// Announces every action as an event, refuses actions above 9
+    struct Announcer {
+        events_tx: flume::Sender<EngineEvent<u32>>,
+        events_rx: Option<flume::Receiver<EngineEvent<u32>>>,
+    }
+
+    impl TickEngine for Announcer {
+        type Action = u32;
+        type State = u32;
+        type Event = u32;
+        type Codec = ZenohCodec;
+
+        fn max_clients(&self) -> Option<usize> {
+            None
+        }
+
+        fn validate_action(_player: &NodeId, _slot: PlayerSlot, action: &u32) -> std::result::Result<(), String> {
+            if *action > 9 {
+                return Err("action above 9".to_string());
+            }
+            Ok(())
+        }
+
+        fn take_events(&mut self) -> Option<flume::Receiver<EngineEvent<u32>>> {
+            self.events_rx.take()
+        }
+
+        fn on_action(&mut self, _from: &NodeId, _slot: PlayerSlot, action: u32) {
+            let _ = self.events_tx.send(EngineEvent::broadcast(action));
+        }
+
+        fn tick(&mut self, _dt: Duration) -> Option<Self::State> {
+            None
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_tick_engine_validates_and_emits_events() {
+        let factory = tick_engine(Duration::from_millis(10), |_host_id, _state| {
+            let (events_tx, events_rx) = flume::unbounded();
+            Announcer {
+                events_tx,
+                events_rx: Some(events_rx),
+            }
+        })
+        .unwrap();
+        let (input_tx, input_rx) = flume::unbounded();
+        let (output_tx, _output_rx) = flume::unbounded();
+        let mut engine = factory(NodeId::generate(), input_rx, output_tx, None);
+
+        let player = NodeId::generate();
+        let slot = PlayerSlot::new(0, 1);
+        assert!(engine.validate_action(&player, slot, &10).is_err());
+        assert!(engine.validate_action(&player, slot, &9).is_ok());
+
+        let events = engine.take_events().expect("events of the tick engine");
+        input_tx.send((player, slot, 7)).unwrap();
+        assert_eq!(events.recv_async().await.unwrap(), EngineEvent::broadcast(7));
+    }
+
+    #[test]
+    fn test_tick_engine_rejects_zero_interval() {
+        let new_engine = |_host_id, _state| Summer {
+            sum: 0,
+            elapsed: Duration::ZERO,
+        };
+        assert!(matches!(tick_engine(Duration::ZERO, new_engine), Err(ArenaError::InvalidConfig(_))));
+    }
+}
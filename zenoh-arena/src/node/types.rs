@@ -1,14 +1,16 @@
 /// Core types for the zenoh-arena library
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Instant;
-use zenoh::key_expr::KeyExpr;
 
 use crate::error::{ArenaError, Result};
-use crate::network::{HostQueryable, NodeLivelinessToken, NodeLivelinessWatch, NodePublisher, NodeSubscriber};
-use crate::network::keyexpr::{LinkType, NodeType};
+use crate::network::{ChatMessage, ChatRequest, HostConnection, HostQueryable, NodeLivelinessToken, NodeLivelinessWatch, NodePublisher, NodeSubscriber, Transport};
+use crate::network::keyexpr::{KeyexprLink, LinkType, NodeType};
 use crate::node::client_state::ClientState;
+use crate::node::config::NodeConfig;
 use crate::node::game_engine::{EngineFactory, GameEngine};
-use crate::node::host_state::HostState;
+use crate::node::chat::HostChat;
+use crate::node::host_state::{HostRoom, HostState};
 use crate::node::name_generator;
 use crate::node::searching_host_state::SearchingHostState;
 
@@ -44,32 +46,326 @@ impl NodeId {
 
     /// Validate that a string can be used as NodeId (single keyexpr chunk)
     fn validate(s: &str) -> Result<()> {
-        if s.is_empty() {
-            return Err(ArenaError::InvalidNodeName(
-                "Node name cannot be empty".to_string(),
-            ));
-        }
+        validate_chunk(s).map_err(ArenaError::InvalidNodeName)
+    }
+}
 
-        // Check for invalid characters: / * $ ? # @
-        for ch in s.chars() {
-            if matches!(ch, '/' | '*' | '$' | '?' | '#' | '@') {
-                return Err(ArenaError::InvalidNodeName(format!(
-                    "Node name '{}' contains invalid character '{}'",
-                    s, ch
-                )));
-            }
+impl std::fmt::Display for NodeId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Check that a name is a non-empty single keyexpr chunk without special characters
+fn validate_chunk(s: &str) -> std::result::Result<(), String> {
+    if s.is_empty() {
+        return Err("Name cannot be empty".to_string());
+    }
+
+    // Check for invalid characters: / * $ ? # @
+    for ch in s.chars() {
+        if matches!(ch, '/' | '*' | '$' | '?' | '#' | '@') {
+            return Err(format!("Name '{}' contains invalid character '{}'", s, ch));
         }
+    }
+
+    Ok(())
+}
+
+/// Room identifier
+///
+/// A host may run several rooms, each with its own engine instance, clients and
+/// state channel. Like [`NodeId`], a RoomId must be a valid single-chunk keyexpr.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RoomId(String);
 
-        Ok(())
+impl RoomId {
+    /// Create from a name (must be keyexpr-compatible)
+    /// Returns error if name contains invalid characters
+    pub fn new(name: impl Into<String>) -> Result<Self> {
+        let name = name.into();
+        validate_chunk(&name).map_err(ArenaError::InvalidRoomName)?;
+        Ok(RoomId(name))
+    }
+
+    /// Get the string representation
+    pub fn as_str(&self) -> &str {
+        &self.0
     }
 }
 
-impl std::fmt::Display for NodeId {
+impl std::fmt::Display for RoomId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.0)
     }
 }
 
+/// Numbered player slot assigned by the host when accepting a player
+///
+/// Slots are numbered per room from 0; the host player always takes slot 0 of the game
+/// it plays in. A player reconnecting to the same host gets its previous slot back.
+/// Players are distributed over the teams declared by the engine in slot order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayerSlot {
+    /// Slot number
+    pub index: u32,
+    /// Team of the slot
+    pub team: u32,
+}
+
+impl PlayerSlot {
+    /// Create the slot with the given number for an engine with `team_count` teams
+    pub fn new(index: u32, team_count: u32) -> Self {
+        Self {
+            index,
+            team: index % team_count.max(1),
+        }
+    }
+}
+
+impl std::fmt::Display for PlayerSlot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "slot {} (team {})", self.index, self.team)
+    }
+}
+
+/// Public information about a room served by a host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoomInfo {
+    /// Room identifier
+    pub id: RoomId,
+    /// Clients playing in this room
+    pub connected_clients: Vec<NodeId>,
+    /// Maximum number of clients allowed in this room (None = unlimited)
+    pub max_clients: Option<usize>,
+    /// Connected players of this room with their slots, including the host if it plays here
+    pub players: Vec<(NodeId, PlayerSlot)>,
+}
+
+/// What a host does when an engine panics
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Handle the crash like the end of the game
+    Never,
+    /// Recreate the engine with the last published state as initial state,
+    /// at most `max_restarts` times per room
+    Restart {
+        /// Maximum number of restarts of each room's engine
+        max_restarts: u32,
+    },
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::Restart { max_restarts: 3 }
+    }
+}
+
+/// How actions issued while searching for a host are buffered
+///
+/// Buffered actions are replayed in order to the new host once connected, or to the
+/// local engine if the node becomes host itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActionBuffering {
+    /// Maximum number of buffered actions; the oldest are dropped first (0 disables buffering)
+    pub capacity: usize,
+    /// Actions older than this are dropped instead of replayed (None keeps them)
+    pub max_age: Option<std::time::Duration>,
+}
+
+impl ActionBuffering {
+    /// Drop actions issued while searching
+    pub fn disabled() -> Self {
+        Self {
+            capacity: 0,
+            max_age: None,
+        }
+    }
+}
+
+impl Default for ActionBuffering {
+    fn default() -> Self {
+        Self {
+            capacity: 32,
+            max_age: Some(std::time::Duration::from_secs(2)),
+        }
+    }
+}
+
+/// What a host does with an action breaking its [`ActionLimits`] or failing validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ViolationPolicy {
+    /// Drop the action
+    #[default]
+    Drop,
+    /// Forward the action anyway, logging a warning
+    Warn,
+    /// Drop the action, and kick the client once it has committed `after` violations
+    ///
+    /// A kicked client is told, removed from its room, and its connection requests are
+    /// rejected for the search timeout of the host. Its further actions are ignored. The
+    /// kicked node returns to searching, skipping the host in its first search.
+    Kick {
+        /// Number of violations after which the client is kicked
+        after: u32,
+    },
+}
+
+/// Limits a host applies to the actions of each client
+///
+/// Actions are also checked by [`GameEngine::validate_action`](crate::GameEngine::validate_action).
+/// Actions of the host's own player are not limited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ActionLimits {
+    /// Sustained number of actions per second of each client (None for no limit)
+    pub rate: Option<u32>,
+    /// Number of actions a client may send at once above the sustained rate
+    pub burst: u32,
+    /// Maximum encoded size of an action in bytes (None for no limit)
+    pub max_size: Option<usize>,
+    /// What to do with actions breaking the limits or failing validation
+    pub on_violation: ViolationPolicy,
+}
+
+/// Counters of actions breaking the [`ActionLimits`] of a host
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ActionViolations {
+    /// Actions exceeding the rate limit of their client
+    pub rate_limited: u64,
+    /// Actions exceeding the maximum size
+    pub oversized: u64,
+    /// Actions rejected by the engine's validation
+    pub invalid: u64,
+    /// Clients kicked for repeated violations
+    pub kicked: u64,
+}
+
+impl std::ops::AddAssign for ActionViolations {
+    fn add_assign(&mut self, other: Self) {
+        self.rate_limited += other.rate_limited;
+        self.oversized += other.oversized;
+        self.invalid += other.invalid;
+        self.kicked += other.kicked;
+    }
+}
+
+/// What a full queue between a host and its engine does with a new item
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backpressure {
+    /// Wait until the queue has room
+    ///
+    /// The host waits for the engine to take actions, the engine waits for the host to
+    /// take states. Engines running as tokio tasks should send states with `send_async`.
+    #[default]
+    Block,
+    /// Drop the oldest queued item
+    DropOldest,
+    /// Keep only the latest item, whatever the capacity
+    Latest,
+}
+
+/// Capacity and backpressure policy of a queue between a host and its engine
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EngineQueue {
+    /// Maximum number of queued items, at least 1 (None for unbounded)
+    pub capacity: Option<usize>,
+    /// What to do with a new item when the queue is full
+    pub backpressure: Backpressure,
+}
+
+impl EngineQueue {
+    /// Queue holding up to `capacity` items
+    pub fn bounded(capacity: usize, backpressure: Backpressure) -> Self {
+        Self {
+            capacity: Some(capacity),
+            backpressure,
+        }
+    }
+
+    /// Queue keeping only the latest item, for latest-wins games
+    pub fn latest() -> Self {
+        Self::bounded(1, Backpressure::Latest)
+    }
+}
+
+/// Queues between a host and the engine of each of its rooms
+///
+/// Both queues are unbounded by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EngineQueues {
+    /// Actions sent by the host to the engine
+    pub actions: EngineQueue,
+    /// States sent by the engine to the host
+    pub states: EngineQueue,
+}
+
+/// Maximum rates at which a host hands on the states of each of its engines
+///
+/// States produced faster are coalesced: only the most recent one is handed on once
+/// the interval has elapsed. Both rates are unlimited by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StateRates {
+    /// States published to clients per second, at least 1 (None for every state)
+    pub publish: Option<u32>,
+    /// States returned by `Node::step` on the host per second, at least 1 (None for every state)
+    pub deliver: Option<u32>,
+}
+
+/// Shared-memory pool used by a host to publish large states
+///
+/// States at least `threshold` bytes long are published from shared memory, so that
+/// clients on the same machine map them instead of receiving a copy. Remote clients
+/// receive them as usual.
+#[cfg(feature = "shared-memory")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SharedMemory {
+    /// Size of the shared-memory segment in bytes
+    pub pool_size: usize,
+    /// Minimum size of a state payload to publish it from shared memory
+    pub threshold: usize,
+}
+
+#[cfg(feature = "shared-memory")]
+impl Default for SharedMemory {
+    fn default() -> Self {
+        Self {
+            pool_size: 16 * 1024 * 1024,
+            threshold: 4 * 1024,
+        }
+    }
+}
+
+/// Depth of the queues between a host and the engine of a room, and items they dropped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueueStats {
+    /// Room of the engine (None for a host without rooms)
+    pub room: Option<RoomId>,
+    /// Actions waiting to be taken by the engine
+    pub queued_actions: usize,
+    /// States waiting to be published by the host
+    pub queued_states: usize,
+    /// Actions dropped from the full action queue
+    pub dropped_actions: u64,
+    /// States dropped from the full state queue
+    pub dropped_states: u64,
+}
+
+/// What a host does when it discovers other hosts of the same game
+///
+/// Hosts are ranked by number of clients, ties broken by the smaller node id. A host that
+/// yields returns to searching and joins the host it yielded to. It tells its clients
+/// which host that is, and they connect to it directly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergePolicy {
+    /// Never look for other hosts
+    Never,
+    /// Hosts without clients yield to higher ranked hosts
+    #[default]
+    EmptyHosts,
+    /// Any host yields to higher ranked hosts, handing its clients off to the host it yields to
+    HandOff,
+}
+
 /// Node role in the arena
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum NodeRole {
@@ -93,14 +389,34 @@ pub struct NodeInfo {
 }
 
 /// Result of a step execution
+///
+/// `Ev` is the event type of the engine (see [`GameEngine::Event`](crate::GameEngine::Event)).
 #[derive(Debug, Clone)]
-pub enum StepResult<S> {
+pub enum StepResult<S, Ev = ()> {
     /// A game state was produced
     GameState(S),
     /// The step timed out without producing a game state
     Timeout,
+    /// A game state was produced by one of the rooms of a multi-room host
+    RoomState(RoomId, S),
     /// Node state changed
     RoleChanged(NodeRole),
+    /// The engine of the room (None for a host without rooms) panicked, with the panic message
+    ///
+    /// Depending on the [`RestartPolicy`] the engine was restarted from the last published
+    /// state, or the crash ended the game: the room got a fresh engine, or a host without
+    /// rooms went back to searching (see [`Node::state`](crate::Node::state)).
+    EngineCrashed(Option<RoomId>, String),
+    /// A chat message sent to everybody or to this node was received
+    ///
+    /// Messages sent to everybody are also returned to their sender.
+    Chat(ChatMessage),
+    /// An event emitted by the engine of the room (None for a host without rooms) for
+    /// everybody or for this node
+    ///
+    /// Events are delivered reliably and apart from the state, see
+    /// [`GameEngine::take_events`](crate::GameEngine::take_events).
+    Event(Option<RoomId>, Ev),
     /// The node has stopped
     Stop,
 }
@@ -114,13 +430,21 @@ pub enum NodeState {
     Client {
         /// ID of the host we're connected to
         host_id: NodeId,
+        /// Room assigned by the host (None if the host does not serve rooms)
+        room: Option<RoomId>,
+        /// Player slot assigned by the host
+        slot: PlayerSlot,
     },
     /// Acting as host
     Host {
         /// Whether accepting new clients (derived from queryable presence and capacity)
         is_accepting: bool,
-        /// List of connected client IDs
+        /// List of connected client IDs (across all rooms)
         connected_clients: Vec<NodeId>,
+        /// Players of the host's own game (the first room) with their slots, host included
+        players: Vec<(NodeId, PlayerSlot)>,
+        /// Rooms served by the host (empty if the host does not serve rooms)
+        rooms: Vec<RoomInfo>,
     },
     /// Node has stopped
     Stop,
@@ -129,13 +453,16 @@ pub enum NodeState {
 impl std::fmt::Display for NodeState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            NodeState::SearchingHost => write!(f, "Searching"),
-            NodeState::Client { host_id } => {
-                write!(f, "Client to: {}", host_id)
-            }
+            NodeState::SearchingHost => write!(f, "Searching for host..."),
+            NodeState::Client { host_id, room, .. } => match room {
+                Some(room) => write!(f, "Connected as client to host: {} (room: {})", host_id, room),
+                None => write!(f, "Connected as client to host: {}", host_id),
+            },
             NodeState::Host {
                 is_accepting,
                 connected_clients,
+                rooms,
+                ..
             } => {
                 let accepting_str = if *is_accepting { "open" } else { "closed" };
                 let client_info = if connected_clients.is_empty() {
@@ -144,7 +471,11 @@ impl std::fmt::Display for NodeState {
                     format!("{} client(s)", connected_clients.len())
                 };
                 
-                write!(f, "Host ({}, {})", accepting_str, client_info)
+                if rooms.is_empty() {
+                    write!(f, "Host mode ({}, {})", accepting_str, client_info)
+                } else {
+                    write!(f, "Host mode ({}, {}, {} room(s))", accepting_str, client_info, rooms.len())
+                }
             }
             NodeState::Stop => write!(f, "Node stopped"),
         }
@@ -184,7 +515,8 @@ where
             }
             NodeStateInternal::Host(host_state) => f
                 .debug_struct("Host")
-                .field("connected_clients", &host_state.connected_clients)
+                .field("connected_clients", &host_state.connected_clients())
+                .field("rooms", &host_state.room_infos())
                 .field("pending_client_disconnects_count", &"<futures>")
                 .finish(),
             NodeStateInternal::Stop => f.debug_tuple("Stop").finish(),
@@ -200,18 +532,41 @@ where
     ///
     /// Drops the current state (including engine and liveliness token if in Host mode)
     pub fn searching() -> Self {
-        NodeStateInternal::SearchingHost(SearchingHostState {
-            _phantom: std::marker::PhantomData,
-        })
+        NodeStateInternal::SearchingHost(SearchingHostState::new(true))
+    }
+
+    /// Transition to SearchingHost state without becoming host when no host is found
+    ///
+    /// Used after leaving a game: the node keeps searching until it connects to a host.
+    /// The `previous_host` left by a client is skipped by the first search, so that the
+    /// client does not join it again before it noticed the departure.
+    pub fn leaving(previous_host: Option<NodeId>) -> Self {
+        NodeStateInternal::SearchingHost(SearchingHostState::new(false).excluding(previous_host))
+    }
+
+    /// Transition to SearchingHost state, joining the host `host` handed off to
+    ///
+    /// The node connects to `host` directly, and searches if it cannot reach it by `deadline`.
+    pub fn handed_off(host: NodeId, deadline: tokio::time::Instant) -> Self {
+        NodeStateInternal::SearchingHost(SearchingHostState::new(true).handed_off_to(host, deadline))
+    }
+
+    /// Transition to SearchingHost state after being kicked by `host`
+    ///
+    /// The host rejects the node, so the first search skips it.
+    pub fn kicked(host: NodeId) -> Self {
+        NodeStateInternal::SearchingHost(SearchingHostState::new(true).excluding(Some(host)))
     }
 
     /// Create a new Host state
     ///
-    /// Creates liveliness token and queryable for host discovery
+    /// Creates liveliness token and queryable for host discovery, and opens the rooms.
+    /// Without configured rooms a single room without id is opened. The initial state
+    /// is passed to the engine of the first room.
     pub async fn host<F>(
         get_engine: &F,
-        session: &zenoh::Session,
-        prefix: impl Into<KeyExpr<'static>>,
+        transport: &dyn Transport,
+        config: &NodeConfig,
         node_id: &NodeId,
         initial_state: Option<E::State>,
     ) -> Result<Self>
@@ -219,98 +574,220 @@ where
         E: GameEngine,
         F: EngineFactory<E>,
     {
-        let prefix = prefix.into();
-
-        // Create channels for engine communication
-        let (input_tx, input_rx) = flume::unbounded();
-        let (output_tx, output_rx) = flume::unbounded();
-
-        // Create engine with the channels and optional initial state
-        let engine = get_engine(node_id.clone(), input_rx, output_tx, initial_state);
+        let prefix = config.keyexpr_prefix.clone();
+        let rooms = &config.rooms;
 
         // Create host liveliness token for discovery
-        let token =
-            NodeLivelinessToken::declare(session, prefix.clone(), NodeType::Host, node_id.clone())
-                .await?;
+        let token = NodeLivelinessToken::declare(
+            transport,
+            prefix.clone(),
+            NodeType::Host,
+            node_id.clone(),
+            None,
+        )
+        .await?;
+
+        // Draw the id of this hosting session, sent to clients on accept
+        let session = rand::random::<u64>();
 
         // Declare queryable for host discovery
-        let queryable = HostQueryable::declare(session, prefix.clone(), node_id.clone()).await?;
+        let queryable = HostQueryable::declare(
+            transport,
+            prefix.clone(),
+            node_id.clone(),
+            E::handshake_info().with_session(session),
+            config.handshake_qos,
+        )
+        .await?;
 
         // Create liveliness watch for monitoring connected clients
         let client_liveliness_watch = NodeLivelinessWatch::new();
 
-        // Create action subscriber to receive actions from clients
-        let action_subscriber = NodeSubscriber::new(session, prefix.clone(), LinkType::Action, node_id).await?;
+        // Open the rooms, each with its own engine, action subscriber and state publisher
+        let room_ids: Vec<Option<RoomId>> = if rooms.is_empty() {
+            vec![None]
+        } else {
+            rooms.iter().cloned().map(Some).collect()
+        };
+        let mut initial_state = initial_state;
+        let mut host_rooms = Vec::with_capacity(room_ids.len());
+        for (index, room_id) in room_ids.into_iter().enumerate() {
+            // The host plays in the first room
+            let room = HostRoom::open(
+                get_engine,
+                transport,
+                config,
+                node_id,
+                room_id,
+                index == 0,
+                session,
+                initial_state.take(),
+            )
+            .await?;
+            host_rooms.push(room);
+        }
 
-        // Create state publisher to send game state to all clients (using wildcard for receiver)
-        let state_publisher = NodePublisher::new(
-            session,
+        // Declare the chat links shared by all the rooms
+        let chat = HostChat::open(transport, config, node_id, session).await?;
+
+        // Declare the notice link to all the clients: <prefix>/notice/<host_id>/*
+        let notice_publisher = NodePublisher::new(
+            transport,
             prefix.clone(),
-            LinkType::State,
+            LinkType::Notice,
             node_id,
-            None, // Broadcast to all clients
-        ).await?;
+            None,
+            None,
+            config.notice_qos,
+        )
+        .await?
+        .with_session(session);
 
         Ok(NodeStateInternal::Host(HostState {
-            connected_clients: Vec::new(),
-            engine,
-            input_tx,
-            output_rx,
+            rooms: host_rooms,
+            chat,
+            notice_publisher,
             _liveliness_token: Some(token),
             queryable: Some(Arc::new(queryable)),
             client_liveliness_watch,
-            action_subscriber,
-            state_publisher,
+            next_merge_check: None,
+            merge_discovery: None,
+            session,
+            ignored_samples: 0,
+            spoofed_samples: 0,
+            malformed_samples: 0,
+            action_violations: ActionViolations::default(),
+            kicked: HashMap::new(),
+            unconfirmed: HashMap::new(),
         }))
     }
 
     /// Create a new Client state
     ///
-    /// Subscribes to liveliness events for the host and declares a client liveliness token
+    /// Subscribes to liveliness events for the host and declares a client liveliness token.
+    /// If the host assigned a room, the client token and links are scoped to that room.
     pub async fn client(
-        session: &zenoh::Session,
-        prefix: impl Into<KeyExpr<'static>>,
-        host_id: NodeId,
+        transport: &dyn Transport,
+        config: &NodeConfig,
+        connection: HostConnection,
         client_id: NodeId,
-    ) -> Result<Self>
-    where
-        E::Action: zenoh_ext::Serialize,
-    {
-        let prefix = prefix.into();
+    ) -> Result<Self> {
+        let prefix = config.keyexpr_prefix.clone();
+        let HostConnection {
+            host_id,
+            room,
+            slot,
+            session,
+        } = connection;
 
         // Create and subscribe to liveliness events for the host
         let mut liveliness_watch = NodeLivelinessWatch::new();
         liveliness_watch
-            .subscribe(session, prefix.clone(), NodeType::Host, Some(host_id.clone()))
+            .subscribe(transport, prefix.clone(), NodeType::Host, Some(host_id.clone()), None)
             .await?;
 
+        // Advertise the connection to observers before the client token, so that they
+        // learn the host together with the role: <prefix>[/room/<room>]/connection/<client_id>/<host_id>
+        let connection_keyexpr =
+            KeyexprLink::new(prefix.clone(), LinkType::Connection, Some(client_id.clone()), Some(host_id.clone()))
+                .with_room(room.clone());
+        let connection_token = transport.declare_liveliness_token(connection_keyexpr.into()).await?;
+
         // Declare client liveliness token (type: Client) so host can track our presence
-        let liveliness_token =
-            NodeLivelinessToken::declare(session, prefix.clone(), NodeType::Client, client_id.clone()).await?;
+        let liveliness_token = NodeLivelinessToken::declare(
+            transport,
+            prefix.clone(),
+            NodeType::Client,
+            client_id.clone(),
+            room.clone(),
+        )
+        .await?;
 
         // Create publisher for sending actions to the host
         let action_publisher = NodePublisher::new(
-            session,
+            transport,
             prefix.clone(),
             LinkType::Action,
             &client_id,
             Some(&host_id),
-        ).await?;
+            room.clone(),
+            config.action_qos,
+        )
+        .await?
+        .with_session(session);
 
         // Create subscriber for receiving game state from the host
         let state_subscriber = NodeSubscriber::new(
-            session,
-            prefix,
+            transport,
+            prefix.clone(),
             LinkType::State,
             &client_id,
-        ).await?;
+            room.clone(),
+        )
+        .await?
+        .with_sessions();
+
+        // Create subscriber for receiving engine events from the host
+        let event_subscriber = NodeSubscriber::new(
+            transport,
+            prefix.clone(),
+            LinkType::Event,
+            &client_id,
+            room.clone(),
+        )
+        .await?
+        .with_sessions();
+
+        // Create chat links with the host, then ask for the messages sent before we joined
+        let chat_subscriber = NodeSubscriber::new(
+            transport,
+            prefix.clone(),
+            LinkType::Chat,
+            &client_id,
+            None,
+        )
+        .await?
+        .with_sessions();
+        let chat_publisher = NodePublisher::new(
+            transport,
+            prefix.clone(),
+            LinkType::Chat,
+            &client_id,
+            Some(&host_id),
+            None,
+            config.chat_qos,
+        )
+        .await?
+        .with_session(session);
+        chat_publisher.put(&ChatRequest::History).await?;
+
+        // Create subscriber for receiving notices from the host
+        let notice_subscriber = NodeSubscriber::new(
+            transport,
+            prefix,
+            LinkType::Notice,
+            &client_id,
+            None,
+        )
+        .await?
+        .with_sessions();
 
         Ok(NodeStateInternal::Client(ClientState {
             host_id,
+            room,
+            slot,
+            session,
+            ignored_samples: 0,
+            malformed_samples: 0,
             liveliness_watch,
             _liveliness_token: liveliness_token,
+            _connection_token: connection_token,
             action_publisher,
             state_subscriber,
+            event_subscriber,
+            chat_publisher,
+            chat_subscriber,
+            notice_subscriber,
         }))
     }
 }
@@ -319,18 +796,68 @@ impl<E> NodeStateInternal<E>
 where
     E: GameEngine,
 {
+    /// Number of samples ignored in this state for coming from another host, another
+    /// session or an unadmitted node
+    pub(crate) fn ignored_samples(&self) -> u64 {
+        match self {
+            NodeStateInternal::Client(client_state) => client_state.ignored_samples,
+            NodeStateInternal::Host(host_state) => host_state.ignored_samples,
+            NodeStateInternal::SearchingHost(_) | NodeStateInternal::Stop => 0,
+        }
+    }
+
+    /// Number of handshake queries, actions or states dropped in this state for being malformed
+    pub(crate) fn malformed_samples(&self) -> u64 {
+        match self {
+            NodeStateInternal::Client(client_state) => client_state.malformed_samples,
+            NodeStateInternal::Host(host_state) => host_state.malformed_samples,
+            NodeStateInternal::SearchingHost(_) | NodeStateInternal::Stop => 0,
+        }
+    }
+
+    /// Number of actions dropped in this state for coming from another transport
+    /// session than the one their sender was admitted from
+    pub(crate) fn spoofed_samples(&self) -> u64 {
+        match self {
+            NodeStateInternal::Host(host_state) => host_state.spoofed_samples,
+            NodeStateInternal::SearchingHost(_) | NodeStateInternal::Client(_) | NodeStateInternal::Stop => 0,
+        }
+    }
+
+    /// Counters of actions breaking the action limits in this state
+    pub(crate) fn action_violations(&self) -> ActionViolations {
+        match self {
+            NodeStateInternal::Host(host_state) => host_state.action_violations,
+            NodeStateInternal::SearchingHost(_) | NodeStateInternal::Client(_) | NodeStateInternal::Stop => {
+                ActionViolations::default()
+            }
+        }
+    }
+
+    /// Depth of the engine queues of each room in this state (empty when not hosting)
+    pub(crate) fn queue_stats(&self) -> Vec<QueueStats> {
+        match self {
+            NodeStateInternal::Host(host_state) => host_state.rooms.iter().map(HostRoom::queue_stats).collect(),
+            NodeStateInternal::SearchingHost(_) | NodeStateInternal::Client(_) | NodeStateInternal::Stop => Vec::new(),
+        }
+    }
+
     /// Convert internal state to public NodeState
     pub(crate) fn to_node_state(&self) -> NodeState {
         match self {
             NodeStateInternal::SearchingHost(_) => NodeState::SearchingHost,
             NodeStateInternal::Client(client_state) => NodeState::Client {
                 host_id: client_state.host_id.clone(),
+                room: client_state.room.clone(),
+                slot: client_state.slot,
             },
             NodeStateInternal::Host(host_state) => {
                 // Use the centralized is_accepting_clients() method
                 NodeState::Host {
                     is_accepting: host_state.is_accepting_clients(),
-                    connected_clients: host_state.connected_clients.clone(),
+                    connected_clients: host_state.connected_clients(),
+                    players: host_state.rooms[0].players(),
+                    rooms: host_state.room_infos(),
                 }
             }
             NodeStateInternal::Stop => NodeState::Stop,
@@ -353,6 +880,8 @@ mod tests {
         let host_id = NodeId::from_name("test_host".to_string()).unwrap();
         let state = NodeState::Client { 
             host_id,
+            room: None,
+            slot: PlayerSlot::new(1, 1),
         };
         assert_eq!(
             format!("{}", state),
@@ -365,6 +894,8 @@ mod tests {
         let state = NodeState::Host {
             is_accepting: true,
             connected_clients: vec![],
+            players: vec![],
+            rooms: vec![],
         };
         assert_eq!(format!("{}", state), "Host mode (open, no clients)");
     }
@@ -376,7 +907,54 @@ mod tests {
         let state = NodeState::Host {
             is_accepting: true,
             connected_clients: vec![client1, client2],
+            players: vec![],
+            rooms: vec![],
         };
         assert_eq!(format!("{}", state), "Host mode (open, 2 client(s))");
     }
+
+    #[test]
+    fn test_node_state_display_rooms() {
+        let host_id = NodeId::from_name("test_host".to_string()).unwrap();
+        let room = RoomId::new("lobby").unwrap();
+        let state = NodeState::Client {
+            host_id,
+            room: Some(room.clone()),
+            slot: PlayerSlot::new(1, 1),
+        };
+        assert_eq!(
+            format!("{}", state),
+            "Connected as client to host: test_host (room: lobby)"
+        );
+
+        let state = NodeState::Host {
+            is_accepting: false,
+            connected_clients: vec![],
+            players: vec![],
+            rooms: vec![RoomInfo {
+                id: room,
+                connected_clients: vec![],
+                max_clients: Some(2),
+                players: vec![],
+            }],
+        };
+        assert_eq!(format!("{}", state), "Host mode (closed, no clients, 1 room(s))");
+    }
+
+    #[test]
+    fn test_player_slot_teams() {
+        assert_eq!(PlayerSlot::new(0, 2).team, 0);
+        assert_eq!(PlayerSlot::new(3, 2).team, 1);
+        // Engines without teams put everybody in team 0
+        assert_eq!(PlayerSlot::new(5, 1).team, 0);
+        assert_eq!(PlayerSlot::new(5, 0).team, 0);
+        assert_eq!(PlayerSlot::new(3, 2).to_string(), "slot 3 (team 1)");
+    }
+
+    #[test]
+    fn test_room_id_validation() {
+        assert_eq!(RoomId::new("lobby").unwrap().as_str(), "lobby");
+        assert!(matches!(RoomId::new(""), Err(ArenaError::InvalidRoomName(_))));
+        assert!(matches!(RoomId::new("a/b"), Err(ArenaError::InvalidRoomName(_))));
+    }
 }